@@ -4,65 +4,166 @@
 
 mod subscription;
 
-use actix_web::dev::{Payload, PayloadStream};
-use actix_web::{http, web, Error, FromRequest, HttpRequest};
-use async_graphql::http::StreamBody;
-use async_graphql::{IntoQueryBuilder, IntoQueryBuilderOpts, ParseRequestError, QueryBuilder};
+use actix_web::{web, Error, HttpRequest, HttpResponse, Route};
+use async_graphql::http::{handle_opts, handle_sse, Body};
+use async_graphql::{IntoQueryBuilderOpts, ObjectType, QueryBuilder, Schema, SubscriptionType};
 use futures::channel::mpsc;
-use futures::{Future, SinkExt, StreamExt, TryFutureExt};
-use std::pin::Pin;
+use futures::{SinkExt, StreamExt};
+use std::io;
 
 pub use subscription::WSSubscription;
 
-/// Extractor for GraphQL request
+/// GraphQL request handler
 ///
-/// It's a wrapper of `QueryBuilder`, you can use `GQLRequest::into_inner` unwrap it to `QueryBuilder`.
-/// `async_graphql::IntoQueryBuilderOpts` allows to configure extraction process.
-pub struct GQLRequest(QueryBuilder);
-
-impl GQLRequest {
-    /// Unwrap it to `QueryBuilder`.
-    pub fn into_inner(self) -> QueryBuilder {
-        self.0
-    }
+/// The query is read from the body of POST requests, and from the query string of GET requests, mutations are not
+/// allowed over GET. The requests are checked with `IntoQueryBuilderOpts::csrf_guard` first.
+///
+/// The request is handled by `async_graphql::http::handle_opts`, its media type is negotiated with the `Accept` header
+/// of the request. The responses of GET requests have an `ETag`, they are replaced with `304 Not Modified` when it
+/// matches the `If-None-Match` header of the request.
+///
+/// ```rust,ignore
+/// async fn index(schema: web::Data<MySchema>, req: HttpRequest, payload: web::Payload) -> HttpResponse {
+///     async_graphql_actix_web::graphql(req, payload, &schema, |query_builder| query_builder).await
+/// }
+///
+/// App::new().service(web::resource("/").route(web::post().to(index)).route(web::get().to(index)))
+/// ```
+pub async fn graphql<Query, Mutation, Subscription, F>(
+    req: HttpRequest,
+    payload: web::Payload,
+    schema: &Schema<Query, Mutation, Subscription>,
+    query_builder_configuration: F,
+) -> HttpResponse
+where
+    Query: ObjectType + Send + Sync + 'static,
+    Mutation: ObjectType + Send + Sync + 'static,
+    Subscription: SubscriptionType + Send + Sync + 'static,
+    F: Fn(QueryBuilder) -> QueryBuilder,
+{
+    graphql_opts(
+        req,
+        payload,
+        schema,
+        query_builder_configuration,
+        Default::default(),
+    )
+    .await
 }
 
-impl FromRequest for GQLRequest {
-    type Error = Error;
-    type Future = Pin<Box<dyn Future<Output = Result<GQLRequest, Error>>>>;
-    type Config = IntoQueryBuilderOpts;
+/// Similar to graphql, but you can set the options `IntoQueryBuilderOpts`.
+pub async fn graphql_opts<Query, Mutation, Subscription, F>(
+    req: HttpRequest,
+    payload: web::Payload,
+    schema: &Schema<Query, Mutation, Subscription>,
+    query_builder_configuration: F,
+    opts: IntoQueryBuilderOpts,
+) -> HttpResponse
+where
+    Query: ObjectType + Send + Sync + 'static,
+    Mutation: ObjectType + Send + Sync + 'static,
+    Subscription: SubscriptionType + Send + Sync + 'static,
+    F: Fn(QueryBuilder) -> QueryBuilder,
+{
+    into_response(
+        handle_opts(
+            schema,
+            into_http_request(&req, payload),
+            &opts,
+            query_builder_configuration,
+        )
+        .await,
+    )
+}
 
-    fn from_request(req: &HttpRequest, payload: &mut Payload<PayloadStream>) -> Self::Future {
-        let config = req.app_data::<Self::Config>().cloned().unwrap_or_default();
-        let content_type = req
-            .headers()
-            .get(http::header::CONTENT_TYPE)
-            .and_then(|value| value.to_str().ok())
-            .map(|value| value.to_string());
+/// Execute a subscription and stream the results as Server-Sent Events
+///
+/// It accepts the same requests as `graphql`, and the GET requests of an `EventSource`, it's an alternative to
+/// `WSSubscription` for clients that can't use WebSockets. See `async_graphql::http::handle_sse`.
+///
+/// ```rust,ignore
+/// async fn index_sse(schema: web::Data<MySchema>, req: HttpRequest, payload: web::Payload) -> HttpResponse {
+///     async_graphql_actix_web::graphql_subscription_sse(req, payload, &schema, |query_builder| query_builder).await
+/// }
+/// ```
+pub async fn graphql_subscription_sse<Query, Mutation, Subscription, F>(
+    req: HttpRequest,
+    payload: web::Payload,
+    schema: &Schema<Query, Mutation, Subscription>,
+    query_builder_configuration: F,
+) -> HttpResponse
+where
+    Query: ObjectType + Send + Sync + 'static,
+    Mutation: ObjectType + Send + Sync + 'static,
+    Subscription: SubscriptionType + Send + Sync + 'static,
+    F: Fn(QueryBuilder) -> QueryBuilder,
+{
+    into_response(
+        handle_sse(
+            schema,
+            into_http_request(&req, payload),
+            query_builder_configuration,
+        )
+        .await,
+    )
+}
 
-        let (mut tx, rx) = mpsc::channel(16);
+fn into_http_request(req: &HttpRequest, mut payload: web::Payload) -> http::Request<Body> {
+    let (mut tx, rx) = mpsc::channel(16);
 
-        // Because Payload is !Send, so forward it to mpsc::Sender
-        let mut payload = web::Payload(payload.take());
-        actix_rt::spawn(async move {
-            while let Some(item) = payload.next().await {
-                if tx.send(item).await.is_err() {
-                    return;
-                }
+    // Because Payload is !Send, so forward it to mpsc::Sender
+    actix_rt::spawn(async move {
+        while let Some(item) = payload.next().await {
+            let item = item.map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()));
+            if tx.send(item).await.is_err() {
+                return;
             }
-        });
+        }
+    });
 
-        Box::pin(async move {
-            (content_type, StreamBody::new(rx))
-                .into_query_builder_opts(&config)
-                .map_ok(GQLRequest)
-                .map_err(|err| match err {
-                    ParseRequestError::TooManyFiles | ParseRequestError::TooLarge => {
-                        actix_web::error::ErrorPayloadTooLarge(err)
-                    }
-                    _ => actix_web::error::ErrorBadRequest(err),
-                })
-                .await
-        })
+    let mut http_req = http::Request::new(Body::from_stream(rx));
+    *http_req.method_mut() = req.method().clone();
+    *http_req.uri_mut() = req.uri().clone();
+    *http_req.headers_mut() = req
+        .headers()
+        .iter()
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+    http_req
+}
+
+fn into_response(resp: http::Response<Body>) -> HttpResponse {
+    let (parts, body) = resp.into_parts();
+    let mut res = HttpResponse::build(parts.status);
+    for (name, value) in parts.headers.iter() {
+        res.header(name.clone(), value.clone());
     }
+    match body {
+        Body::Full(bytes) => res.body(bytes),
+        Body::Stream(s) => res.streaming(s),
+    }
+}
+
+/// A route that serves a GraphQL IDE page
+///
+/// The page is generated with `async_graphql::http::IdeConfig`.
+///
+/// ```rust,ignore
+/// use async_graphql::http::IdeConfig;
+///
+/// App::new().service(web::resource("/graphiql").route(async_graphql_actix_web::graphql_ide(
+///     IdeConfig::new("/").subscription_endpoint("/").graphiql_source(),
+/// )))
+/// ```
+pub fn graphql_ide(source: String) -> Route {
+    web::get().to(move || {
+        let source = source.clone();
+        async move {
+            Ok::<_, Error>(
+                HttpResponse::Ok()
+                    .content_type("text/html; charset=utf-8")
+                    .body(source),
+            )
+        }
+    })
 }
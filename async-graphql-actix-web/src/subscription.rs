@@ -1,22 +1,30 @@
 use actix::{
     Actor, ActorContext, ActorFuture, AsyncContext, ContextFutureSpawner, StreamHandler, WrapFuture,
 };
-use actix_web_actors::ws::{Message, ProtocolError, WebsocketContext};
-use async_graphql::{Data, FieldResult, ObjectType, Schema, SubscriptionType, WebSocketTransport};
-use bytes::Bytes;
+use actix_web::{web, HttpRequest, HttpResponse};
+use actix_web_actors::ws::{self, CloseReason, Message, ProtocolError, WebsocketContext};
+use async_graphql::http::{serve_websocket, WebSocketMessage};
+use async_graphql::{
+    Data, FieldResult, GraphQLTransportWS, ObjectType, Schema, SubscriptionType, WebSocketHooks,
+    WebSocketProtocols, WebSocketTransport,
+};
 use futures::channel::mpsc;
 use futures::SinkExt;
 use std::time::{Duration, Instant};
 
 const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
 const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Actor for subscription via websocket
 pub struct WSSubscription<Query, Mutation, Subscription> {
     schema: Schema<Query, Mutation, Subscription>,
     hb: Instant,
-    sink: Option<mpsc::Sender<Bytes>>,
-    init_context_data: Option<Box<dyn Fn(serde_json::Value) -> FieldResult<Data> + Send + Sync>>,
+    sink: Option<mpsc::Sender<WebSocketMessage>>,
+    protocol: WebSocketProtocols,
+    keep_alive_interval: Option<Duration>,
+    connection_init_timeout: Option<Duration>,
+    hooks: WebSocketHooks,
 }
 
 impl<Query, Mutation, Subscription> WSSubscription<Query, Mutation, Subscription>
@@ -31,7 +39,10 @@ where
             schema: schema.clone(),
             hb: Instant::now(),
             sink: None,
-            init_context_data: None,
+            protocol: WebSocketProtocols::SubscriptionsTransportWS,
+            keep_alive_interval: Some(Duration::from_secs(10)),
+            connection_init_timeout: Some(Duration::from_secs(10)),
+            hooks: Default::default(),
         }
     }
 
@@ -40,20 +51,73 @@ where
     where
         F: Fn(serde_json::Value) -> FieldResult<Data> + Send + Sync + 'static,
     {
+        self.hooks(WebSocketHooks::from_init_context_data(f))
+    }
+
+    /// Set the lifecycle hooks of the connection, they replace the `init_context_data` function.
+    pub fn hooks(self, hooks: WebSocketHooks) -> Self {
+        Self { hooks, ..self }
+    }
+
+    /// Set the interval of the keep-alive messages of the subscriptions-transport-ws protocol, `None` disables them.
+    /// The default is 10 seconds.
+    pub fn keep_alive_interval(self, interval: Option<Duration>) -> Self {
+        Self {
+            keep_alive_interval: interval,
+            ..self
+        }
+    }
+
+    /// Set how long to wait for the `connection_init` message, `None` waits forever. The default is 10 seconds.
+    pub fn connection_init_timeout(self, timeout: Option<Duration>) -> Self {
         Self {
-            init_context_data: Some(Box::new(f)),
+            connection_init_timeout: timeout,
             ..self
         }
     }
 
+    /// Set the protocol of the connection, the default is `WebSocketProtocols::SubscriptionsTransportWS`.
+    ///
+    /// Use `WSSubscription::start` to negotiate it with the client instead.
+    pub fn protocol(self, protocol: WebSocketProtocols) -> Self {
+        Self { protocol, ..self }
+    }
+
+    /// Start the websocket connection, negotiating the protocol with the `Sec-WebSocket-Protocol` header of the request.
+    pub fn start(
+        self,
+        req: &HttpRequest,
+        payload: web::Payload,
+    ) -> Result<HttpResponse, actix_web::Error> {
+        let protocol = WebSocketProtocols::negotiate(
+            req.headers()
+                .get("sec-websocket-protocol")
+                .and_then(|value| value.to_str().ok()),
+        );
+        ws::start_with_protocols(
+            self.protocol(protocol),
+            &[protocol.sec_websocket_protocol()],
+            req,
+            payload,
+        )
+    }
+
     fn hb(&self, ctx: &mut WebsocketContext<Self>) {
         ctx.run_interval(HEARTBEAT_INTERVAL, |act, ctx| {
             if Instant::now().duration_since(act.hb) > CLIENT_TIMEOUT {
-                ctx.stop();
+                act.close(ctx);
             }
             ctx.ping(b"");
         });
     }
+
+    /// Close the subscription connection, the actor stops when `serve_websocket` returns after the
+    /// `on_disconnect` hook, or at the second call.
+    fn close(&mut self, ctx: &mut WebsocketContext<Self>) {
+        if self.sink.take().is_none() {
+            ctx.stop();
+        }
+    }
 }
 
 impl<Query, Mutation, Subscription> Actor for WSSubscription<Query, Mutation, Subscription>
@@ -67,14 +131,41 @@ where
     fn started(&mut self, ctx: &mut Self::Context) {
         self.hb(ctx);
         let schema = self.schema.clone();
-        let (sink, stream) = schema.subscription_connection(
-            if let Some(init_with_payload) = self.init_context_data.take() {
-                WebSocketTransport::new(init_with_payload)
-            } else {
-                WebSocketTransport::default()
-            },
-        );
+        let hooks = self.hooks.clone();
+        let (mut tick_tx, ticks) = mpsc::channel(1);
+        ctx.run_interval(TICK_INTERVAL, move |_, _| {
+            let _ = tick_tx.try_send(());
+        });
+
+        // The connection is served by `serve_websocket`, the actor forwards the messages from and to the socket.
+        let (sink, rx) = mpsc::channel(8);
+        let (tx, stream) = mpsc::channel::<WebSocketMessage>(8);
         ctx.add_stream(stream);
+        match self.protocol {
+            WebSocketProtocols::SubscriptionsTransportWS => {
+                actix_rt::spawn(serve_websocket(
+                    schema,
+                    WebSocketTransport::default()
+                        .hooks(hooks)
+                        .keep_alive_interval(self.keep_alive_interval)
+                        .connection_init_timeout(self.connection_init_timeout),
+                    tx,
+                    rx,
+                    ticks,
+                ));
+            }
+            WebSocketProtocols::GraphQLTransportWS => {
+                actix_rt::spawn(serve_websocket(
+                    schema,
+                    GraphQLTransportWS::default()
+                        .hooks(hooks)
+                        .connection_init_timeout(self.connection_init_timeout),
+                    tx,
+                    rx,
+                    ticks,
+                ));
+            }
+        }
         self.sink = Some(sink);
     }
 }
@@ -89,7 +180,7 @@ where
     fn handle(&mut self, msg: Result<Message, ProtocolError>, ctx: &mut Self::Context) {
         let msg = match msg {
             Err(_) => {
-                ctx.stop();
+                self.close(ctx);
                 return;
             }
             Ok(msg) => msg,
@@ -105,28 +196,38 @@ where
             }
             Message::Text(s) => {
                 if let Some(mut sink) = self.sink.clone() {
-                    async move { sink.send(s.into()).await }
+                    async move { sink.send(WebSocketMessage::Text(s)).await }
                         .into_actor(self)
                         .then(|_, actor, _| async {}.into_actor(actor))
                         .wait(ctx);
                 }
             }
             Message::Binary(_) | Message::Close(_) | Message::Continuation(_) => {
-                ctx.stop();
+                self.close(ctx);
             }
             Message::Nop => {}
         }
     }
+
+    fn finished(&mut self, ctx: &mut Self::Context) {
+        self.close(ctx);
+    }
 }
 
-impl<Query, Mutation, Subscription> StreamHandler<Bytes>
+impl<Query, Mutation, Subscription> StreamHandler<WebSocketMessage>
     for WSSubscription<Query, Mutation, Subscription>
 where
     Query: ObjectType + Send + Sync + 'static,
     Mutation: ObjectType + Send + Sync + 'static,
     Subscription: SubscriptionType + Send + Sync + 'static,
 {
-    fn handle(&mut self, data: Bytes, ctx: &mut Self::Context) {
-        ctx.text(unsafe { std::str::from_utf8_unchecked(&data) });
+    fn handle(&mut self, msg: WebSocketMessage, ctx: &mut Self::Context) {
+        match msg {
+            WebSocketMessage::Text(text) => ctx.text(text),
+            WebSocketMessage::Close(frame) => ctx.close(frame.map(|frame| CloseReason {
+                code: frame.code.into(),
+                description: Some(frame.reason),
+            })),
+        }
     }
 }
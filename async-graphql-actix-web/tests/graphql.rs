@@ -0,0 +1,152 @@
+use actix_web::{test, web, App, HttpRequest, HttpResponse};
+use async_graphql::*;
+use serde_json::json;
+
+struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    async fn add(&self, a: i32, b: i32) -> i32 {
+        a + b
+    }
+}
+
+struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    async fn action(&self) -> bool {
+        true
+    }
+}
+
+type MySchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+async fn index(
+    schema: web::Data<MySchema>,
+    req: HttpRequest,
+    payload: web::Payload,
+) -> HttpResponse {
+    async_graphql_actix_web::graphql(req, payload, &schema, |query_builder| query_builder).await
+}
+
+#[actix_rt::test]
+async fn quickstart() {
+    let mut app = test::init_service(
+        App::new()
+            .data(Schema::new(QueryRoot, MutationRoot, EmptySubscription))
+            .route("/", web::post().to(index))
+            .route("/", web::get().to(index)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/")
+        .header("content-type", "application/json")
+        .set_payload(r#"{"query":"{ add(a: 10, b: 20) }"}"#)
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers().get("content-type").unwrap(),
+        "application/json"
+    );
+    let body = test::read_body(resp).await;
+    assert_eq!(
+        serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+        json!({"data": {"add": 30}})
+    );
+
+    let req = test::TestRequest::post()
+        .uri("/")
+        .set_payload(r#"{"query":"{ add(a: 10, b: 20) }"}"#)
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_rt::test]
+async fn get() {
+    let mut app = test::init_service(
+        App::new()
+            .data(Schema::new(QueryRoot, MutationRoot, EmptySubscription))
+            .route("/", web::post().to(index))
+            .route("/", web::get().to(index)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .header("graphql-preflight", "1")
+        .uri("/?query=%7B%20add(a%3A%2010%2C%20b%3A%2020)%20%7D")
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body = test::read_body(resp).await;
+    assert_eq!(
+        serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+        json!({"data": {"add": 30}})
+    );
+
+    let req = test::TestRequest::get()
+        .header("graphql-preflight", "1")
+        .uri("/?query=mutation%20%7B%20action%20%7D")
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), 405);
+    assert_eq!(resp.headers().get("allow").unwrap(), "POST");
+
+    let req = test::TestRequest::get()
+        .uri("/?query=%7B%20add(a%3A%2010%2C%20b%3A%2020)%20%7D")
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_rt::test]
+async fn accept() {
+    let mut app = test::init_service(
+        App::new()
+            .data(Schema::new(QueryRoot, MutationRoot, EmptySubscription))
+            .route("/", web::post().to(index)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/")
+        .header("content-type", "application/json")
+        .header("accept", "application/graphql-response+json")
+        .set_payload(r#"{"query":"{ add(a: 10) }"}"#)
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), 400);
+    assert_eq!(
+        resp.headers().get("content-type").unwrap(),
+        "application/graphql-response+json"
+    );
+}
+
+#[actix_rt::test]
+async fn etag() {
+    let mut app = test::init_service(
+        App::new()
+            .data(Schema::new(QueryRoot, MutationRoot, EmptySubscription))
+            .route("/", web::get().to(index)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .header("graphql-preflight", "1")
+        .uri("/?query=%7B%20add(a%3A%2010%2C%20b%3A%2020)%20%7D")
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), 200);
+    let etag = resp.headers().get("etag").unwrap().clone();
+
+    let req = test::TestRequest::get()
+        .header("graphql-preflight", "1")
+        .uri("/?query=%7B%20add(a%3A%2010%2C%20b%3A%2020)%20%7D")
+        .header("if-none-match", etag)
+        .to_request();
+    let resp = test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), 304);
+}
@@ -909,3 +909,27 @@ impl Entity {
         Ok(None)
     }
 }
+
+#[derive(Debug)]
+pub struct Node {
+    pub guard: Option<TokenStream>,
+}
+
+impl Node {
+    pub fn parse(crate_name: &TokenStream, attrs: &[Attribute]) -> Result<Option<Self>> {
+        for attr in attrs {
+            match attr.parse_meta()? {
+                Meta::List(ls) if ls.path.is_ident("node") => {
+                    let guard = parse_guards(crate_name, &ls)?;
+                    return Ok(Some(Self { guard }));
+                }
+                Meta::Path(p) if p.is_ident("node") => {
+                    return Ok(Some(Self { guard: None }));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(None)
+    }
+}
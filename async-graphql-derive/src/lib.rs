@@ -17,7 +17,7 @@ use crate::utils::get_crate_name;
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::parse_macro_input;
-use syn::{AttributeArgs, DeriveInput, ItemImpl};
+use syn::{AttributeArgs, DeriveInput, ImplItem, ItemImpl};
 
 #[proc_macro_attribute]
 #[allow(non_snake_case)]
@@ -124,8 +124,18 @@ pub fn DataSource(args: TokenStream, input: TokenStream) -> TokenStream {
         Ok(datasource_args) => datasource_args,
         Err(err) => return err.to_compile_error().into(),
     };
-    let item_impl = parse_macro_input!(input as ItemImpl);
+    let mut item_impl = parse_macro_input!(input as ItemImpl);
     let crate_name = get_crate_name(datasource_args.internal);
+    let has_cursor_type = item_impl.items.iter().any(|item| match item {
+        ImplItem::Type(ty) => ty.ident == "CursorType",
+        _ => false,
+    });
+    if !has_cursor_type {
+        item_impl.items.insert(
+            0,
+            syn::parse2::<ImplItem>(quote! { type CursorType = #crate_name::Cursor; }).unwrap(),
+        );
+    }
     let expanded = quote! {
         #[#crate_name::async_trait::async_trait]
         #item_impl
@@ -4,7 +4,10 @@ use crate::utils::{build_value_repr, check_reserved_name, get_crate_name};
 use inflector::Inflector;
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{Block, Error, FnArg, ImplItem, ItemImpl, Pat, Result, ReturnType, Type, TypeReference};
+use syn::{
+    Block, Error, FnArg, GenericArgument, ImplItem, ItemImpl, Pat, PathArguments, Result,
+    ReturnType, Type, TypeReference,
+};
 
 pub fn generate(object_args: &args::Object, item_impl: &mut ItemImpl) -> Result<TokenStream> {
     let crate_name = get_crate_name(object_args.internal);
@@ -40,6 +43,8 @@ pub fn generate(object_args: &args::Object, item_impl: &mut ItemImpl) -> Result<
     let mut find_entities = Vec::new();
     let mut add_keys = Vec::new();
     let mut create_entity_types = Vec::new();
+    let mut find_nodes = Vec::new();
+    let mut add_nodes = Vec::new();
 
     for item in &mut item_impl.items {
         if let ImplItem::Method(method) = item {
@@ -180,6 +185,117 @@ pub fn generate(object_args: &args::Object, item_impl: &mut ItemImpl) -> Result<
                         .map(|(idx, _)| idx)
                         .unwrap(),
                 );
+            } else if let Some(node) = args::Node::parse(&crate_name, &method.attrs)? {
+                let ty = match &method.sig.output {
+                    ReturnType::Type(_, ty) => OutputType::parse(ty)?,
+                    ReturnType::Default => {
+                        return Err(Error::new_spanned(&method.sig.output, "Missing type"))
+                    }
+                };
+                let mut create_ctx = true;
+                let mut has_id = false;
+
+                for (idx, arg) in method.sig.inputs.iter_mut().enumerate() {
+                    if let FnArg::Receiver(receiver) = arg {
+                        if idx != 0 {
+                            return Err(Error::new_spanned(
+                                receiver,
+                                "The self receiver must be the first parameter.",
+                            ));
+                        }
+                    } else if let FnArg::Typed(pat) = arg {
+                        if idx == 0 {
+                            return Err(Error::new_spanned(
+                                pat,
+                                "The self receiver must be the first parameter.",
+                            ));
+                        }
+
+                        match (&*pat.pat, &*pat.ty) {
+                            (Pat::Ident(_), Type::Path(_)) if !has_id => {
+                                has_id = true;
+                            }
+                            (arg, Type::Reference(TypeReference { elem, .. })) => {
+                                if let Type::Path(path) = elem.as_ref() {
+                                    if idx != 1
+                                        || path.path.segments.last().unwrap().ident != "Context"
+                                    {
+                                        return Err(Error::new_spanned(
+                                            arg,
+                                            "The Context must be the second argument.",
+                                        ));
+                                    } else {
+                                        create_ctx = false;
+                                    }
+                                }
+                            }
+                            (arg, _) => {
+                                return Err(Error::new_spanned(
+                                    arg,
+                                    "The node loader must have only one id argument.",
+                                ))
+                            }
+                        }
+                    }
+                }
+
+                if !has_id {
+                    return Err(Error::new_spanned(
+                        &method.sig,
+                        "The node loader must have an id argument.",
+                    ));
+                }
+
+                if create_ctx {
+                    let arg =
+                        syn::parse2::<FnArg>(quote! { _: &#crate_name::Context<'_> }).unwrap();
+                    method.sig.inputs.insert(1, arg);
+                }
+
+                let node_type = optional_inner_type(ty.value_type());
+                add_nodes.push(quote! {
+                    registry.add_node(#gql_typename, &<#node_type as #crate_name::Type>::type_name());
+                    <#node_type as #crate_name::Type>::create_type_info(registry);
+                });
+
+                let field_ident = &method.sig.ident;
+                if let OutputType::Value(inner_ty) = &ty {
+                    let block = &method.block;
+                    let new_block = quote!({
+                        {
+                            let value:#inner_ty = async move #block.await;
+                            Ok(value)
+                        }
+                    });
+                    method.block = syn::parse2::<Block>(new_block).expect("invalid block");
+                    method.sig.output = syn::parse2::<ReturnType>(
+                        quote! { -> #crate_name::FieldResult<#inner_ty> },
+                    )
+                    .expect("invalid result type");
+                }
+                let do_find = quote! { self.#field_ident(ctx, id.into()).await.map_err(|err| err.into_error(pos))? };
+
+                let guard = node.guard.map(
+                    |guard| quote! { #guard.check(ctx).await.map_err(|err| err.into_error(pos))?; },
+                );
+
+                find_nodes.push(quote! {
+                    if type_name == <#node_type as #crate_name::Type>::type_name() {
+                        #guard
+                        let ctx_obj = ctx.with_selection_set(&ctx.selection_set);
+                        return #crate_name::OutputValueType::resolve(&#do_find, &ctx_obj, pos).await;
+                    }
+                });
+
+                method.attrs.remove(
+                    method
+                        .attrs
+                        .iter()
+                        .enumerate()
+                        .find(|(_, a)| a.path.is_ident("node"))
+                        .map(|(idx, _)| idx)
+                        .unwrap(),
+                );
             } else if let Some(field) = args::Field::parse(&crate_name, &method.attrs)? {
                 if method.sig.asyncness.is_none() {
                     return Err(Error::new_spanned(&method, "Must be asynchronous"));
@@ -448,6 +564,7 @@ pub fn generate(object_args: &args::Object, item_impl: &mut ItemImpl) -> Result<
                 });
                 #(#create_entity_types)*
                 #(#add_keys)*
+                #(#add_nodes)*
                 ty
             }
         }
@@ -475,6 +592,11 @@ pub fn generate(object_args: &args::Object, item_impl: &mut ItemImpl) -> Result<
                 #(#find_entities_iter)*
                 Err(#crate_name::QueryError::EntityNotFound.into_error(pos))
             }
+
+            async fn find_node(&self, ctx: &#crate_name::Context<'_>, pos: #crate_name::Pos, type_name: &str, id: #crate_name::ID) -> #crate_name::Result<#crate_name::serde_json::Value> {
+                #(#find_nodes)*
+                Ok(#crate_name::serde_json::Value::Null)
+            }
         }
 
         #[#crate_name::async_trait::async_trait]
@@ -486,3 +608,18 @@ pub fn generate(object_args: &args::Object, item_impl: &mut ItemImpl) -> Result<
     };
     Ok(expanded.into())
 }
+
+fn optional_inner_type(ty: Type) -> Type {
+    if let Type::Path(path) = &ty {
+        if let Some(segment) = path.path.segments.last() {
+            if segment.ident == "Option" {
+                if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                    if let Some(GenericArgument::Type(inner_ty)) = args.args.first() {
+                        return inner_ty.clone();
+                    }
+                }
+            }
+        }
+    }
+    ty
+}
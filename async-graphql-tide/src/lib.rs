@@ -4,14 +4,24 @@
 #![allow(clippy::type_complexity)]
 #![allow(clippy::needless_doctest_main)]
 
-use async_graphql::http::GQLResponse;
-use async_graphql::{
-    IntoQueryBuilder, IntoQueryBuilderOpts, ObjectType, QueryBuilder, Schema, SubscriptionType,
-};
-use tide::{http::headers, Request, Response, Status, StatusCode};
+use async_graphql::http::{handle_opts, handle_sse, Body};
+use async_graphql::{IntoQueryBuilderOpts, ObjectType, QueryBuilder, Schema, SubscriptionType};
+use futures::channel::mpsc;
+use futures::{SinkExt, StreamExt, TryStreamExt};
+use std::convert::TryFrom;
+use tide::http::headers::{self, HeaderName};
+use tide::{Endpoint, Request, Response, StatusCode};
 
 /// GraphQL request handler
 ///
+/// The query is read from the body of POST requests, and from the query string of GET requests, mutations are not
+/// allowed over GET. Register the handler with both `post` and `get` to accept both. The requests are checked with
+/// `IntoQueryBuilderOpts::csrf_guard` first.
+///
+/// The request is handled by `async_graphql::http::handle_opts`, its media type is negotiated with the `Accept` header
+/// of the request. The responses of GET requests have an `ETag`, they are replaced with `304 Not Modified` when it
+/// matches the `If-None-Match` header of the request. The headers set by the resolvers with
+/// `Context::insert_http_header`, `Context::append_http_header` and `Context::set_cookie` are added to the response.
 ///
 /// # Examples
 /// *[Full Example](<https://github.com/async-graphql/examples/blob/master/tide/starwars/src/main.rs>)*
@@ -72,22 +82,127 @@ where
     TideState: Send + Sync + 'static,
     F: Fn(QueryBuilder) -> QueryBuilder,
 {
-    let content_type = req
-        .header(&headers::CONTENT_TYPE)
-        .and_then(|values| values.first().map(|value| value.to_string()));
-
-    let mut query_builder = (content_type, req)
-        .into_query_builder_opts(&opts)
-        .await
-        .status(StatusCode::BadRequest)?;
+    into_response(
+        handle_opts(
+            &schema,
+            into_http_request(req),
+            &opts,
+            query_builder_configuration,
+        )
+        .await,
+    )
+}
 
-    query_builder = query_builder_configuration(query_builder);
+/// GraphQL subscription handler over Server-Sent Events
+///
+/// It accepts the same requests as `graphql`, and the GET requests of an `EventSource`, executes the subscription and
+/// streams the results as `text/event-stream`, for clients that can't use WebSockets. See
+/// `async_graphql::http::handle_sse`.
+///
+/// ```no_run
+/// # use async_graphql::*;
+/// # use tide::Request;
+/// # struct QueryRoot;
+/// # #[Object]
+/// # impl QueryRoot {}
+/// # struct SubscriptionRoot;
+/// # #[Subscription]
+/// # impl SubscriptionRoot {
+/// #     async fn values(&self) -> impl futures::Stream<Item = i32> {
+/// #         futures::stream::iter(0..10)
+/// #     }
+/// # }
+/// let mut app = tide::new();
+/// app.at("/sse").post(|req: Request<()>| async move {
+///     let schema = Schema::new(QueryRoot, EmptyMutation, SubscriptionRoot);
+///     async_graphql_tide::graphql_subscription_sse(req, schema, |query_builder| query_builder).await
+/// });
+/// ```
+pub async fn graphql_subscription_sse<Query, Mutation, Subscription, TideState, F>(
+    req: Request<TideState>,
+    schema: Schema<Query, Mutation, Subscription>,
+    query_builder_configuration: F,
+) -> tide::Result<Response>
+where
+    Query: ObjectType + Send + Sync + 'static,
+    Mutation: ObjectType + Send + Sync + 'static,
+    Subscription: SubscriptionType + Send + Sync + 'static,
+    TideState: Send + Sync + 'static,
+    F: Fn(QueryBuilder) -> QueryBuilder,
+{
+    into_response(handle_sse(&schema, into_http_request(req), query_builder_configuration).await)
+}
 
-    let query_response = query_builder.execute(&schema).await;
+/// An endpoint that serves a GraphQL IDE page
+///
+/// The page is generated with `async_graphql::http::IdeConfig`.
+///
+/// ```no_run
+/// use async_graphql::http::IdeConfig;
+///
+/// let mut app = tide::new();
+/// app.at("/graphiql").get(async_graphql_tide::graphql_ide(
+///     IdeConfig::new("/").graphiql_source(),
+/// ));
+/// ```
+pub fn graphql_ide<TideState: Send + Sync + 'static>(source: String) -> impl Endpoint<TideState> {
+    move |_req: Request<TideState>| {
+        let source = source.clone();
+        async move {
+            Ok(Response::new(StatusCode::Ok)
+                .body_string(source)
+                .set_header(headers::CONTENT_TYPE, "text/html; charset=utf-8"))
+        }
+    }
+}
 
-    let gql_response = GQLResponse(query_response);
+fn into_http_request<TideState: Send + Sync + 'static>(
+    req: Request<TideState>,
+) -> http::Request<Body> {
+    let method = req.method().to_string().parse().unwrap_or_default();
+    let uri = req.uri().as_str().parse().unwrap_or_default();
+    let headers = req
+        .iter()
+        .flat_map(|(name, values)| {
+            values.iter().filter_map(move |value| {
+                Some((name.as_str().parse().ok()?, value.as_str().parse().ok()?))
+            })
+        })
+        .collect();
 
-    let resp = Response::new(StatusCode::Ok).body_json(&gql_response)?;
+    let mut http_req = http::Request::new(Body::from_reader(req));
+    *http_req.method_mut() = method;
+    *http_req.uri_mut() = uri;
+    *http_req.headers_mut() = headers;
+    http_req
+}
 
+fn into_response(resp: http::Response<Body>) -> tide::Result<Response> {
+    let (parts, body) = resp.into_parts();
+    let status = StatusCode::try_from(parts.status.as_u16())?;
+    let mut resp = match body {
+        Body::Full(bytes) => Response::new(status).body_string(String::from_utf8(bytes.to_vec())?),
+        Body::Stream(mut s) => {
+            // Because the body must be Sync, forward the chunks to mpsc::Sender
+            let (mut tx, rx) = mpsc::channel(16);
+            async_std::task::spawn(async move {
+                while let Some(chunk) = s.next().await {
+                    if tx.send(chunk).await.is_err() {
+                        return;
+                    }
+                }
+            });
+            Response::new(status).body(rx.into_async_read())
+        }
+    };
+    for (name, value) in parts.headers.iter() {
+        if let (Ok(name), Ok(value)) = (name.as_str().parse::<HeaderName>(), value.to_str()) {
+            resp = if name == headers::CONTENT_TYPE {
+                resp.set_header(name, value)
+            } else {
+                resp.append_header(name, value)
+            };
+        }
+    }
     Ok(resp)
 }
@@ -1,4 +1,5 @@
 mod test_utils;
+use futures::AsyncReadExt;
 use serde_json::json;
 use smol::{Task, Timer};
 use std::time::Duration;
@@ -59,6 +60,112 @@ fn quickstart() -> Result<()> {
     })
 }
 
+#[test]
+fn get() -> Result<()> {
+    smol::run(async {
+        let listen_addr = test_utils::find_listen_addr().await;
+
+        let server = Task::<Result<()>>::spawn(async move {
+            struct QueryRoot;
+            #[Object]
+            impl QueryRoot {
+                async fn add(&self, a: i32, b: i32) -> i32 {
+                    a + b
+                }
+            }
+
+            struct MutationRoot;
+            #[Object]
+            impl MutationRoot {
+                async fn action(&self) -> bool {
+                    true
+                }
+            }
+
+            let mut app = tide::new();
+            app.at("/").get(|req: Request<()>| async move {
+                let schema = Schema::build(QueryRoot, MutationRoot, EmptySubscription).finish();
+                async_graphql_tide::graphql(req, schema, |query_builder| query_builder).await
+            });
+            app.listen(&listen_addr).await?;
+
+            Ok(())
+        });
+
+        let client = Task::<Result<()>>::spawn(async move {
+            Timer::after(Duration::from_millis(300)).await;
+
+            let resp = reqwest::Client::new()
+                .get(format!("http://{}", listen_addr).as_str())
+                .header("graphql-preflight", "1")
+                .query(&[
+                    ("query", "query Add($b: Int!) { add(a: 10, b: $b) }"),
+                    ("variables", r#"{"b": 20}"#),
+                ])
+                .send()
+                .await?;
+
+            assert_eq!(resp.status(), reqwest::StatusCode::OK);
+            let string = resp.text().await?;
+            assert_eq!(string, json!({"data": {"add": 30}}).to_string());
+
+            let resp = reqwest::Client::new()
+                .get(format!("http://{}", listen_addr).as_str())
+                .query(&[("query", "{ add(a: 10, b: 20) }")])
+                .send()
+                .await?;
+            assert_eq!(resp.status(), reqwest::StatusCode::BAD_REQUEST);
+
+            let resp = reqwest::Client::new()
+                .get(format!("http://{}", listen_addr).as_str())
+                .header("graphql-preflight", "1")
+                .query(&[("query", "mutation { action }")])
+                .send()
+                .await?;
+
+            assert_eq!(resp.status(), reqwest::StatusCode::METHOD_NOT_ALLOWED);
+            assert_eq!(resp.headers()[reqwest::header::ALLOW], "POST");
+            let value: serde_json::Value = serde_json::from_str(&resp.text().await?)?;
+            assert_eq!(
+                value["errors"][0]["message"],
+                json!("Mutations are not allowed in this request.")
+            );
+
+            let resp = reqwest::Client::new()
+                .get(format!("http://{}", listen_addr).as_str())
+                .header("graphql-preflight", "1")
+                .query(&[("query", "{ add(a: 10) }")])
+                .header(reqwest::header::ACCEPT, "application/json")
+                .send()
+                .await?;
+            assert_eq!(resp.status(), reqwest::StatusCode::OK);
+
+            let resp = reqwest::Client::new()
+                .get(format!("http://{}", listen_addr).as_str())
+                .header("graphql-preflight", "1")
+                .query(&[("query", "{ add(a: 10) }")])
+                .header(
+                    reqwest::header::ACCEPT,
+                    "application/graphql-response+json, application/json",
+                )
+                .send()
+                .await?;
+            assert_eq!(resp.status(), reqwest::StatusCode::BAD_REQUEST);
+            assert_eq!(
+                resp.headers()[reqwest::header::CONTENT_TYPE],
+                "application/graphql-response+json"
+            );
+
+            Ok(())
+        });
+
+        client.await?;
+        server.cancel().await;
+
+        Ok(())
+    })
+}
+
 #[test]
 fn hello() -> Result<()> {
     smol::run(async {
@@ -166,23 +273,23 @@ fn upload() -> Result<()> {
             struct MutationRoot;
             #[Object]
             impl MutationRoot {
-                async fn single_upload(&self, file: Upload) -> FileInfo {
+                async fn single_upload(
+                    &self,
+                    ctx: &Context<'_>,
+                    file: Upload,
+                ) -> FieldResult<FileInfo> {
+                    let file = file.value(ctx)?;
                     println!("single_upload: filename={}", file.filename);
                     println!("single_upload: content_type={:?}", file.content_type);
-                    println!("single_upload: path={:?}", file.path);
 
-                    let file_path = file.path.clone();
-                    let content = Task::blocking(async move { std::fs::read_to_string(file_path) })
-                        .await
-                        .ok();
-                    assert_eq!(content, Some("test\r\n".to_owned()));
+                    let mut content = String::new();
+                    file.reader()?.read_to_string(&mut content).await?;
+                    assert_eq!(content, "test\r\n");
 
-                    let file_info = FileInfo {
-                        filename: file.filename,
-                        mime_type: file.content_type,
-                    };
-
-                    file_info
+                    Ok(FileInfo {
+                        filename: file.filename.clone(),
+                        mime_type: file.content_type.clone(),
+                    })
                 }
             }
 
@@ -206,6 +313,7 @@ fn upload() -> Result<()> {
 
             let resp = reqwest::Client::new()
                 .post(format!("http://{}", listen_addr).as_str())
+                .header("graphql-preflight", "1")
                 .multipart(form)
                 .send()
                 .await?;
@@ -228,3 +336,141 @@ fn upload() -> Result<()> {
         Ok(())
     })
 }
+
+#[test]
+fn http_headers() -> Result<()> {
+    smol::run(async {
+        let listen_addr = test_utils::find_listen_addr().await;
+
+        let server = Task::<Result<()>>::spawn(async move {
+            struct QueryRoot;
+            #[Object(cache_control(max_age = 60))]
+            impl QueryRoot {
+                async fn user_agent(&self, ctx: &Context<'_>) -> FieldResult<String> {
+                    ctx.insert_http_header("x-custom", "custom")?;
+                    ctx.append_http_header("x-custom", "appended")?;
+                    ctx.set_cookie("session", "abc")?;
+                    ctx.set_cookie("theme", "dark")?;
+                    let metadata = ctx.data::<async_graphql::http::RequestMetadata>();
+                    Ok(metadata
+                        .header("user-agent")
+                        .unwrap_or_default()
+                        .to_string())
+                }
+            }
+
+            let mut app = tide::new();
+            app.at("/").post(|req: Request<()>| async move {
+                let schema = Schema::build(QueryRoot, EmptyMutation, EmptySubscription).finish();
+                async_graphql_tide::graphql(req, schema, |query_builder| query_builder).await
+            });
+            app.listen(&listen_addr).await?;
+
+            Ok(())
+        });
+
+        let client = Task::<Result<()>>::spawn(async move {
+            Timer::after(Duration::from_millis(300)).await;
+
+            let resp = reqwest::Client::new()
+                .post(format!("http://{}", listen_addr).as_str())
+                .body(r#"{"query":"{ userAgent }"}"#)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(reqwest::header::USER_AGENT, "test-agent")
+                .send()
+                .await?;
+
+            assert_eq!(resp.status(), reqwest::StatusCode::OK);
+            let values = |name| {
+                resp.headers()
+                    .get_all(name)
+                    .iter()
+                    .map(|value| value.to_str().unwrap().to_string())
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(values("x-custom"), vec!["custom", "appended"]);
+            assert_eq!(values("set-cookie"), vec!["session=abc", "theme=dark"]);
+            assert_eq!(resp.headers()["cache-control"], "max-age=60");
+            let string = resp.text().await?;
+            println!("{}", string);
+
+            assert_eq!(
+                string,
+                json!({"data": {"userAgent": "test-agent"}}).to_string()
+            );
+
+            Ok(())
+        });
+
+        client.await?;
+        server.cancel().await;
+
+        Ok(())
+    })
+}
+
+#[test]
+fn subscription_sse() -> Result<()> {
+    smol::run(async {
+        let listen_addr = test_utils::find_listen_addr().await;
+
+        let server = Task::<Result<()>>::spawn(async move {
+            struct QueryRoot;
+            #[Object]
+            impl QueryRoot {}
+
+            struct SubscriptionRoot;
+            #[Subscription]
+            impl SubscriptionRoot {
+                async fn values(&self) -> impl futures::Stream<Item = i32> {
+                    futures::stream::iter(0..2)
+                }
+            }
+
+            let mut app = tide::new();
+            app.at("/").post(|req: Request<()>| async move {
+                let schema = Schema::new(QueryRoot, EmptyMutation, SubscriptionRoot);
+                async_graphql_tide::graphql_subscription_sse(req, schema, |query_builder| {
+                    query_builder
+                })
+                .await
+            });
+            app.listen(&listen_addr).await?;
+
+            Ok(())
+        });
+
+        let client = Task::<Result<()>>::spawn(async move {
+            Timer::after(Duration::from_millis(300)).await;
+
+            let resp = reqwest::Client::new()
+                .post(format!("http://{}", listen_addr).as_str())
+                .body(r#"{"query":"subscription { values }"}"#)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send()
+                .await?;
+
+            assert_eq!(resp.status(), reqwest::StatusCode::OK);
+            assert_eq!(
+                resp.headers()[reqwest::header::CONTENT_TYPE],
+                "text/event-stream"
+            );
+            let string = resp.text().await?;
+            println!("{}", string);
+
+            assert_eq!(
+                string,
+                "event: next\ndata: {\"data\":{\"values\":0}}\n\n\
+                 event: next\ndata: {\"data\":{\"values\":1}}\n\n\
+                 event: complete\ndata:\n\n"
+            );
+
+            Ok(())
+        });
+
+        client.await?;
+        server.cancel().await;
+
+        Ok(())
+    })
+}
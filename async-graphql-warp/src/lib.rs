@@ -4,36 +4,32 @@
 #![allow(clippy::type_complexity)]
 #![allow(clippy::needless_doctest_main)]
 
-use async_graphql::http::StreamBody;
+use async_graphql::http::{handle_opts, handle_sse, Body, WebSocketMessage};
 use async_graphql::{
-    Data, FieldResult, IntoQueryBuilder, IntoQueryBuilderOpts, ObjectType, QueryBuilder, Schema,
-    SubscriptionType, WebSocketTransport,
+    Data, FieldResult, GraphQLTransportWS, IntoQueryBuilderOpts, ObjectType, QueryBuilder, Schema,
+    SubscriptionTransport, SubscriptionType, WebSocketHooks, WebSocketProtocols,
+    WebSocketTransport,
 };
-use bytes::Bytes;
-use futures::select;
-use futures::{SinkExt, StreamExt};
+use bytes::{Buf, Bytes};
+use futures::{future, SinkExt, Stream, StreamExt, TryStreamExt};
+use std::convert::Infallible;
 use std::sync::Arc;
-use warp::filters::ws::Message;
+use std::time::Duration;
+use warp::filters::path::FullPath;
+use warp::filters::ws::{Message, WebSocket};
 use warp::filters::BoxedFilter;
-use warp::reject::Reject;
-use warp::{Filter, Rejection, Reply};
-
-/// Bad request error
-///
-/// It's a wrapper of `async_graphql::ParseRequestError`.
-pub struct BadRequest(pub async_graphql::ParseRequestError);
-
-impl std::fmt::Debug for BadRequest {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
-    }
-}
-
-impl Reject for BadRequest {}
+use warp::http::{HeaderMap, Method, Request};
+use warp::reply::Response;
+use warp::{Filter, Reply};
 
 /// GraphQL request filter
 ///
-/// It outputs a tuple containing the `Schema` and `QuertBuilder`.
+/// It accepts POST requests, and GET requests with the query in the query string, mutations are
+/// not allowed over GET. The requests are checked with `IntoQueryBuilderOpts::csrf_guard` first.
+///
+/// The request is handled by `async_graphql::http::handle_opts`, its media type is negotiated with
+/// the `Accept` header of the request. The responses of GET requests have an `ETag`, they are
+/// replaced with `304 Not Modified` when it matches the `If-None-Match` header of the request.
 ///
 /// # Examples
 /// *[Full Example](<https://github.com/async-graphql/examples/blob/master/warp/starwars/src/main.rs>)*
@@ -41,9 +37,6 @@ impl Reject for BadRequest {}
 /// ```no_run
 ///
 /// use async_graphql::*;
-/// use warp::{Filter, Reply};
-/// use std::convert::Infallible;
-/// use async_graphql::http::GQLResponse;
 ///
 /// struct QueryRoot;
 ///
@@ -58,65 +51,156 @@ impl Reject for BadRequest {}
 /// #[tokio::main]
 /// async fn main() {
 ///     let schema = Schema::new(QueryRoot, EmptyMutation, EmptySubscription);
-///     let filter = async_graphql_warp::graphql(schema).and_then(|(schema, builder): (_, QueryBuilder)| async move {
-///         let resp = builder.execute(&schema).await;
-///         Ok::<_, Infallible>(warp::reply::json(&GQLResponse(resp)).into_response())
-///     });
+///     let filter = async_graphql_warp::graphql(schema);
 ///     warp::serve(filter).run(([0, 0, 0, 0], 8000)).await;
 /// }
 /// ```
 pub fn graphql<Query, Mutation, Subscription>(
     schema: Schema<Query, Mutation, Subscription>,
-) -> BoxedFilter<((Schema<Query, Mutation, Subscription>, QueryBuilder),)>
+) -> BoxedFilter<(Response,)>
 where
     Query: ObjectType + Send + Sync + 'static,
     Mutation: ObjectType + Send + Sync + 'static,
     Subscription: SubscriptionType + Send + Sync + 'static,
 {
-    warp::any()
-        .and(warp::post())
-        .and(warp::header::optional::<String>("content-type"))
-        .and(warp::body::stream())
-        .and(warp::any().map(move || schema.clone()))
-        .and_then(|content_type, body, schema| async move {
-            let builder = (content_type, StreamBody::new(body))
-                .into_query_builder()
-                .await
-                .map_err(|err| warp::reject::custom(BadRequest(err)))?;
-            Ok::<_, Rejection>((schema, builder))
-        })
-        .boxed()
+    graphql_opts(schema, |query_builder| query_builder, Default::default())
 }
 
-/// Similar to graphql, but you can set the options `IntoQueryBuilderOpts`.
-pub fn graphql_opts<Query, Mutation, Subscription>(
+/// Similar to graphql, but you can configure the `QueryBuilder` and set the options `IntoQueryBuilderOpts`.
+pub fn graphql_opts<Query, Mutation, Subscription, F>(
     schema: Schema<Query, Mutation, Subscription>,
+    query_builder_configuration: F,
     opts: IntoQueryBuilderOpts,
-) -> BoxedFilter<((Schema<Query, Mutation, Subscription>, QueryBuilder),)>
+) -> BoxedFilter<(Response,)>
 where
     Query: ObjectType + Send + Sync + 'static,
     Mutation: ObjectType + Send + Sync + 'static,
     Subscription: SubscriptionType + Send + Sync + 'static,
+    F: Fn(QueryBuilder) -> QueryBuilder + Send + Sync + 'static,
 {
     let opts = Arc::new(opts);
+    let query_builder_configuration = Arc::new(query_builder_configuration);
+    http_request()
+        .and(warp::any().map(move || schema.clone()))
+        .and(warp::any().map(move || opts.clone()))
+        .and(warp::any().map(move || query_builder_configuration.clone()))
+        .and_then(
+            |req: Request<Body>,
+             schema: Schema<Query, Mutation, Subscription>,
+             opts: Arc<IntoQueryBuilderOpts>,
+             query_builder_configuration: Arc<F>| async move {
+                let resp = handle_opts(&schema, req, &opts, |query_builder| {
+                    query_builder_configuration(query_builder)
+                })
+                .await;
+                Ok::<_, Infallible>(into_response(resp))
+            },
+        )
+        .boxed()
+}
+
+/// Convert the GET and POST requests to `http::Request`, with the full path and query of the request.
+fn http_request() -> BoxedFilter<(Request<Body>,)> {
     warp::any()
-        .and(warp::post())
-        .and(warp::header::optional::<String>("content-type"))
+        .and(warp::get().or(warp::post()).unify())
+        .and(warp::method())
+        .and(warp::path::full())
+        .and(warp::query::raw().or(warp::any().map(String::new)).unify())
+        .and(warp::header::headers_cloned())
         .and(warp::body::stream())
-        .and(warp::any().map(move || opts.clone()))
+        .map(
+            |method: Method, path: FullPath, query: String, headers: HeaderMap, body| {
+                let uri = if query.is_empty() {
+                    path.as_str().parse()
+                } else {
+                    format!("{}?{}", path.as_str(), query).parse()
+                };
+                let mut req = Request::new(into_body(body));
+                *req.method_mut() = method;
+                *req.uri_mut() = uri.unwrap_or_default();
+                *req.headers_mut() = headers;
+                req
+            },
+        )
+        .boxed()
+}
+
+fn into_body<S, B>(s: S) -> Body
+where
+    S: Stream<Item = Result<B, warp::Error>> + Send + 'static,
+    B: Buf,
+{
+    Body::from_stream(s.map_ok(|mut buf| buf.to_bytes()))
+}
+
+/// GraphQL subscription filter over Server-Sent Events
+///
+/// It accepts the same requests as `graphql`, and the GET requests of an `EventSource`, executes the
+/// subscription and streams the results as `text/event-stream`, for clients that can't use WebSockets.
+/// See `async_graphql::http::handle_sse`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use async_graphql::*;
+/// use warp::Filter;
+/// use futures::{Stream, StreamExt};
+/// use std::time::Duration;
+///
+/// struct QueryRoot;
+///
+/// #[Object]
+/// impl QueryRoot {}
+///
+/// struct SubscriptionRoot;
+///
+/// #[Subscription]
+/// impl SubscriptionRoot {
+///     async fn tick(&self) -> impl Stream<Item = String> {
+///         tokio::time::interval(Duration::from_secs(1)).map(|n| format!("{}", n.elapsed().as_secs_f32()))
+///     }
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let schema = Schema::new(QueryRoot, EmptyMutation, SubscriptionRoot);
+///     let filter = warp::path("sse").and(async_graphql_warp::graphql_subscription_sse(schema));
+///     warp::serve(filter).run(([0, 0, 0, 0], 8000)).await;
+/// }
+/// ```
+pub fn graphql_subscription_sse<Query, Mutation, Subscription>(
+    schema: Schema<Query, Mutation, Subscription>,
+) -> BoxedFilter<(impl Reply,)>
+where
+    Query: ObjectType + Send + Sync + 'static,
+    Mutation: ObjectType + Send + Sync + 'static,
+    Subscription: SubscriptionType + Send + Sync + 'static,
+{
+    http_request()
         .and(warp::any().map(move || schema.clone()))
         .and_then(
-            |content_type, body, opts: Arc<IntoQueryBuilderOpts>, schema| async move {
-                let builder = (content_type, StreamBody::new(body))
-                    .into_query_builder_opts(&opts)
-                    .await
-                    .map_err(|err| warp::reject::custom(BadRequest(err)))?;
-                Ok::<_, Rejection>((schema, builder))
+            |req: Request<Body>, schema: Schema<Query, Mutation, Subscription>| async move {
+                let resp = handle_sse(&schema, req, |query_builder| query_builder).await;
+                Ok::<_, Infallible>(into_response(resp))
             },
         )
         .boxed()
 }
 
+fn into_response(resp: warp::http::Response<Body>) -> Response {
+    let (parts, body) = resp.into_parts();
+    let body = match body {
+        Body::Full(bytes) => bytes.into(),
+        Body::Stream(s) => {
+            let s: Box<
+                dyn Stream<Item = Result<Bytes, Box<dyn std::error::Error + Send + Sync>>> + Send,
+            > = Box::new(s.map_err(Into::into));
+            s.into()
+        }
+    };
+    Response::from_parts(parts, body)
+}
+
 /// GraphQL subscription filter
 ///
 /// # Examples
@@ -157,60 +241,15 @@ where
     Mutation: ObjectType + Sync + Send + 'static,
     Subscription: SubscriptionType + Send + Sync + 'static,
 {
-    warp::any()
-        .and(warp::ws())
-        .and(warp::any().map(move || schema.clone()))
-        .map(
-            |ws: warp::ws::Ws, schema: Schema<Query, Mutation, Subscription>| {
-                ws.on_upgrade(move |websocket| {
-                    let (mut tx, rx) = websocket.split();
-                    let (mut stx, srx) =
-                        schema.subscription_connection(WebSocketTransport::default());
-
-                    let mut rx = rx.fuse();
-                    let mut srx = srx.fuse();
-
-                    async move {
-                        loop {
-                            select! {
-                                bytes = srx.next() => {
-                                    if let Some(bytes) = bytes {
-                                        if tx
-                                            .send(Message::text(unsafe {
-                                                String::from_utf8_unchecked(bytes.to_vec())
-                                            }))
-                                            .await
-                                            .is_err()
-                                        {
-                                            return;
-                                        }
-                                    } else {
-                                        return;
-                                    }
-                                }
-                                msg = rx.next() => {
-                                    if let Some(Ok(msg)) = msg {
-                                        if msg.is_text() {
-                                            if stx.send(Bytes::copy_from_slice(msg.as_bytes())).await.is_err() {
-                                                return;
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                })
-            },
-        ).map(|reply| {
-            warp::reply::with_header(reply, "Sec-WebSocket-Protocol", "graphql-ws")
-        })
-        .boxed()
+    graphql_subscription_with_data(schema, |_| Ok(Default::default()))
 }
 
 /// GraphQL subscription filter
 ///
 /// Specifies that a function converts the init payload to data.
+///
+/// The protocol is negotiated with the `Sec-WebSocket-Protocol` header, both `graphql-ws`
+/// (subscriptions-transport-ws) and `graphql-transport-ws` are supported.
 pub fn graphql_subscription_with_data<Query, Mutation, Subscription, F>(
     schema: Schema<Query, Mutation, Subscription>,
     init_context_data: F,
@@ -220,55 +259,153 @@ where
     Mutation: ObjectType + Sync + Send + 'static,
     Subscription: SubscriptionType + Send + Sync + 'static,
     F: Fn(serde_json::Value) -> FieldResult<Data> + Send + Sync + Clone + 'static,
+{
+    graphql_subscription_with_hooks(
+        schema,
+        WebSocketHooks::from_init_context_data(init_context_data),
+    )
+}
+
+/// GraphQL subscription filter
+///
+/// Specifies the lifecycle hooks of the connections, such as an async `on_connect` that validates the init payload.
+///
+/// # Examples
+///
+/// ```no_run
+/// use async_graphql::*;
+/// use warp::Filter;
+/// use futures::Stream;
+///
+/// struct QueryRoot;
+///
+/// #[Object]
+/// impl QueryRoot {}
+///
+/// struct SubscriptionRoot;
+///
+/// #[Subscription]
+/// impl SubscriptionRoot {
+///     async fn values(&self) -> impl Stream<Item = i32> {
+///         futures::stream::iter(0..10)
+///     }
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let schema = Schema::new(QueryRoot, EmptyMutation, SubscriptionRoot);
+///     let hooks = WebSocketHooks::default().on_connect(|payload| async move {
+///         if payload["token"].as_str() != Some("123456") {
+///             return Err("Unauthorized".into());
+///         }
+///         Ok(Data::default())
+///     });
+///     let filter = async_graphql_warp::graphql_subscription_with_hooks(schema, hooks);
+///     warp::serve(filter).run(([0, 0, 0, 0], 8000)).await;
+/// }
+/// ```
+pub fn graphql_subscription_with_hooks<Query, Mutation, Subscription>(
+    schema: Schema<Query, Mutation, Subscription>,
+    hooks: WebSocketHooks,
+) -> BoxedFilter<(impl Reply,)>
+where
+    Query: ObjectType + Sync + Send + 'static,
+    Mutation: ObjectType + Sync + Send + 'static,
+    Subscription: SubscriptionType + Send + Sync + 'static,
 {
     warp::any()
         .and(warp::ws())
+        .and(warp::header::optional::<String>("sec-websocket-protocol"))
         .and(warp::any().map(move || schema.clone()))
-        .and(warp::any().map(move || init_context_data.clone()))
+        .and(warp::any().map(move || hooks.clone()))
         .map(
-            |ws: warp::ws::Ws, schema: Schema<Query, Mutation, Subscription>, init_context_data: F| {
-                ws.on_upgrade(move |websocket| {
-                    let (mut tx, rx) = websocket.split();
-                    let (mut stx, srx) =
-                        schema.subscription_connection(WebSocketTransport::new(init_context_data));
-
-                    let mut rx = rx.fuse();
-                    let mut srx = srx.fuse();
-
-                    async move {
-                        loop {
-                            select! {
-                                bytes = srx.next() => {
-                                    if let Some(bytes) = bytes {
-                                        if tx
-                                            .send(Message::text(unsafe {
-                                                String::from_utf8_unchecked(bytes.to_vec())
-                                            }))
-                                            .await
-                                            .is_err()
-                                        {
-                                            return;
-                                        }
-                                    } else {
-                                        return;
-                                    }
-                                }
-                                msg = rx.next() => {
-                                    if let Some(Ok(msg)) = msg {
-                                        if msg.is_text() {
-                                            if stx.send(Bytes::copy_from_slice(msg.as_bytes())).await.is_err() {
-                                                return;
-                                            }
-                                        }
-                                    }
-                                }
-                            }
+            |ws: warp::ws::Ws,
+             protocol: Option<String>,
+             schema: Schema<Query, Mutation, Subscription>,
+             hooks: WebSocketHooks| {
+                let protocol = WebSocketProtocols::negotiate(protocol.as_deref());
+                let reply = ws.on_upgrade(move |websocket| async move {
+                    match protocol {
+                        WebSocketProtocols::SubscriptionsTransportWS => {
+                            serve_websocket(
+                                websocket,
+                                schema,
+                                WebSocketTransport::default().hooks(hooks),
+                            )
+                            .await
+                        }
+                        WebSocketProtocols::GraphQLTransportWS => {
+                            serve_websocket(
+                                websocket,
+                                schema,
+                                GraphQLTransportWS::default().hooks(hooks),
+                            )
+                            .await
                         }
                     }
-                })
+                });
+                warp::reply::with_header(
+                    reply,
+                    "Sec-WebSocket-Protocol",
+                    protocol.sec_websocket_protocol(),
+                )
             },
-        ).map(|reply| {
-        warp::reply::with_header(reply, "Sec-WebSocket-Protocol", "graphql-ws")
-    })
+        )
+        .boxed()
+}
+
+async fn serve_websocket<Query, Mutation, Subscription, T>(
+    websocket: WebSocket,
+    schema: Schema<Query, Mutation, Subscription>,
+    transport: T,
+) where
+    Query: ObjectType + Sync + Send + 'static,
+    Mutation: ObjectType + Sync + Send + 'static,
+    Subscription: SubscriptionType + Send + Sync + 'static,
+    T: SubscriptionTransport,
+{
+    let (tx, rx) = websocket.split();
+    let tx = tx.with(|msg: WebSocketMessage| {
+        future::ok::<_, warp::Error>(match msg {
+            WebSocketMessage::Text(text) => Message::text(text),
+            WebSocketMessage::Close(Some(frame)) => Message::close_with(frame.code, frame.reason),
+            WebSocketMessage::Close(None) => Message::close(),
+        })
+    });
+    let rx = rx.filter_map(|msg| {
+        future::ready(match msg {
+            Ok(msg) if msg.is_close() => Some(WebSocketMessage::Close(None)),
+            Ok(msg) => msg
+                .to_str()
+                .ok()
+                .map(|text| WebSocketMessage::Text(text.to_string())),
+            Err(_) => Some(WebSocketMessage::Close(None)),
+        })
+    });
+    let ticks = tokio::time::interval(Duration::from_secs(1)).map(|_| ());
+    async_graphql::http::serve_websocket(schema, transport, tx, rx, ticks).await
+}
+
+/// GraphQL IDE page filter
+///
+/// It replies to GET requests with the page generated with `async_graphql::http::IdeConfig`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use async_graphql::http::IdeConfig;
+/// use warp::Filter;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let filter = warp::path("graphiql").and(async_graphql_warp::graphql_ide(
+///         IdeConfig::new("/").subscription_endpoint("/").graphiql_source(),
+///     ));
+///     warp::serve(filter).run(([0, 0, 0, 0], 8000)).await;
+/// }
+/// ```
+pub fn graphql_ide(source: String) -> BoxedFilter<(impl Reply,)> {
+    warp::get()
+        .map(move || warp::reply::html(source.clone()))
         .boxed()
 }
@@ -0,0 +1,243 @@
+use async_graphql::*;
+use serde_json::json;
+
+struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    async fn add(&self, a: i32, b: i32) -> i32 {
+        a + b
+    }
+}
+
+struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    async fn action(&self) -> bool {
+        true
+    }
+}
+
+fn body_json(body: &[u8]) -> serde_json::Value {
+    serde_json::from_slice(body).unwrap()
+}
+
+#[tokio::test]
+async fn quickstart() {
+    let filter =
+        async_graphql_warp::graphql(Schema::new(QueryRoot, MutationRoot, EmptySubscription));
+
+    let resp = warp::test::request()
+        .method("POST")
+        .header("content-type", "application/json")
+        .body(r#"{"query":"{ add(a: 10, b: 20) }"}"#)
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["content-type"], "application/json");
+    assert_eq!(body_json(resp.body()), json!({"data": {"add": 30}}));
+
+    let resp = warp::test::request()
+        .method("POST")
+        .body(r#"{"query":"{ add(a: 10, b: 20) }"}"#)
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test]
+async fn get() {
+    let filter =
+        async_graphql_warp::graphql(Schema::new(QueryRoot, MutationRoot, EmptySubscription));
+
+    let resp = warp::test::request()
+        .method("GET")
+        .header("graphql-preflight", "1")
+        .path("/?query=%7B%20add(a%3A%2010%2C%20b%3A%2020)%20%7D")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(body_json(resp.body()), json!({"data": {"add": 30}}));
+
+    let resp = warp::test::request()
+        .method("GET")
+        .header("graphql-preflight", "1")
+        .path("/?query=mutation%20%7B%20action%20%7D")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), 405);
+    assert_eq!(resp.headers()["allow"], "POST");
+
+    let resp = warp::test::request()
+        .method("GET")
+        .path("/?query=%7B%20add(a%3A%2010%2C%20b%3A%2020)%20%7D")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), 400);
+
+    let resp = warp::test::request()
+        .method("PUT")
+        .path("/?query=%7B%20add(a%3A%2010%2C%20b%3A%2020)%20%7D")
+        .filter(&filter)
+        .await;
+    assert!(resp.is_err());
+}
+
+#[tokio::test]
+async fn accept() {
+    let filter =
+        async_graphql_warp::graphql(Schema::new(QueryRoot, MutationRoot, EmptySubscription));
+
+    let resp = warp::test::request()
+        .method("POST")
+        .header("content-type", "application/json")
+        .header("accept", "application/graphql-response+json")
+        .body(r#"{"query":"{ add(a: 10) }"}"#)
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), 400);
+    assert_eq!(
+        resp.headers()["content-type"],
+        "application/graphql-response+json"
+    );
+
+    let resp = warp::test::request()
+        .method("POST")
+        .header("content-type", "application/json")
+        .header("accept", "application/json")
+        .body(r#"{"query":"{ add(a: 10) }"}"#)
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["content-type"], "application/json");
+}
+
+#[tokio::test]
+async fn etag() {
+    let filter =
+        async_graphql_warp::graphql(Schema::new(QueryRoot, MutationRoot, EmptySubscription));
+
+    let resp = warp::test::request()
+        .method("GET")
+        .header("graphql-preflight", "1")
+        .path("/?query=%7B%20add(a%3A%2010%2C%20b%3A%2020)%20%7D")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), 200);
+    let etag = resp.headers()["etag"].clone();
+
+    let resp = warp::test::request()
+        .method("GET")
+        .header("graphql-preflight", "1")
+        .path("/?query=%7B%20add(a%3A%2010%2C%20b%3A%2020)%20%7D")
+        .header("if-none-match", etag.clone())
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), 304);
+    assert_eq!(resp.headers()["etag"], etag);
+    assert!(resp.body().is_empty());
+
+    let resp = warp::test::request()
+        .method("POST")
+        .header("content-type", "application/json")
+        .header("if-none-match", etag)
+        .body(r#"{"query":"{ add(a: 10, b: 20) }"}"#)
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), 200);
+    assert!(resp.headers().get("etag").is_none());
+}
+
+#[tokio::test]
+async fn query_builder_configuration() {
+    struct Token(String);
+
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {
+        async fn token(&self, ctx: &Context<'_>) -> String {
+            ctx.data::<Token>().0.clone()
+        }
+    }
+
+    let filter = async_graphql_warp::graphql_opts(
+        Schema::new(QueryRoot, EmptyMutation, EmptySubscription),
+        |query_builder| query_builder.data(Token("123456".to_string())),
+        Default::default(),
+    );
+    let resp = warp::test::request()
+        .method("POST")
+        .header("content-type", "application/json")
+        .body(r#"{"query":"{ token }"}"#)
+        .reply(&filter)
+        .await;
+    assert_eq!(body_json(resp.body()), json!({"data": {"token": "123456"}}));
+}
+
+#[tokio::test]
+async fn request_metadata() {
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {
+        async fn uri(&self, ctx: &Context<'_>) -> String {
+            ctx.data::<http::RequestMetadata>().uri.to_string()
+        }
+    }
+
+    let filter =
+        async_graphql_warp::graphql(Schema::new(QueryRoot, EmptyMutation, EmptySubscription));
+    let resp = warp::test::request()
+        .method("GET")
+        .header("graphql-preflight", "1")
+        .path("/graphql?query=%7B%20uri%20%7D&x=1")
+        .reply(&filter)
+        .await;
+    assert_eq!(
+        body_json(resp.body()),
+        json!({"data": {"uri": "/graphql?query=%7B%20uri%20%7D&x=1"}})
+    );
+}
+
+#[tokio::test]
+async fn subscription_sse() {
+    struct SubscriptionRoot;
+
+    #[Subscription]
+    impl SubscriptionRoot {
+        async fn values(&self) -> impl futures::Stream<Item = i32> {
+            futures::stream::iter(0..2)
+        }
+    }
+
+    let filter = async_graphql_warp::graphql_subscription_sse(Schema::new(
+        QueryRoot,
+        EmptyMutation,
+        SubscriptionRoot,
+    ));
+
+    // the request of an `EventSource`, which can't set headers
+    let resp = warp::test::request()
+        .method("GET")
+        .path("/?query=subscription%20%7B%20values%20%7D")
+        .header("accept", "text/event-stream")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["content-type"], "text/event-stream");
+    assert_eq!(
+        std::str::from_utf8(resp.body()).unwrap(),
+        "event: next\ndata: {\"data\":{\"values\":0}}\n\n\
+         event: next\ndata: {\"data\":{\"values\":1}}\n\n\
+         event: complete\ndata:\n\n"
+    );
+
+    let resp = warp::test::request()
+        .method("GET")
+        .path("/?query=subscription%20%7B%20values%20%7D")
+        .reply(&filter)
+        .await;
+    assert_eq!(resp.status(), 400);
+}
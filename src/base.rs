@@ -45,6 +45,15 @@ pub trait Type {
         }
         Some(v[1].to_string().into())
     }
+
+    /// Parse a `GlobalID` returned by `global_id`, returns `None` if it is invalid or belongs to another type.
+    fn parse_global_id(id: ID) -> Option<ID> {
+        let (type_name, id) = crate::types::decode_global_id(&id)?;
+        if type_name != Self::type_name() {
+            return None;
+        }
+        Some(id)
+    }
 }
 
 /// Represents a GraphQL input value
@@ -101,6 +110,17 @@ pub trait ObjectType: OutputValueType {
         }
     }
 
+    /// Load the Relay node with the specified type name and id, returns `null` if it does not exist.
+    async fn find_node(
+        &self,
+        _ctx: &Context<'_>,
+        _pos: Pos,
+        _type_name: &str,
+        _id: ID,
+    ) -> Result<serde_json::Value> {
+        Ok(serde_json::Value::Null)
+    }
+
     /// Query entities with params
     async fn find_entity(
         &self,
@@ -1,14 +1,22 @@
-use crate::extensions::BoxExtension;
+use crate::data_factory::{DataFactories, LazyData};
+use crate::extensions::{extension_results, BoxExtension};
 use crate::registry::Registry;
-use crate::{InputValueType, Pos, QueryError, Result, Schema, Type};
+use crate::types::upload_value;
+use crate::{
+    FieldResult, InputValueType, Lookahead, OutputValueType, Pos, QueryError, QueryResponse,
+    Result, Schema, Type, UploadValue,
+};
 use fnv::FnvHashMap;
 use graphql_parser::query::{
     Directive, Field, FragmentDefinition, SelectionSet, Value, VariableDefinition,
 };
+use http::header::{HeaderValue, IntoHeaderName, SET_COOKIE};
+use http::HeaderMap;
+use parking_lot::Mutex;
 use std::any::{Any, TypeId};
 use std::collections::{BTreeMap, HashMap};
+use std::convert::TryInto;
 use std::ops::{Deref, DerefMut};
-use std::path::Path;
 use std::sync::atomic::AtomicUsize;
 use std::sync::Arc;
 
@@ -55,13 +63,7 @@ impl Variables {
         }
     }
 
-    pub(crate) fn set_upload(
-        &mut self,
-        var_path: &str,
-        filename: &str,
-        content_type: Option<&str>,
-        path: &Path,
-    ) {
+    pub(crate) fn set_upload(&mut self, var_path: &str) {
         let mut it = var_path.split('.').peekable();
 
         if let Some(first) = it.next() {
@@ -78,7 +80,7 @@ impl Variables {
                 if let Value::List(ls) = current {
                     if let Some(value) = ls.get_mut(idx as usize) {
                         if !has_next {
-                            *value = Value::String(file_string(filename, content_type, path));
+                            *value = upload_value(var_path);
                             return;
                         } else {
                             current = value;
@@ -90,7 +92,7 @@ impl Variables {
             } else if let Value::Object(obj) = current {
                 if let Some(value) = obj.get_mut(s) {
                     if !has_next {
-                        *value = Value::String(file_string(filename, content_type, path));
+                        *value = upload_value(var_path);
                         return;
                     } else {
                         current = value;
@@ -103,14 +105,6 @@ impl Variables {
     }
 }
 
-fn file_string(filename: &str, content_type: Option<&str>, path: &Path) -> String {
-    if let Some(content_type) = content_type {
-        format!("file:{}:{}|", filename, content_type) + &path.display().to_string()
-    } else {
-        format!("file:{}|", filename) + &path.display().to_string()
-    }
-}
-
 fn json_value_to_gql_value(value: serde_json::Value) -> Value {
     match value {
         serde_json::Value::Null => Value::Null,
@@ -138,6 +132,13 @@ impl Data {
     pub fn insert<D: Any + Send + Sync>(&mut self, data: D) {
         self.0.insert(TypeId::of::<D>(), Box::new(data));
     }
+
+    /// Gets the data of the type, returns `None` if it has not been inserted.
+    pub fn get<D: Any + Send + Sync>(&self) -> Option<&D> {
+        self.0
+            .get(&TypeId::of::<D>())
+            .and_then(|data| data.downcast_ref::<D>())
+    }
 }
 
 /// Context for `SelectionSet`
@@ -265,7 +266,12 @@ pub struct ContextBase<'a, T> {
     pub(crate) registry: &'a Registry,
     pub(crate) data: &'a Data,
     pub(crate) ctx_data: Option<&'a Data>,
+    pub(crate) http_headers: &'a Mutex<HeaderMap>,
+    pub(crate) data_factories: &'a DataFactories,
+    pub(crate) lazy_data: &'a LazyData,
+    pub(crate) creating_data: Option<TypeId>,
     pub(crate) fragments: &'a HashMap<String, FragmentDefinition>,
+    pub(crate) uploads: &'a [(String, UploadValue)],
 }
 
 impl<'a, T> Deref for ContextBase<'a, T> {
@@ -282,6 +288,16 @@ pub struct Environment {
     pub variable_definitions: Vec<VariableDefinition>,
     pub fragments: HashMap<String, FragmentDefinition>,
     pub ctx_data: Arc<Data>,
+    pub http_headers: Mutex<HeaderMap>,
+    pub lazy_data: LazyData,
+    pub extensions: Vec<BoxExtension>,
+}
+
+impl Drop for Environment {
+    fn drop(&mut self) {
+        // the streams of the subscription own the environment, it's dropped when the subscription ends
+        self.extensions.iter().for_each(|e| e.subscription_end());
+    }
 }
 
 impl Environment {
@@ -292,21 +308,95 @@ impl Environment {
         path_node: Option<QueryPathNode<'a>>,
         item: T,
         inc_resolve_id: &'a AtomicUsize,
+        extensions: &'a [BoxExtension],
     ) -> ContextBase<'a, T> {
         ContextBase {
             path_node,
             resolve_id: ResolveId::root(),
             inc_resolve_id,
-            extensions: &[],
+            extensions,
             item,
             variables: &self.variables,
             variable_definitions: &self.variable_definitions,
             registry: &schema.0.registry,
             data: &schema.0.data,
             ctx_data: Some(&self.ctx_data),
+            http_headers: &self.http_headers,
+            data_factories: &schema.0.data_factories,
+            lazy_data: &self.lazy_data,
+            creating_data: None,
             fragments: &self.fragments,
+            uploads: &[],
         }
     }
+
+    /// Resolve an event of a subscription field.
+    ///
+    /// Every event is resolved with new instances of the extensions, the instances that parsed and validated the
+    /// subscription only see its end.
+    #[doc(hidden)]
+    pub async fn resolve_subscription_event<T, Query, Mutation, Subscription>(
+        &self,
+        schema: &Schema<Query, Mutation, Subscription>,
+        field_name: &str,
+        selection_set: &SelectionSet,
+        pos: Pos,
+        msg: &T,
+    ) -> Result<QueryResponse>
+    where
+        T: OutputValueType + Send + Sync,
+    {
+        let extensions = schema.create_extensions();
+        let resolve_id = AtomicUsize::default();
+        let ctx_selection_set = self.create_context(
+            schema,
+            Some(QueryPathNode {
+                parent: None,
+                segment: QueryPathSegment::Name(field_name),
+            }),
+            selection_set,
+            &resolve_id,
+            &extensions,
+        );
+
+        extensions.iter().for_each(|e| e.execution_start());
+        let res = OutputValueType::resolve(msg, &ctx_selection_set, pos).await;
+        extensions.iter().for_each(|e| e.execution_end());
+        let value = res?;
+
+        let mut data = serde_json::Map::new();
+        data.insert(field_name.to_string(), value);
+        Ok(QueryResponse {
+            data: data.into(),
+            extensions: extension_results(&extensions),
+            cache_control: Default::default(),
+            http_headers: Default::default(),
+        })
+    }
+}
+
+/// A cookie name is a token of RFC 2616.
+fn is_cookie_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b > 0x20 && b < 0x7f && !b"()<>@,;:\\\"/[]?={}".contains(&b))
+}
+
+/// A cookie value is a sequence of cookie-octets, optionally in double quotes.
+fn is_cookie_value(value: &str) -> bool {
+    let value = if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        &value[1..value.len() - 1]
+    } else {
+        value
+    };
+    value.bytes().all(|b| {
+        b == 0x21
+            || (0x23..=0x2b).contains(&b)
+            || (0x2d..=0x3a).contains(&b)
+            || (0x3c..=0x5b).contains(&b)
+            || (0x5d..=0x7e).contains(&b)
+    })
 }
 
 impl<'a, T> ContextBase<'a, T> {
@@ -342,7 +432,12 @@ impl<'a, T> ContextBase<'a, T> {
             registry: self.registry,
             data: self.data,
             ctx_data: self.ctx_data,
+            http_headers: self.http_headers,
+            data_factories: self.data_factories,
+            lazy_data: self.lazy_data,
+            creating_data: self.creating_data,
             fragments: self.fragments,
+            uploads: self.uploads,
         }
     }
 
@@ -362,24 +457,92 @@ impl<'a, T> ContextBase<'a, T> {
             registry: self.registry,
             data: self.data,
             ctx_data: self.ctx_data,
+            http_headers: self.http_headers,
+            data_factories: self.data_factories,
+            lazy_data: self.lazy_data,
+            creating_data: self.creating_data,
             fragments: self.fragments,
+            uploads: self.uploads,
         }
     }
 
     /// Gets the global data defined in the `Context` or `Schema`.
+    ///
+    /// **It doesn't run the `DataFactory` of the type**, the data of a factory is only returned once it has been
+    /// created in the request. Use `Context::data_async` for the data of the factories.
+    ///
+    /// # Panics
+    ///
+    /// It panics if the specified type data does not exist, or if it's the data of a `DataFactory` that has not been
+    /// created yet.
     pub fn data<D: Any + Send + Sync>(&self) -> &D {
-        self.data_opt::<D>()
-            .expect("The specified data type does not exist.")
+        match self.data_opt::<D>() {
+            Some(data) => data,
+            None if self.data_factories.0.contains_key(&TypeId::of::<D>()) => panic!(
+                "The data of `{}` is created by a data factory, use `Context::data_async` to get it.",
+                std::any::type_name::<D>()
+            ),
+            None => panic!("The specified data type does not exist."),
+        }
     }
 
     /// Gets the global data defined in the `Context` or `Schema`, returns `None` if the specified type data does not exist.
+    ///
+    /// **It doesn't run the `DataFactory` of the type**, it returns `None` until the data of the factory has been
+    /// created in the request with `Context::data_async`.
     pub fn data_opt<D: Any + Send + Sync>(&self) -> Option<&D> {
+        let type_id = TypeId::of::<D>();
         self.ctx_data
-            .and_then(|ctx_data| ctx_data.0.get(&TypeId::of::<D>()))
-            .or_else(|| self.data.0.get(&TypeId::of::<D>()))
+            .and_then(|ctx_data| ctx_data.0.get(&type_id))
+            .map(|d| d.as_ref() as &(dyn Any + Send + Sync))
+            .or_else(|| self.lazy_data.get(&type_id))
+            .or_else(|| self.data.0.get(&type_id).map(|d| d.as_ref()))
             .and_then(|d| d.downcast_ref::<D>())
     }
 
+    /// Inserts a header into the HTTP response, replacing any existing values with the same name.
+    ///
+    /// The integrations apply these headers to the outgoing HTTP response. Headers set while
+    /// resolving a subscription are ignored.
+    pub fn insert_http_header<K, V>(&self, name: K, value: V) -> FieldResult<()>
+    where
+        K: IntoHeaderName,
+        V: TryInto<HeaderValue>,
+        V::Error: std::fmt::Display + Send + Sync + 'static,
+    {
+        let value = value.try_into()?;
+        self.http_headers.lock().insert(name, value);
+        Ok(())
+    }
+
+    /// Appends a header to the HTTP response, keeping the existing values with the same name.
+    pub fn append_http_header<K, V>(&self, name: K, value: V) -> FieldResult<()>
+    where
+        K: IntoHeaderName,
+        V: TryInto<HeaderValue>,
+        V::Error: std::fmt::Display + Send + Sync + 'static,
+    {
+        let value = value.try_into()?;
+        self.http_headers.lock().append(name, value);
+        Ok(())
+    }
+
+    /// Adds a `Set-Cookie` header to the HTTP response.
+    ///
+    /// Returns an error if the name is not a token or the value has characters that are not allowed
+    /// by [RFC 6265](https://tools.ietf.org/html/rfc6265#section-4.1.1). If you need cookie
+    /// attributes such as `Path` or `HttpOnly`, use `append_http_header` with the complete
+    /// `Set-Cookie` value.
+    pub fn set_cookie(&self, name: &str, value: &str) -> FieldResult<()> {
+        if !is_cookie_name(name) {
+            return Err(format!("Invalid cookie name \"{}\"", name).into());
+        }
+        if !is_cookie_value(value) {
+            return Err(format!("Invalid cookie value \"{}\"", value).into());
+        }
+        self.append_http_header(SET_COOKIE, format!("{}={}", name, value))
+    }
+
     fn var_value(&self, name: &str, pos: Pos) -> Result<Value> {
         let def = self
             .variable_definitions
@@ -505,7 +668,12 @@ impl<'a> ContextBase<'a, &'a SelectionSet> {
             registry: self.registry,
             data: self.data,
             ctx_data: self.ctx_data,
+            http_headers: self.http_headers,
+            data_factories: self.data_factories,
+            lazy_data: self.lazy_data,
+            creating_data: self.creating_data,
             fragments: self.fragments,
+            uploads: self.uploads,
         }
     }
 }
@@ -550,6 +718,74 @@ impl<'a> ContextBase<'a, &'a Field> {
         }
     }
 
+    /// Gets the data defined in the `Context` or `Schema`, or creates it with the `DataFactory`
+    /// registered for this type.
+    ///
+    /// The factory runs at most once per request, the created data or the error is cached for the rest of the request.
+    /// A factory that asks for its own output, directly or through other factories, gets an error instead of waiting
+    /// for itself.
+    pub async fn data_async<D: Any + Send + Sync>(&self) -> FieldResult<&D> {
+        if let Some(data) = self.data_opt::<D>() {
+            return Ok(data);
+        }
+        match self
+            .lazy_data
+            .get_or_create(self.data_factories, &TypeId::of::<D>(), self)
+            .await
+        {
+            Some(res) => Ok(res?
+                .downcast_ref::<D>()
+                .expect("The data factory returned a wrong type.")),
+            None => Err("The specified data type does not exist.".into()),
+        }
+    }
+
+    /// Creates a uniform interface to inspect the forthcoming selections.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use async_graphql::*;
+    ///
+    /// #[SimpleObject]
+    /// #[derive(Clone)]
+    /// struct Detail {
+    ///     c: i32,
+    ///     d: i32,
+    /// }
+    ///
+    /// #[SimpleObject]
+    /// struct MyObj {
+    ///     a: i32,
+    ///     b: i32,
+    ///     detail: Detail,
+    /// }
+    ///
+    /// struct Query;
+    ///
+    /// #[Object]
+    /// impl Query {
+    ///     async fn obj(&self, ctx: &Context<'_>) -> MyObj {
+    ///         if ctx.look_ahead().field("a").exists() {
+    ///             // This is a query like `obj { a }`
+    ///         } else if ctx.look_ahead().field("detail").field("c").exists() {
+    ///             // This is a query like `obj { detail { c } }`
+    ///         } else {
+    ///             // This query doesn't have `a`
+    ///         }
+    ///         unimplemented!()
+    ///     }
+    /// }
+    /// ```
+    pub fn look_ahead(&self) -> Lookahead<'a> {
+        Lookahead::new(
+            self.fragments,
+            self.variables,
+            self.variable_definitions,
+            self.item,
+        )
+    }
+
     #[doc(hidden)]
     pub fn result_name(&self) -> &str {
         self.item
@@ -0,0 +1,175 @@
+use crate::context::ContextBase;
+use crate::{Context, FieldResult};
+use fnv::{FnvHashMap, FnvHashSet};
+use futures::lock::Mutex;
+use once_cell::sync::OnceCell;
+use std::any::{Any, TypeId};
+
+/// Per-request data factory
+///
+/// A factory is registered with `SchemaBuilder::data_factory` and runs lazily the first time
+/// `Context::data_async` asks for its output in a request. The result, the data or the error, is
+/// cached for the rest of that request, so the factory runs at most once per query or subscription.
+///
+/// **The output is only created by `Context::data_async`.** `Context::data_opt` returns it once it
+/// has been created in the request, and `None` before, `Context::data` panics before. Always use
+/// `Context::data_async` for the outputs of the factories.
+///
+/// The factory receives the context of the field that first requested the data, so it can use
+/// the data of the request and the schema, including the outputs of other factories.
+///
+/// # Examples
+///
+/// ```rust
+/// use async_graphql::*;
+///
+/// struct CurrentUser(String);
+///
+/// struct CurrentUserFactory;
+///
+/// #[async_trait::async_trait]
+/// impl DataFactory for CurrentUserFactory {
+///     type Output = CurrentUser;
+///
+///     async fn create(&self, ctx: &Context<'_>) -> FieldResult<CurrentUser> {
+///         let token = ctx.data_opt::<String>().ok_or("Unauthorized")?;
+///         Ok(CurrentUser(format!("user of {}", token)))
+///     }
+/// }
+///
+/// struct QueryRoot;
+///
+/// #[Object]
+/// impl QueryRoot {
+///     async fn current_user(&self, ctx: &Context<'_>) -> FieldResult<String> {
+///         Ok(ctx.data_async::<CurrentUser>().await?.0.clone())
+///     }
+/// }
+///
+/// async_std::task::block_on(async move {
+///     let schema = Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+///         .data_factory(CurrentUserFactory)
+///         .finish();
+///     let res = QueryBuilder::new("{ currentUser }")
+///         .data("abc".to_string())
+///         .execute(&schema)
+///         .await
+///         .unwrap()
+///         .data;
+///     assert_eq!(res, serde_json::json!({ "currentUser": "user of abc" }));
+/// });
+/// ```
+#[async_trait::async_trait]
+pub trait DataFactory: Send + Sync + 'static {
+    /// The type of the created data
+    type Output: Any + Send + Sync;
+
+    /// Create the data for the current request.
+    ///
+    /// Asking for `Self::Output` inside this method, directly or through other factories, returns an error. The
+    /// factories of a request run one at a time, so the other factories are created here in turn.
+    async fn create(&self, ctx: &Context<'_>) -> FieldResult<Self::Output>;
+}
+
+#[async_trait::async_trait]
+pub(crate) trait BoxDataFactory: Send + Sync {
+    fn output_type_name(&self) -> &'static str;
+
+    async fn create_boxed(&self, ctx: &Context<'_>) -> FieldResult<Box<dyn Any + Send + Sync>>;
+}
+
+#[async_trait::async_trait]
+impl<F: DataFactory> BoxDataFactory for F {
+    fn output_type_name(&self) -> &'static str {
+        std::any::type_name::<F::Output>()
+    }
+
+    async fn create_boxed(&self, ctx: &Context<'_>) -> FieldResult<Box<dyn Any + Send + Sync>> {
+        Ok(Box::new(self.create(ctx).await?))
+    }
+}
+
+/// The data factories registered in the schema
+#[derive(Default)]
+pub(crate) struct DataFactories(pub(crate) FnvHashMap<TypeId, Box<dyn BoxDataFactory>>);
+
+impl DataFactories {
+    pub(crate) fn insert<F: DataFactory>(&mut self, factory: F) {
+        self.0.insert(TypeId::of::<F::Output>(), Box::new(factory));
+    }
+}
+
+/// The data created by the factories in the current request
+///
+/// The factories of a request run one at a time, a factory that runs while another one is being created can only
+/// have been called by it. So a factory whose output is already being created depends on itself.
+#[doc(hidden)]
+#[derive(Default)]
+pub struct LazyData {
+    cells: FnvHashMap<TypeId, OnceCell<FieldResult<Box<dyn Any + Send + Sync>>>>,
+    lock: Mutex<()>,
+    creating: std::sync::Mutex<FnvHashSet<TypeId>>,
+}
+
+impl LazyData {
+    pub(crate) fn new(factories: &DataFactories) -> Self {
+        LazyData {
+            cells: factories
+                .0
+                .keys()
+                .map(|type_id| (*type_id, OnceCell::new()))
+                .collect(),
+            lock: Mutex::new(()),
+            creating: Default::default(),
+        }
+    }
+
+    /// Returns the data if it has already been created in this request.
+    pub(crate) fn get(&self, type_id: &TypeId) -> Option<&(dyn Any + Send + Sync)> {
+        self.cells
+            .get(type_id)
+            .and_then(|cell| cell.get())
+            .and_then(|res| res.as_ref().ok())
+            .map(|value| value.as_ref())
+    }
+
+    pub(crate) async fn get_or_create<'a>(
+        &'a self,
+        factories: &DataFactories,
+        type_id: &TypeId,
+        ctx: &Context<'_>,
+    ) -> Option<FieldResult<&'a (dyn Any + Send + Sync)>> {
+        let cell = self.cells.get(type_id)?;
+        let factory = factories.0.get(type_id)?;
+
+        if cell.get().is_none() {
+            // the factories called by a factory run while it holds the lock
+            let _guard = if ctx.creating_data.is_none() {
+                Some(self.lock.lock().await)
+            } else {
+                None
+            };
+            if cell.get().is_none() {
+                if !self.creating.lock().unwrap().insert(*type_id) {
+                    return Some(Err(format!(
+                        "The data factory of `{}` depends on itself.",
+                        factory.output_type_name()
+                    )
+                    .into()));
+                }
+                let ctx = ContextBase {
+                    creating_data: Some(*type_id),
+                    ..ctx.clone()
+                };
+                let res = factory.create_boxed(&ctx).await;
+                self.creating.lock().unwrap().remove(type_id);
+                let _ = cell.set(res);
+            }
+        }
+
+        cell.get().map(|res| match res {
+            Ok(value) => Ok(value.as_ref()),
+            Err(err) => Err(err.clone()),
+        })
+    }
+}
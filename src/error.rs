@@ -235,6 +235,9 @@ pub enum QueryError {
 
     #[error("\"__typename\" must be an existing string")]
     TypeNameNotExists,
+
+    #[error("Mutations are not allowed in this request.")]
+    MutationNotAllowed,
 }
 
 impl QueryError {
@@ -280,11 +283,14 @@ impl From<ParseError> for Error {
 #[derive(Debug, Error)]
 pub enum ParseRequestError {
     #[error("{0}")]
-    Io(#[from] std::io::Error),
+    Io(std::io::Error),
 
     #[error("Invalid request: {0}")]
     InvalidRequest(serde_json::Error),
 
+    #[error("Invalid query string: {0}")]
+    InvalidQueryString(serde_urlencoded::de::Error),
+
     #[error("Invalid files map: {0}")]
     InvalidFilesMap(serde_json::Error),
 
@@ -308,8 +314,28 @@ pub enum ParseRequestError {
 
     #[error("The file size is too large")]
     TooLarge,
+
+    #[error("The request body is too large")]
+    PayloadTooLarge,
+
+    #[error("The content type \"{0}\" of the file is not allowed")]
+    ContentTypeNotAllowed(String),
+
+    #[error("The request is blocked to prevent cross-site request forgery, it requires a non-simple \"Content-Type\" or a preflight header such as \"graphql-preflight\"")]
+    CsrfPrevented,
 }
 
+impl From<std::io::Error> for ParseRequestError {
+    fn from(err: std::io::Error) -> Self {
+        crate::http::into_parse_request_error(err).unwrap_or_else(ParseRequestError::Io)
+    }
+}
+
+/// An error in the types of a schema, returned by `SchemaBuilder::try_finish`.
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("Invalid schema: {0}")]
+pub struct SchemaError(pub String);
+
 #[allow(missing_docs)]
 #[derive(Debug, Error, PartialEq)]
 pub enum Error {
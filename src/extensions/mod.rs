@@ -57,8 +57,36 @@ pub trait Extension: Sync + Send + 'static {
     /// Called at the end of the resolve field.
     fn resolve_field_end(&self, resolve_id: ResolveId) {}
 
+    /// Called when a subscription stream ends.
+    ///
+    /// The parse and validation of a subscription are called once, for the instance of the
+    /// extension that receives this call, it is also called if they fail. Every event of the
+    /// subscription is executed with a new instance of the extension, and the result of that
+    /// instance is added to the response of the event.
+    fn subscription_end(&self) {}
+
     /// Get the results
     fn result(&self) -> Option<serde_json::Value> {
         None
     }
 }
+
+pub(crate) fn extension_results(
+    extensions: &[BoxExtension],
+) -> Option<serde_json::Map<String, serde_json::Value>> {
+    if extensions.is_empty() {
+        return None;
+    }
+    Some(
+        extensions
+            .iter()
+            .filter_map(|e| {
+                if let Some(name) = e.name() {
+                    e.result().map(|res| (name.to_string(), res))
+                } else {
+                    None
+                }
+            })
+            .collect(),
+    )
+}
@@ -0,0 +1,82 @@
+use bytes::Bytes;
+use futures::task::{Context, Poll};
+use futures::{stream, AsyncRead, AsyncReadExt, Stream, TryStreamExt};
+use std::io::{Error, ErrorKind, Result};
+use std::pin::Pin;
+
+/// The body of the HTTP requests and responses of `handle`
+///
+/// The integrations convert the bodies of their framework from and to it.
+pub enum Body {
+    /// A body that is already in memory
+    Full(Bytes),
+
+    /// A streamed body, such as the body of a request or Server-Sent Events
+    Stream(Pin<Box<dyn Stream<Item = Result<Bytes>> + Send>>),
+}
+
+impl Body {
+    /// Create a body from a stream of chunks.
+    pub fn from_stream<S, E>(s: S) -> Self
+    where
+        S: Stream<Item = std::result::Result<Bytes, E>> + Send + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        Body::Stream(Box::pin(s.map_err(|err| Error::new(ErrorKind::Other, err))))
+    }
+
+    /// Create a body from a reader.
+    pub fn from_reader<R: AsyncRead + Send + Unpin + 'static>(reader: R) -> Self {
+        Body::Stream(Box::pin(stream::unfold(
+            Some(reader),
+            |reader| async move {
+                let mut reader = reader?;
+                let mut buf = vec![0; 4096];
+                match reader.read(&mut buf).await {
+                    Ok(0) => None,
+                    Ok(size) => {
+                        buf.truncate(size);
+                        Some((Ok(Bytes::from(buf)), Some(reader)))
+                    }
+                    Err(err) => Some((Err(err), None)),
+                }
+            },
+        )))
+    }
+}
+
+impl Default for Body {
+    fn default() -> Self {
+        Body::Full(Bytes::new())
+    }
+}
+
+impl From<Bytes> for Body {
+    fn from(bytes: Bytes) -> Self {
+        Body::Full(bytes)
+    }
+}
+
+impl From<Vec<u8>> for Body {
+    fn from(bytes: Vec<u8>) -> Self {
+        Body::Full(bytes.into())
+    }
+}
+
+impl From<String> for Body {
+    fn from(s: String) -> Self {
+        Body::Full(s.into())
+    }
+}
+
+impl Stream for Body {
+    type Item = Result<Bytes>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match &mut *self {
+            Body::Full(bytes) if bytes.is_empty() => Poll::Ready(None),
+            Body::Full(bytes) => Poll::Ready(Some(Ok(std::mem::replace(bytes, Bytes::new())))),
+            Body::Stream(s) => s.as_mut().poll_next(cx),
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use crate::ParseRequestError;
+use http::header::{self, HeaderMap};
+use http::Method;
+use mime::Mime;
+
+/// The content types that browsers send cross-site without a CORS preflight request
+const SIMPLE_CONTENT_TYPES: &[&str] = &[
+    "application/x-www-form-urlencoded",
+    "multipart/form-data",
+    "text/plain",
+];
+
+/// Prevents cross-site request forgery
+///
+/// Browsers send the "simple requests", such as GET requests or multipart POST requests, cross-site without a CORS
+/// preflight request, so a malicious page can execute queries with the cookies of the user. The guard rejects them
+/// with `ParseRequestError::CsrfPrevented`, a request is accepted if it has a `Content-Type` header other than
+/// `application/x-www-form-urlencoded`, `multipart/form-data` and `text/plain`, or one of the preflight headers.
+///
+/// An `EventSource` can't set headers, its requests are only accepted by the routes of Server-Sent Events that enable
+/// `CsrfGuard::event_source`, such as `async_graphql::http::handle_sse`.
+///
+/// It's enabled by default in `IntoQueryBuilderOpts`, the integrations check it before the request is parsed.
+///
+/// # Examples
+///
+/// ```rust
+/// use async_graphql::http::CsrfGuard;
+/// use async_graphql::IntoQueryBuilderOpts;
+///
+/// let opts = IntoQueryBuilderOpts {
+///     csrf_guard: CsrfGuard::default().headers(&["x-requested-with"]),
+///     ..IntoQueryBuilderOpts::default()
+/// };
+/// ```
+#[derive(Debug, Clone)]
+pub struct CsrfGuard {
+    enabled: bool,
+    headers: Vec<String>,
+    event_source: bool,
+}
+
+impl Default for CsrfGuard {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            headers: vec![
+                "x-apollo-operation-name".to_string(),
+                "apollo-require-preflight".to_string(),
+                "graphql-preflight".to_string(),
+            ],
+            event_source: false,
+        }
+    }
+}
+
+impl CsrfGuard {
+    /// A guard that accepts every request.
+    ///
+    /// Only disable it if the endpoint doesn't use cookies or other credentials that browsers send automatically.
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Self::default()
+        }
+    }
+
+    /// Set the preflight headers, the default is `x-apollo-operation-name`, `apollo-require-preflight` and
+    /// `graphql-preflight`.
+    pub fn headers<T: AsRef<str>>(self, headers: &[T]) -> Self {
+        Self {
+            headers: headers
+                .iter()
+                .map(|name| name.as_ref().to_ascii_lowercase())
+                .collect(),
+            ..self
+        }
+    }
+
+    /// Accept the GET requests of an `EventSource`, which have the `Accept: text/event-stream` header, the default is
+    /// `false`.
+    ///
+    /// Only enable it for the routes of Server-Sent Events, they can't execute queries or mutations.
+    pub fn event_source(self, event_source: bool) -> Self {
+        Self {
+            event_source,
+            ..self
+        }
+    }
+
+    /// Check the method and the headers of a request.
+    pub fn check(&self, method: &Method, headers: &HeaderMap) -> Result<(), ParseRequestError> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        if self.event_source && method == Method::GET && accepts_event_stream(headers) {
+            return Ok(());
+        }
+
+        if let Some(content_type) = headers.get(header::CONTENT_TYPE) {
+            // browsers preflight the content types they can't parse too
+            match content_type
+                .to_str()
+                .ok()
+                .and_then(|value| value.parse::<Mime>().ok())
+            {
+                Some(mime) if SIMPLE_CONTENT_TYPES.contains(&mime.essence_str()) => {}
+                _ => return Ok(()),
+            }
+        }
+
+        if self
+            .headers
+            .iter()
+            .any(|name| headers.contains_key(name.as_str()))
+        {
+            return Ok(());
+        }
+
+        Err(ParseRequestError::CsrfPrevented)
+    }
+}
+
+fn accepts_event_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|value| value.trim().parse::<Mime>().ok())
+        .any(|mime| mime.essence_str() == "text/event-stream")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+
+    fn headers(items: &[(&'static str, &'static str)]) -> HeaderMap {
+        items
+            .iter()
+            .map(|(name, value)| {
+                (
+                    header::HeaderName::from_static(name),
+                    HeaderValue::from_static(value),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_csrf_guard() {
+        let guard = CsrfGuard::default();
+        assert!(guard
+            .check(
+                &Method::POST,
+                &headers(&[("content-type", "application/json")])
+            )
+            .is_ok());
+        assert!(guard
+            .check(&Method::POST, &headers(&[("content-type", "invalid")]))
+            .is_ok());
+        assert!(matches!(
+            guard.check(&Method::POST, &headers(&[])),
+            Err(ParseRequestError::CsrfPrevented)
+        ));
+        assert!(matches!(
+            guard.check(
+                &Method::POST,
+                &headers(&[("content-type", "multipart/form-data; boundary=xyz")])
+            ),
+            Err(ParseRequestError::CsrfPrevented)
+        ));
+        assert!(guard
+            .check(
+                &Method::POST,
+                &headers(&[
+                    ("content-type", "multipart/form-data; boundary=xyz"),
+                    ("graphql-preflight", "1"),
+                ])
+            )
+            .is_ok());
+        assert!(guard
+            .check(&Method::POST, &headers(&[("x-apollo-operation-name", "")]))
+            .is_ok());
+
+        let guard = CsrfGuard::default().headers(&["X-Requested-With"]);
+        assert!(guard
+            .check(
+                &Method::POST,
+                &headers(&[("x-requested-with", "XMLHttpRequest")])
+            )
+            .is_ok());
+        assert!(guard
+            .check(&Method::POST, &headers(&[("graphql-preflight", "1")]))
+            .is_err());
+
+        assert!(CsrfGuard::disabled()
+            .check(&Method::POST, &headers(&[]))
+            .is_ok());
+
+        assert!(CsrfGuard::default()
+            .check(&Method::GET, &headers(&[]))
+            .is_err());
+        assert!(CsrfGuard::default()
+            .check(&Method::GET, &headers(&[("graphql-preflight", "1")]))
+            .is_ok());
+        assert!(CsrfGuard::default()
+            .check(&Method::GET, &headers(&[("accept", "text/event-stream")]))
+            .is_err());
+
+        let guard = CsrfGuard::default().event_source(true);
+        assert!(guard
+            .check(&Method::GET, &headers(&[("accept", "text/event-stream")]))
+            .is_ok());
+        assert!(guard.check(&Method::GET, &headers(&[])).is_err());
+        assert!(guard
+            .check(&Method::GET, &headers(&[("accept", "application/json")]))
+            .is_err());
+        assert!(guard
+            .check(&Method::POST, &headers(&[("accept", "text/event-stream")]))
+            .is_err());
+    }
+}
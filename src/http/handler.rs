@@ -0,0 +1,275 @@
+use crate::http::CsrfGuard;
+use crate::http::{
+    build_http_response, parse_query_string, sse_stream, with_etag, Body, RequestMetadata,
+    ResponseMediaType,
+};
+use crate::{
+    IntoQueryBuilder, IntoQueryBuilderOpts, ObjectType, ParseRequestError, QueryBuilder, Schema,
+    SubscriptionType,
+};
+use futures::{StreamExt, TryStreamExt};
+use http::header::{self, HeaderMap, HeaderValue};
+use http::{Method, Request, Response, StatusCode};
+
+fn header_str(headers: &HeaderMap, name: header::HeaderName) -> Option<&str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+/// Returns the status code of a request that can't be parsed.
+///
+/// It's `413 Payload Too Large` if the body or the files are too large, `415 Unsupported Media Type` if the content
+/// type of a file is not allowed, otherwise `400 Bad Request`.
+pub fn parse_request_error_status(err: &ParseRequestError) -> StatusCode {
+    match err {
+        ParseRequestError::TooManyFiles
+        | ParseRequestError::TooLarge
+        | ParseRequestError::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+        ParseRequestError::ContentTypeNotAllowed(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        _ => StatusCode::BAD_REQUEST,
+    }
+}
+
+/// Build the HTTP response of a request that can't be parsed, the body is the error message.
+pub fn parse_request_error_response(err: &ParseRequestError) -> Response<Body> {
+    let mut resp = Response::new(Body::from(err.to_string()));
+    *resp.status_mut() = parse_request_error_status(err);
+    resp.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("text/plain; charset=utf-8"),
+    );
+    resp
+}
+
+/// Receive the GraphQL request of an HTTP request.
+///
+/// The request is checked with `IntoQueryBuilderOpts::csrf_guard`, then the query is read from the query string of
+/// GET requests, and from the body of the other requests. The `RequestMetadata` of the request is added to the
+/// context data.
+pub async fn receive_request(
+    req: Request<Body>,
+    opts: &IntoQueryBuilderOpts,
+) -> std::result::Result<QueryBuilder, ParseRequestError> {
+    let (parts, body) = req.into_parts();
+    opts.csrf_guard.check(&parts.method, &parts.headers)?;
+
+    let builder = if parts.method == Method::GET {
+        parse_query_string(parts.uri.query().unwrap_or_default())?
+    } else {
+        let content_type =
+            header_str(&parts.headers, header::CONTENT_TYPE).map(ToString::to_string);
+        (content_type, body.into_async_read())
+            .into_query_builder_opts(opts)
+            .await?
+    };
+    Ok(builder.data(RequestMetadata {
+        method: parts.method,
+        uri: parts.uri,
+        headers: parts.headers,
+    }))
+}
+
+/// Handle a GraphQL request
+///
+/// It's the whole HTTP service of a GraphQL endpoint, the integrations convert the requests and the responses of their
+/// framework and call it, so they behave the same:
+///
+/// - Only GET and POST requests are accepted, mutations are not allowed over GET.
+/// - The request is read with `receive_request`, an invalid request is answered with `parse_request_error_response`.
+/// - The response is built with `build_http_response`, its media type is negotiated with the `Accept` header.
+/// - The responses of GET requests are conditional with `with_etag`.
+///
+/// # Examples
+///
+/// ```rust
+/// use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema};
+/// use async_graphql::http::Body;
+/// use futures::TryStreamExt;
+///
+/// struct QueryRoot;
+///
+/// #[Object]
+/// impl QueryRoot {
+///     async fn value(&self) -> i32 {
+///         10
+///     }
+/// }
+///
+/// async_std::task::block_on(async move {
+///     let schema = Schema::new(QueryRoot, EmptyMutation, EmptySubscription);
+///     let req = http::Request::post("/")
+///         .header("content-type", "application/json")
+///         .body(Body::from(r#"{"query": "{ value }"}"#.to_string()))
+///         .unwrap();
+///     let resp = async_graphql::http::handle(&schema, req).await;
+///     assert_eq!(resp.status(), http::StatusCode::OK);
+///     let body = resp.into_body().map_ok(|bytes| bytes.to_vec()).try_concat().await.unwrap();
+///     assert_eq!(body, br#"{"data":{"value":10}}"#.to_vec());
+/// });
+/// ```
+pub async fn handle<Query, Mutation, Subscription>(
+    schema: &Schema<Query, Mutation, Subscription>,
+    req: Request<Body>,
+) -> Response<Body>
+where
+    Query: ObjectType + Send + Sync + 'static,
+    Mutation: ObjectType + Send + Sync + 'static,
+    Subscription: SubscriptionType + Send + Sync + 'static,
+{
+    handle_opts(schema, req, &Default::default(), |builder| builder).await
+}
+
+/// Similar to `handle`, but you can set the options `IntoQueryBuilderOpts` and configure the `QueryBuilder`.
+pub async fn handle_opts<Query, Mutation, Subscription, F>(
+    schema: &Schema<Query, Mutation, Subscription>,
+    req: Request<Body>,
+    opts: &IntoQueryBuilderOpts,
+    query_builder_configuration: F,
+) -> Response<Body>
+where
+    Query: ObjectType + Send + Sync + 'static,
+    Mutation: ObjectType + Send + Sync + 'static,
+    Subscription: SubscriptionType + Send + Sync + 'static,
+    F: FnOnce(QueryBuilder) -> QueryBuilder,
+{
+    let method = req.method().clone();
+    if method != Method::GET && method != Method::POST {
+        let mut resp = Response::new(Body::default());
+        *resp.status_mut() = StatusCode::METHOD_NOT_ALLOWED;
+        resp.headers_mut()
+            .insert(header::ALLOW, HeaderValue::from_static("GET, POST"));
+        return resp;
+    }
+
+    let media_type = ResponseMediaType::negotiate(header_str(req.headers(), header::ACCEPT));
+    let if_none_match = header_str(req.headers(), header::IF_NONE_MATCH).map(ToString::to_string);
+    let builder = match receive_request(req, opts).await {
+        Ok(builder) => query_builder_configuration(builder),
+        Err(err) => return parse_request_error_response(&err),
+    };
+
+    let resp = build_http_response(builder.execute(schema).await, media_type);
+    with_etag(resp, &method, if_none_match.as_deref()).map(Body::from)
+}
+
+/// Handle a GraphQL subscription request over Server-Sent Events
+///
+/// It's the HTTP service of a Server-Sent Events endpoint. The request is read with `receive_request`, its CSRF guard
+/// accepts the requests of an `EventSource`, see `CsrfGuard::event_source`. The results are streamed with
+/// `sse_response`, an invalid request is answered with `parse_request_error_response`.
+pub async fn handle_sse<Query, Mutation, Subscription, F>(
+    schema: &Schema<Query, Mutation, Subscription>,
+    req: Request<Body>,
+    query_builder_configuration: F,
+) -> Response<Body>
+where
+    Query: ObjectType + Send + Sync + 'static,
+    Mutation: ObjectType + Send + Sync + 'static,
+    Subscription: SubscriptionType + Send + Sync + 'static,
+    F: FnOnce(QueryBuilder) -> QueryBuilder,
+{
+    let opts = IntoQueryBuilderOpts {
+        csrf_guard: CsrfGuard::default().event_source(true),
+        ..IntoQueryBuilderOpts::default()
+    };
+    match receive_request(req, &opts).await {
+        Ok(builder) => sse_response(schema.clone(), query_builder_configuration(builder)),
+        Err(err) => parse_request_error_response(&err),
+    }
+}
+
+/// Build the HTTP response that streams the results of a subscription as Server-Sent Events, see `sse_stream`.
+pub fn sse_response<Query, Mutation, Subscription>(
+    schema: Schema<Query, Mutation, Subscription>,
+    builder: QueryBuilder,
+) -> Response<Body>
+where
+    Query: ObjectType + Send + Sync + 'static,
+    Mutation: ObjectType + Send + Sync + 'static,
+    Subscription: SubscriptionType + Send + Sync + 'static,
+{
+    let mut resp = Response::new(Body::from_stream(
+        sse_stream(schema, builder).map(Ok::<_, std::io::Error>),
+    ));
+    resp.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("text/event-stream"),
+    );
+    resp.headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    resp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EmptyMutation, EmptySubscription};
+
+    struct QueryRoot;
+
+    #[crate::Object(internal)]
+    impl QueryRoot {
+        async fn value(&self) -> i32 {
+            10
+        }
+    }
+
+    async fn body_string(body: Body) -> String {
+        let bytes = body
+            .map_ok(|bytes| bytes.to_vec())
+            .try_concat()
+            .await
+            .unwrap();
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[async_std::test]
+    async fn test_handle() {
+        let schema = Schema::new(QueryRoot, EmptyMutation, EmptySubscription);
+
+        let req = Request::post("/")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from_reader(futures::io::Cursor::new(
+                r#"{"query": "{ value }"}"#,
+            )))
+            .unwrap();
+        let resp = handle(&schema, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            body_string(resp.into_body()).await,
+            r#"{"data":{"value":10}}"#
+        );
+
+        let req = Request::post("/")
+            .body(Body::from(r#"{"query": "{ value }"}"#.to_string()))
+            .unwrap();
+        let resp = handle(&schema, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let req = Request::get("/?query=%7B%20value%20%7D")
+            .body(Body::default())
+            .unwrap();
+        let resp = handle(&schema, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let req = Request::get("/?query=%7B%20value%20%7D")
+            .header("graphql-preflight", "1")
+            .body(Body::default())
+            .unwrap();
+        let resp = handle(&schema, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let etag = resp.headers()[header::ETAG].clone();
+
+        let req = Request::get("/?query=%7B%20value%20%7D")
+            .header("graphql-preflight", "1")
+            .header(header::IF_NONE_MATCH, etag)
+            .body(Body::default())
+            .unwrap();
+        let resp = handle(&schema, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+
+        let req = Request::put("/").body(Body::default()).unwrap();
+        let resp = handle(&schema, req).await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(resp.headers()[header::ALLOW], "GET, POST");
+    }
+}
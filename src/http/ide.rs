@@ -0,0 +1,312 @@
+use std::collections::BTreeMap;
+
+/// The credentials policy of the requests sent by a GraphQL IDE, see the `credentials` option of `fetch`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Credentials {
+    /// Never send cookies
+    Omit,
+
+    /// Send cookies to the same origin only
+    SameOrigin,
+
+    /// Always send cookies, also to cross-origin endpoints
+    Include,
+}
+
+impl Default for Credentials {
+    fn default() -> Self {
+        Credentials::SameOrigin
+    }
+}
+
+/// The configuration of a GraphQL IDE page
+///
+/// The same configuration generates the page of GraphiQL, GraphQL Playground or Altair, the integrations serve it
+/// with their `graphql_ide` route.
+///
+/// # Examples
+///
+/// ```rust
+/// use async_graphql::http::{Credentials, IdeConfig};
+///
+/// let source = IdeConfig::new("/graphql")
+///     .subscription_endpoint("/graphql/ws")
+///     .header("Authorization", "Bearer token")
+///     .credentials(Credentials::Include)
+///     .initial_query("{ version }")
+///     .title("My API")
+///     .setting("editorTheme", "dark")
+///     .graphiql_source();
+/// ```
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdeConfig {
+    endpoint: String,
+    subscription_endpoint: Option<String>,
+    headers: BTreeMap<String, String>,
+    credentials: Credentials,
+    query: Option<String>,
+    #[serde(skip)]
+    title: Option<String>,
+    settings: serde_json::Map<String, serde_json::Value>,
+}
+
+impl IdeConfig {
+    /// Create a configuration with the URL of the GraphQL endpoint.
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            subscription_endpoint: None,
+            headers: Default::default(),
+            credentials: Default::default(),
+            query: None,
+            title: None,
+            settings: Default::default(),
+        }
+    }
+
+    /// Set the URL of the WebSocket endpoint of the subscriptions, a relative URL uses the scheme `ws` or `wss` of the
+    /// page.
+    pub fn subscription_endpoint(self, endpoint: impl Into<String>) -> Self {
+        Self {
+            subscription_endpoint: Some(endpoint.into()),
+            ..self
+        }
+    }
+
+    /// Add a header sent with every request.
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+
+    /// Set the credentials policy of the requests, the default is `Credentials::SameOrigin`.
+    pub fn credentials(self, credentials: Credentials) -> Self {
+        Self {
+            credentials,
+            ..self
+        }
+    }
+
+    /// Set the query in the editor when the page is opened.
+    pub fn initial_query(self, query: impl Into<String>) -> Self {
+        Self {
+            query: Some(query.into()),
+            ..self
+        }
+    }
+
+    /// Set the title of the page.
+    pub fn title(self, title: impl Into<String>) -> Self {
+        Self {
+            title: Some(title.into()),
+            ..self
+        }
+    }
+
+    /// Set a setting of the IDE, the names are specific to every IDE.
+    ///
+    /// GraphiQL receives them as props, GraphQL Playground and Altair as their `settings`, such as
+    /// `editor.theme`.
+    pub fn setting(mut self, name: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.settings.insert(name.into(), value.into());
+        self
+    }
+
+    /// Generate the page for [GraphiQL](https://github.com/graphql/graphiql).
+    ///
+    /// Subscriptions use the `graphql-transport-ws` protocol.
+    pub fn graphiql_source(&self) -> String {
+        self.render(GRAPHIQL_TEMPLATE, "GraphiQL")
+    }
+
+    /// Generate the page for [GraphQL Playground](https://github.com/graphql/graphql-playground).
+    ///
+    /// Subscriptions use the `graphql-ws` protocol of `subscriptions-transport-ws`.
+    pub fn playground_source(&self) -> String {
+        self.render(PLAYGROUND_TEMPLATE, "GraphQL Playground")
+    }
+
+    /// Generate the page for [Altair](https://altair.sirmuel.design).
+    ///
+    /// Subscriptions use the `graphql-transport-ws` protocol.
+    pub fn altair_source(&self) -> String {
+        self.render(ALTAIR_TEMPLATE, "Altair")
+    }
+
+    fn render(&self, template: &str, default_title: &str) -> String {
+        // `</` would end the script element
+        let config = serde_json::to_string(self).unwrap().replace("</", "<\\/");
+        template
+            .replace(
+                "%TITLE%",
+                &escape_html(self.title.as_deref().unwrap_or(default_title)),
+            )
+            .replace("%CONFIG%", &config)
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => res.push_str("&amp;"),
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            '"' => res.push_str("&quot;"),
+            '\'' => res.push_str("&#39;"),
+            c => res.push(c),
+        }
+    }
+    res
+}
+
+/// Generate the page for GraphiQL
+pub fn graphiql_source(graphql_endpoint_url: &str, subscription_endpoint: Option<&str>) -> String {
+    with_subscription_endpoint(IdeConfig::new(graphql_endpoint_url), subscription_endpoint)
+        .graphiql_source()
+}
+
+/// Generate the page for GraphQL Playground
+pub fn playground_source(
+    graphql_endpoint_url: &str,
+    subscription_endpoint: Option<&str>,
+) -> String {
+    with_subscription_endpoint(IdeConfig::new(graphql_endpoint_url), subscription_endpoint)
+        .playground_source()
+}
+
+fn with_subscription_endpoint(config: IdeConfig, subscription_endpoint: Option<&str>) -> IdeConfig {
+    match subscription_endpoint {
+        Some(endpoint) => config.subscription_endpoint(endpoint),
+        None => config,
+    }
+}
+
+const GRAPHIQL_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8" />
+  <title>%TITLE%</title>
+  <style>body { margin: 0; } #graphiql { height: 100vh; }</style>
+  <link rel="stylesheet" href="https://unpkg.com/graphiql@2/graphiql.min.css" />
+  <script crossorigin src="https://unpkg.com/react@18/umd/react.production.min.js"></script>
+  <script crossorigin src="https://unpkg.com/react-dom@18/umd/react-dom.production.min.js"></script>
+  <script crossorigin src="https://unpkg.com/graphql-ws@5/umd/graphql-ws.min.js"></script>
+  <script crossorigin src="https://unpkg.com/graphiql@2/graphiql.min.js"></script>
+</head>
+<body>
+  <div id="graphiql">Loading...</div>
+  <script>
+    var config = %CONFIG%;
+    function wsUrl(url) {
+      var u = new URL(url, location.href);
+      if (u.protocol === 'http:') u.protocol = 'ws:';
+      if (u.protocol === 'https:') u.protocol = 'wss:';
+      return u.href;
+    }
+    var fetcher = GraphiQL.createFetcher({
+      url: new URL(config.endpoint, location.href).href,
+      headers: config.headers,
+      wsClient: config.subscriptionEndpoint
+        ? graphqlWs.createClient({ url: wsUrl(config.subscriptionEndpoint) })
+        : undefined,
+      fetch: function (input, init) {
+        return fetch(input, Object.assign({}, init, { credentials: config.credentials }));
+      },
+    });
+    var props = Object.assign({ fetcher: fetcher, defaultQuery: config.query || undefined }, config.settings);
+    ReactDOM.createRoot(document.getElementById('graphiql')).render(React.createElement(GraphiQL, props));
+  </script>
+</body>
+</html>
+"#;
+
+const PLAYGROUND_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8" />
+  <meta name="viewport" content="user-scalable=no, initial-scale=1.0, minimum-scale=1.0, maximum-scale=1.0, minimal-ui">
+  <title>%TITLE%</title>
+  <link rel="stylesheet" href="//cdn.jsdelivr.net/npm/graphql-playground-react/build/static/css/index.css" />
+  <link rel="shortcut icon" href="//cdn.jsdelivr.net/npm/graphql-playground-react/build/favicon.png" />
+  <script src="//cdn.jsdelivr.net/npm/graphql-playground-react/build/static/js/middleware.js"></script>
+</head>
+<body>
+  <div id="root">Loading...</div>
+  <script>
+    window.addEventListener('load', function () {
+      var config = %CONFIG%;
+      var options = {
+        endpoint: config.endpoint,
+        subscriptionEndpoint: config.subscriptionEndpoint,
+        settings: Object.assign({ 'request.credentials': config.credentials }, config.settings),
+      };
+      if (config.query || Object.keys(config.headers).length > 0) {
+        options.tabs = [{ endpoint: config.endpoint, query: config.query || '', headers: config.headers }];
+      }
+      GraphQLPlayground.init(document.getElementById('root'), options);
+    });
+  </script>
+</body>
+</html>
+"#;
+
+const ALTAIR_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8" />
+  <meta name="viewport" content="width=device-width, initial-scale=1" />
+  <title>%TITLE%</title>
+  <base href="https://cdn.jsdelivr.net/npm/altair-static@5/build/dist/" />
+  <link rel="stylesheet" href="styles.css" />
+</head>
+<body>
+  <app-root></app-root>
+  <script src="runtime.js"></script>
+  <script src="polyfills.js"></script>
+  <script src="main.js"></script>
+  <script>
+    var config = %CONFIG%;
+    // the URLs are resolved against the page, not the base of the Altair assets
+    function wsUrl(url) {
+      var u = new URL(url, location.href);
+      if (u.protocol === 'http:') u.protocol = 'ws:';
+      if (u.protocol === 'https:') u.protocol = 'wss:';
+      return u.href;
+    }
+    AltairGraphQL.init({
+      endpointURL: new URL(config.endpoint, location.href).href,
+      subscriptionsEndpoint: config.subscriptionEndpoint ? wsUrl(config.subscriptionEndpoint) : undefined,
+      initialSubscriptionsProvider: 'graphql-ws',
+      initialQuery: config.query || undefined,
+      initialHeaders: config.headers,
+      initialSettings: Object.assign({ 'request.withCredentials': config.credentials === 'include' }, config.settings),
+    });
+  </script>
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ide_source() {
+        let config = IdeConfig::new("/graphql")
+            .header("x-token", "</script><script>alert(1)</script>")
+            .credentials(Credentials::Include)
+            .title("<My API>");
+        let source = config.graphiql_source();
+        assert!(source.contains("<title>&lt;My API&gt;</title>"));
+        assert!(source.contains(r#""x-token":"<\/script><script>alert(1)<\/script>""#));
+        assert!(source.contains(r#""credentials":"include""#));
+        assert!(!source.contains("%CONFIG%"));
+
+        let source = playground_source("/graphql", Some("/ws"));
+        assert!(source.contains("<title>GraphQL Playground</title>"));
+        assert!(source.contains(r#""subscriptionEndpoint":"/ws""#));
+    }
+}
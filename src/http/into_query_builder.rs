@@ -1,3 +1,4 @@
+use crate::http::limited_reader::LimitedReader;
 use crate::http::multipart::{Multipart, PartData};
 use crate::http::GQLRequest;
 use crate::query::{IntoQueryBuilder, IntoQueryBuilderOpts};
@@ -5,6 +6,7 @@ use crate::{ParseRequestError, QueryBuilder};
 use futures::{AsyncRead, AsyncReadExt};
 use mime::Mime;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 #[async_trait::async_trait]
 impl<CT, Body> IntoQueryBuilder for (Option<CT>, Body)
@@ -13,62 +15,54 @@ where
     Body: AsyncRead + Send + Unpin,
 {
     async fn into_query_builder_opts(
-        mut self,
+        self,
         opts: &IntoQueryBuilderOpts,
     ) -> std::result::Result<QueryBuilder, ParseRequestError> {
-        if let Some(boundary) = self
-            .0
-            .and_then(|value| value.as_ref().parse::<Mime>().ok())
-            .and_then(|ct| {
-                if ct.essence_str() == mime::MULTIPART_FORM_DATA {
-                    ct.get_param("boundary")
-                        .map(|boundary| boundary.to_string())
-                } else {
-                    None
-                }
-            })
-        {
+        let mut body = LimitedReader::new(self.1, opts.max_body_size, || {
+            ParseRequestError::PayloadTooLarge
+        });
+        let content_type = self.0.and_then(|value| value.as_ref().parse::<Mime>().ok());
+        if let Some(boundary) = content_type.as_ref().and_then(|ct| {
+            if ct.essence_str() == mime::MULTIPART_FORM_DATA {
+                ct.get_param("boundary")
+                    .map(|boundary| boundary.to_string())
+            } else {
+                None
+            }
+        }) {
             // multipart
-            let mut multipart = Multipart::parse(
-                self.1,
-                boundary.as_str(),
-                opts.temp_dir.as_deref(),
-                opts.max_file_size,
-                opts.max_num_files,
-            )
-            .await?;
+            let mut multipart = Multipart::parse(body, boundary.as_str(), opts).await?;
             let gql_request: GQLRequest = {
                 let part = multipart
                     .remove("operations")
                     .ok_or_else(|| ParseRequestError::MissingOperatorsPart)?;
-                let reader = part.create_reader()?;
-                serde_json::from_reader(reader).map_err(ParseRequestError::InvalidRequest)?
+                serde_json::from_slice(&part.into_bytes().await?)
+                    .map_err(ParseRequestError::InvalidRequest)?
             };
             let mut map: HashMap<String, Vec<String>> = {
                 let part = multipart
                     .remove("map")
                     .ok_or_else(|| ParseRequestError::MissingMapPart)?;
-                let reader = part.create_reader()?;
-                serde_json::from_reader(reader).map_err(ParseRequestError::InvalidFilesMap)?
+                serde_json::from_slice(&part.into_bytes().await?)
+                    .map_err(ParseRequestError::InvalidFilesMap)?
             };
 
             let mut builder = gql_request.into_query_builder().await?;
 
-            // read files
-            for part in &multipart.parts {
-                if let Some(name) = &part.name {
+            // set files
+            for part in multipart.parts {
+                if let (Some(name), Some(filename), PartData::File(content)) =
+                    (&part.name, &part.filename, part.data)
+                {
                     if let Some(var_paths) = map.remove(name) {
+                        let content = Arc::from(content);
                         for var_path in var_paths {
-                            if let (Some(filename), PartData::File(path)) =
-                                (&part.filename, &part.data)
-                            {
-                                builder.set_upload(
-                                    &var_path,
-                                    &filename,
-                                    part.content_type.as_deref(),
-                                    path,
-                                );
-                            }
+                            builder.set_upload(
+                                &var_path,
+                                filename,
+                                part.content_type.as_deref(),
+                                Arc::clone(&content),
+                            );
                         }
                     }
                 }
@@ -78,17 +72,19 @@ where
                 return Err(ParseRequestError::MissingFiles);
             }
 
-            if let Some(temp_dir) = multipart.temp_dir {
-                builder.set_files_holder(temp_dir);
-            }
-
             Ok(builder)
+        } else if content_type
+            .as_ref()
+            .map(|ct| ct.essence_str() == "application/graphql")
+            .unwrap_or_default()
+        {
+            // the body is the query source
+            let mut query_source = String::new();
+            body.read_to_string(&mut query_source).await?;
+            Ok(QueryBuilder::new(query_source))
         } else {
             let mut data = Vec::new();
-            self.1
-                .read_to_end(&mut data)
-                .await
-                .map_err(ParseRequestError::Io)?;
+            body.read_to_end(&mut data).await?;
             let gql_request: GQLRequest =
                 serde_json::from_slice(&data).map_err(ParseRequestError::InvalidRequest)?;
             gql_request.into_query_builder().await
@@ -0,0 +1,69 @@
+use crate::ParseRequestError;
+use futures::task::{Context, Poll};
+use futures::AsyncRead;
+use std::io::{Error, ErrorKind, Result};
+use std::pin::Pin;
+
+/// Returns the `ParseRequestError` carried by an error of `LimitedReader`.
+pub fn into_parse_request_error(err: Error) -> std::result::Result<ParseRequestError, Error> {
+    if err
+        .get_ref()
+        .map(|err| err.is::<ParseRequestError>())
+        .unwrap_or_default()
+    {
+        Ok(*err.into_inner().unwrap().downcast().unwrap())
+    } else {
+        Err(err)
+    }
+}
+
+/// A reader that fails as soon as more than `limit` bytes are read, so an oversized body is rejected without being
+/// buffered.
+///
+/// The `std::io::Error` carries the `ParseRequestError` created by `err`.
+pub struct LimitedReader<R> {
+    inner: R,
+    remaining: Option<usize>,
+    err: fn() -> ParseRequestError,
+}
+
+impl<R> LimitedReader<R> {
+    pub fn new(inner: R, limit: Option<usize>, err: fn() -> ParseRequestError) -> Self {
+        Self {
+            inner,
+            remaining: limit,
+            err,
+        }
+    }
+
+    /// Returns the number of bytes that can still be read.
+    pub fn remaining(&self) -> Option<usize> {
+        self.remaining
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for LimitedReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let remaining = match self.remaining {
+            Some(remaining) => remaining,
+            None => return Pin::new(&mut self.inner).poll_read(cx, buf),
+        };
+
+        // read one byte more than allowed, so a body of exactly `limit` bytes is accepted
+        let len = buf.len().min(remaining.saturating_add(1));
+        match Pin::new(&mut self.inner).poll_read(cx, &mut buf[..len]) {
+            Poll::Ready(Ok(size)) if size > remaining => {
+                Poll::Ready(Err(Error::new(ErrorKind::Other, (self.err)())))
+            }
+            Poll::Ready(Ok(size)) => {
+                self.remaining = Some(remaining - size);
+                Poll::Ready(Ok(size))
+            }
+            res => res,
+        }
+    }
+}
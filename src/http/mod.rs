@@ -1,17 +1,38 @@
 //! A helper module that supports HTTP
 
-mod graphiql_source;
+mod body;
+mod csrf;
+mod handler;
+mod ide;
 mod into_query_builder;
+mod limited_reader;
 mod multipart;
-mod playground_source;
+mod request_metadata;
+mod response;
+mod sse;
 mod stream_body;
 mod token_reader;
+mod upload_storage;
+mod websocket;
 
 use itertools::Itertools;
 
-pub use graphiql_source::graphiql_source;
-pub use playground_source::playground_source;
+pub use body::Body;
+pub use csrf::CsrfGuard;
+pub use handler::{
+    handle, handle_opts, handle_sse, parse_request_error_response, parse_request_error_status,
+    receive_request, sse_response,
+};
+pub use ide::{graphiql_source, playground_source, Credentials, IdeConfig};
+pub(crate) use limited_reader::into_parse_request_error;
+pub use request_metadata::RequestMetadata;
+pub use response::{build_http_response, status_code, with_etag, ResponseMediaType};
+pub use sse::sse_stream;
 pub use stream_body::StreamBody;
+pub use upload_storage::{
+    MemoryUploadStorage, TempDirUploadStorage, UploadContent, UploadReader, UploadStorage,
+};
+pub use websocket::{serve_websocket, WebSocketMessage};
 
 use crate::query::{IntoQueryBuilder, IntoQueryBuilderOpts};
 use crate::{Error, ParseRequestError, QueryBuilder, QueryError, QueryResponse, Result, Variables};
@@ -33,12 +54,8 @@ pub struct GQLRequest {
     pub variables: Option<serde_json::Value>,
 }
 
-#[async_trait::async_trait]
-impl IntoQueryBuilder for GQLRequest {
-    async fn into_query_builder_opts(
-        self,
-        _opts: &IntoQueryBuilderOpts,
-    ) -> std::result::Result<QueryBuilder, ParseRequestError> {
+impl GQLRequest {
+    fn into_builder(self) -> QueryBuilder {
         let mut builder = QueryBuilder::new(self.query);
         if let Some(operation_name) = self.operation_name {
             builder = builder.operator_name(operation_name);
@@ -48,10 +65,78 @@ impl IntoQueryBuilder for GQLRequest {
                 builder = builder.variables(variables);
             }
         }
-        Ok(builder)
+        builder
     }
 }
 
+#[async_trait::async_trait]
+impl IntoQueryBuilder for GQLRequest {
+    async fn into_query_builder_opts(
+        self,
+        _opts: &IntoQueryBuilderOpts,
+    ) -> std::result::Result<QueryBuilder, ParseRequestError> {
+        Ok(self.into_builder())
+    }
+}
+
+/// Parse the query string of an HTTP GET request, such as `query={...}&operationName=...&variables={...}`,
+/// into a `QueryBuilder`. The variables are JSON encoded.
+///
+/// Mutations are disabled with `QueryBuilder::disable_mutation`, as the GraphQL over HTTP specification requires.
+///
+/// # Examples
+///
+/// ```rust
+/// use async_graphql::*;
+///
+/// struct QueryRoot;
+///
+/// #[Object]
+/// impl QueryRoot {
+///     async fn add(&self, a: i32, b: i32) -> i32 {
+///         a + b
+///     }
+/// }
+///
+/// async_std::task::block_on(async move {
+///     let schema = Schema::new(QueryRoot, EmptyMutation, EmptySubscription);
+///     let builder = async_graphql::http::parse_query_string(
+///         "query=query%20Add(%24b%3A%20Int!)%20%7B%20add(a%3A%2010%2C%20b%3A%20%24b)%20%7D&variables=%7B%22b%22%3A20%7D",
+///     ).unwrap();
+///     let res = builder.execute(&schema).await.unwrap().data;
+///     assert_eq!(res, serde_json::json!({ "add": 30 }));
+/// });
+/// ```
+pub fn parse_query_string(
+    query_string: &str,
+) -> std::result::Result<QueryBuilder, ParseRequestError> {
+    #[derive(Deserialize)]
+    struct QueryString {
+        query: String,
+        #[serde(rename = "operationName")]
+        operation_name: Option<String>,
+        variables: Option<String>,
+    }
+
+    let query_string: QueryString =
+        serde_urlencoded::from_str(query_string).map_err(ParseRequestError::InvalidQueryString)?;
+    let variables = match query_string.variables {
+        Some(variables) if !variables.is_empty() => {
+            Some(serde_json::from_str(&variables).map_err(ParseRequestError::InvalidRequest)?)
+        }
+        _ => None,
+    };
+    Ok(GQLRequest {
+        query: query_string.query,
+        operation_name: query_string
+            .operation_name
+            .filter(|operation_name| !operation_name.is_empty()),
+        variables,
+    }
+    .into_builder()
+    .disable_mutation())
+}
+
 /// Serializable GraphQL Response object
 pub struct GQLResponse(pub Result<QueryResponse>);
 
@@ -211,12 +296,87 @@ mod tests {
         assert_eq!(request.query, "{ a b c }");
     }
 
+    #[test]
+    fn test_parse_query_string() {
+        let builder = parse_query_string(
+            "query=query%20A(%24id%3A%20ID!)%20%7B%20a(id%3A%20%24id)%20%7D&operationName=A&variables=%7B%22id%22%3A%221%22%7D",
+        )
+        .unwrap();
+        assert_eq!(builder.query_source, "query A($id: ID!) { a(id: $id) }");
+        assert_eq!(builder.operation_name.as_deref(), Some("A"));
+        assert_eq!(
+            builder.variables.get("id"),
+            Some(&graphql_parser::query::Value::String("1".to_string()))
+        );
+        assert!(builder.mutation_disabled);
+
+        let builder = parse_query_string("query=%7B%20a%20%7D&operationName=&variables=").unwrap();
+        assert_eq!(builder.query_source, "{ a }");
+        assert!(builder.operation_name.is_none());
+
+        assert!(matches!(
+            parse_query_string("operationName=A"),
+            Err(ParseRequestError::InvalidQueryString(_))
+        ));
+        assert!(matches!(
+            parse_query_string("query=%7B%20a%20%7D&variables=%7B"),
+            Err(ParseRequestError::InvalidRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_graphql_body() {
+        async_std::task::block_on(async {
+            let builder = (
+                Some("application/graphql; charset=utf-8"),
+                "query A { a }".as_bytes(),
+            )
+                .into_query_builder()
+                .await
+                .unwrap();
+            assert_eq!(builder.query_source, "query A { a }");
+            assert!(builder.operation_name.is_none());
+        });
+    }
+
+    #[test]
+    fn test_max_body_size() {
+        async_std::task::block_on(async {
+            let body = r#"{"query": "{ a }"}"#;
+            let opts = IntoQueryBuilderOpts {
+                max_body_size: Some(body.len()),
+                ..IntoQueryBuilderOpts::default()
+            };
+            let builder = (Some("application/json"), body.as_bytes())
+                .into_query_builder_opts(&opts)
+                .await
+                .unwrap();
+            assert_eq!(builder.query_source, "{ a }");
+
+            let opts = IntoQueryBuilderOpts {
+                max_body_size: Some(body.len() - 1),
+                ..IntoQueryBuilderOpts::default()
+            };
+            let res = (Some("application/json"), body.as_bytes())
+                .into_query_builder_opts(&opts)
+                .await;
+            assert!(matches!(res, Err(ParseRequestError::PayloadTooLarge)));
+
+            let body = "--xyz\r\nContent-Disposition: form-data; name=\"operations\"\r\n\r\n{}\r\n--xyz--\r\n";
+            let res = (Some("multipart/form-data; boundary=xyz"), body.as_bytes())
+                .into_query_builder_opts(&opts)
+                .await;
+            assert!(matches!(res, Err(ParseRequestError::PayloadTooLarge)));
+        });
+    }
+
     #[test]
     fn test_response_data() {
         let resp = GQLResponse(Ok(QueryResponse {
             data: json!({"ok": true}),
             extensions: None,
             cache_control: Default::default(),
+            http_headers: Default::default(),
         }));
         assert_eq!(
             serde_json::to_value(resp).unwrap(),
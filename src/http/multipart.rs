@@ -1,37 +1,41 @@
+use super::limited_reader::LimitedReader;
 use super::token_reader::*;
+use super::upload_storage::{TempDirUploadStorage, UploadContent, UploadStorage};
+use crate::query::IntoQueryBuilderOpts;
 use crate::ParseRequestError;
 use futures::io::BufReader;
-use futures::{AsyncBufRead, AsyncRead};
+use futures::task::{Context, Poll};
+use futures::{AsyncBufRead, AsyncRead, AsyncReadExt, Future};
 use http::{header::HeaderName, HeaderMap, HeaderValue};
 use itertools::Itertools;
-use std::fs::File;
-use std::io::{Cursor, Read, Write};
-use std::path::{Path, PathBuf};
+use mime::Mime;
+use std::pin::Pin;
 use std::str::FromStr;
-use tempdir::TempDir;
 
 const MAX_HEADERS: usize = 16;
 
 pub enum PartData {
     Bytes(Vec<u8>),
-    File(PathBuf),
+    File(Box<dyn UploadContent>),
 }
 
 pub struct Part {
     pub name: Option<String>,
     pub filename: Option<String>,
     pub content_type: Option<String>,
-    pub size: usize,
     pub data: PartData,
 }
 
 impl Part {
-    pub fn create_reader<'a>(&'a self) -> Result<Box<dyn Read + 'a>, std::io::Error> {
-        let reader: Box<dyn Read> = match &self.data {
-            PartData::Bytes(bytes) => Box::new(Cursor::new(bytes)),
-            PartData::File(path) => Box::new(File::open(path)?),
-        };
-        Ok(reader)
+    pub async fn into_bytes(self) -> Result<Vec<u8>, ParseRequestError> {
+        match self.data {
+            PartData::Bytes(bytes) => Ok(bytes),
+            PartData::File(content) => {
+                let mut bytes = Vec::new();
+                content.reader()?.read_to_end(&mut bytes).await?;
+                Ok(bytes)
+            }
+        }
     }
 }
 
@@ -54,43 +58,75 @@ impl ContentDisposition {
     }
 }
 
+/// Reads the body of a part, it ends at the boundary.
+struct PartReader<'a, R> {
+    reader: &'a mut R,
+    boundary: &'a [u8],
+    state: ReadUntilState,
+    finished: bool,
+}
+
+impl<'a, R: AsyncBufRead + Unpin> AsyncRead for PartReader<'a, R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = &mut *self;
+        if this.finished || buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        let mut fut = this
+            .reader
+            .read_until_token(this.boundary, buf, &mut this.state);
+        match Pin::new(&mut fut).poll(cx) {
+            Poll::Ready(Ok((size, found))) => {
+                this.finished = found;
+                Poll::Ready(Ok(size))
+            }
+            Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
 pub struct Multipart {
-    pub temp_dir: Option<TempDir>,
     pub parts: Vec<Part>,
 }
 
 impl Multipart {
-    pub async fn parse<R: AsyncRead + Unpin>(
+    pub async fn parse<R: AsyncRead + Send + Unpin>(
         reader: R,
         boundary: &str,
-        temp_dir_in: Option<&Path>,
-        max_file_size: Option<usize>,
-        max_num_files: Option<usize>,
+        opts: &IntoQueryBuilderOpts,
     ) -> Result<Multipart, ParseRequestError> {
+        let default_storage;
+        let storage: &dyn UploadStorage = match &opts.upload_storage {
+            Some(storage) => storage.as_ref(),
+            None => {
+                default_storage = match &opts.temp_dir {
+                    Some(temp_dir) => TempDirUploadStorage::new_in(temp_dir.clone()),
+                    None => TempDirUploadStorage::default(),
+                };
+                &default_storage
+            }
+        };
         let mut reader = BufReader::new(reader);
-        let mut temp_dir = None;
         let mut parts = Vec::new();
         let boundary = format!("--{}", boundary);
-        let max_num_files = max_num_files.unwrap_or(std::usize::MAX);
-        let max_file_size = max_file_size.unwrap_or(std::usize::MAX);
-        let mut current_num_files = 0;
+        let mut limits = FileLimits {
+            max_num_files: opts.max_num_files.unwrap_or(std::usize::MAX),
+            max_file_size: opts.max_file_size,
+            remaining_total_size: opts.max_total_file_size,
+            allowed_content_types: opts.allowed_content_types.as_deref(),
+            num_files: 0,
+        };
 
         // first part
         reader.except_token(boundary.as_bytes()).await?;
         reader.except_token(b"\r\n").await?;
         let headers = Self::parse_headers(&mut reader).await?;
-        parts.push(
-            Self::parse_body(
-                &mut reader,
-                &headers,
-                &mut temp_dir,
-                temp_dir_in,
-                max_file_size,
-                &boundary,
-            )
-            .await?,
-        );
-        Multipart::check_max_num_files(&mut parts, max_num_files, &mut current_num_files)?;
+        parts.push(Self::parse_body(&mut reader, &headers, storage, &mut limits, &boundary).await?);
 
         // next parts
         loop {
@@ -101,34 +137,11 @@ impl Multipart {
 
             let headers = Self::parse_headers(&mut reader).await?;
             parts.push(
-                Self::parse_body(
-                    &mut reader,
-                    &headers,
-                    &mut temp_dir,
-                    temp_dir_in,
-                    max_file_size,
-                    &boundary,
-                )
-                .await?,
+                Self::parse_body(&mut reader, &headers, storage, &mut limits, &boundary).await?,
             );
-            Multipart::check_max_num_files(&mut parts, max_num_files, &mut current_num_files)?;
         }
 
-        Ok(Multipart { temp_dir, parts })
-    }
-
-    fn check_max_num_files(
-        parts: &mut Vec<Part>,
-        max_num_files: usize,
-        current_num_files: &mut usize,
-    ) -> Result<(), ParseRequestError> {
-        if parts.last().unwrap().filename.is_some() {
-            *current_num_files += 1;
-            if *current_num_files > max_num_files {
-                return Err(ParseRequestError::TooManyFiles);
-            }
-        }
-        Ok(())
+        Ok(Multipart { parts })
     }
 
     async fn parse_headers<R: AsyncBufRead + Unpin>(
@@ -168,12 +181,11 @@ impl Multipart {
         Ok(headers_map)
     }
 
-    async fn parse_body<R: AsyncBufRead + Unpin>(
-        mut reader: R,
+    async fn parse_body<R: AsyncBufRead + Send + Unpin>(
+        reader: &mut R,
         headers: &HeaderMap,
-        temp_dir: &mut Option<TempDir>,
-        temp_dir_in: Option<&Path>,
-        max_file_size: usize,
+        storage: &dyn UploadStorage,
+        limits: &mut FileLimits<'_>,
         boundary: &str,
     ) -> Result<Part, ParseRequestError> {
         let content_disposition = headers
@@ -188,50 +200,27 @@ impl Multipart {
             .get(http::header::CONTENT_TYPE)
             .and_then(|value| value.to_str().ok())
             .map(ToString::to_string);
-
-        let mut buf = [0; 4096];
-        let mut state = ReadUntilState::default();
-        let mut total_size = 0;
+        let mut part_reader = PartReader {
+            reader,
+            boundary: boundary.as_bytes(),
+            state: ReadUntilState::default(),
+            finished: false,
+        };
 
         let part_data = if let Some(filename) = &content_disposition.filename {
-            if temp_dir.is_none() {
-                if let Some(temp_dir_in) = temp_dir_in {
-                    *temp_dir = Some(TempDir::new_in(temp_dir_in, "async-graphql")?);
-                } else {
-                    *temp_dir = Some(TempDir::new("async-graphql")?);
-                }
-            }
-            let temp_dir = temp_dir.as_mut().unwrap();
-            let path = temp_dir.path().join(filename);
-            let mut file = File::create(&path)?;
-
-            loop {
-                let (size, found) = reader
-                    .read_until_token(boundary.as_bytes(), &mut buf, &mut state)
-                    .await?;
-                total_size += size;
-                if total_size > max_file_size {
-                    return Err(ParseRequestError::TooLarge);
-                }
-                file.write_all(&buf[..size])?;
-                if found {
-                    break;
-                }
-            }
-            PartData::File(path)
+            limits.check_file(content_type.as_deref())?;
+            let mut content = limits.limit_file(part_reader);
+            let stored = storage
+                .store(filename, content_type.as_deref(), &mut content)
+                .await?;
+            // skip what the storage didn't read, the limits still apply
+            let mut buf = [0; 4096];
+            while content.read(&mut buf).await? > 0 {}
+            limits.file_stored(&content);
+            PartData::File(stored)
         } else {
             let mut body = Vec::new();
-
-            loop {
-                let (size, found) = reader
-                    .read_until_token(boundary.as_bytes(), &mut buf, &mut state)
-                    .await?;
-                total_size += size;
-                body.extend_from_slice(&buf[..size]);
-                if found {
-                    break;
-                }
-            }
+            part_reader.read_to_end(&mut body).await?;
             PartData::Bytes(body)
         };
 
@@ -239,7 +228,6 @@ impl Multipart {
             name: content_disposition.name,
             filename: content_disposition.filename,
             content_type,
-            size: total_size,
             data: part_data,
         })
     }
@@ -259,6 +247,70 @@ impl Multipart {
     }
 }
 
+/// The limits of `IntoQueryBuilderOpts` applied to the files
+struct FileLimits<'a> {
+    max_num_files: usize,
+    max_file_size: Option<usize>,
+    remaining_total_size: Option<usize>,
+    allowed_content_types: Option<&'a [String]>,
+    num_files: usize,
+}
+
+impl<'a> FileLimits<'a> {
+    fn check_file(&mut self, content_type: Option<&str>) -> Result<(), ParseRequestError> {
+        self.num_files += 1;
+        if self.num_files > self.max_num_files {
+            return Err(ParseRequestError::TooManyFiles);
+        }
+
+        if let Some(allowed_content_types) = self.allowed_content_types {
+            let content_type = content_type
+                .and_then(|content_type| content_type.parse::<Mime>().ok())
+                .unwrap_or(mime::APPLICATION_OCTET_STREAM);
+            let allowed = allowed_content_types.iter().any(|allowed| {
+                allowed.parse::<Mime>().ok().map_or(false, |allowed| {
+                    (allowed.type_() == mime::STAR || allowed.type_() == content_type.type_())
+                        && (allowed.subtype() == mime::STAR
+                            || allowed.subtype() == content_type.subtype())
+                })
+            });
+            if !allowed {
+                return Err(ParseRequestError::ContentTypeNotAllowed(
+                    content_type.essence_str().to_string(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the limit of the size of the next file, and the error when it's exceeded.
+    fn file_limit(&self) -> (Option<usize>, fn() -> ParseRequestError) {
+        match (self.max_file_size, self.remaining_total_size) {
+            (Some(max_file_size), Some(remaining)) if remaining < max_file_size => {
+                (Some(remaining), || ParseRequestError::PayloadTooLarge)
+            }
+            (None, Some(remaining)) => (Some(remaining), || ParseRequestError::PayloadTooLarge),
+            (max_file_size, _) => (max_file_size, || ParseRequestError::TooLarge),
+        }
+    }
+
+    fn limit_file<R>(&self, reader: R) -> LimitedReader<R> {
+        let (limit, err) = self.file_limit();
+        LimitedReader::new(reader, limit, err)
+    }
+
+    fn file_stored<R>(&mut self, content: &LimitedReader<R>) {
+        if let (Some(remaining_total_size), (Some(limit), _), Some(remaining)) = (
+            self.remaining_total_size,
+            self.file_limit(),
+            content.remaining(),
+        ) {
+            self.remaining_total_size = Some(remaining_total_size - (limit - remaining));
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -273,10 +325,13 @@ mod tests {
              Content-Type: text/plain; charset=utf-8\r\n\r\n\
              data\
              --abbc761f78ff4d7cb7573b5a23f96ef0--\r\n";
-        let multipart =
-            Multipart::parse(data, "abbc761f78ff4d7cb7573b5a23f96ef0", None, None, None)
-                .await
-                .unwrap();
+        let multipart = Multipart::parse(
+            data,
+            "abbc761f78ff4d7cb7573b5a23f96ef0",
+            &Default::default(),
+        )
+        .await
+        .unwrap();
         assert_eq!(multipart.parts.len(), 2);
 
         let part_1 = &multipart.parts[0];
@@ -310,9 +365,10 @@ mod tests {
         assert!(Multipart::parse(
             data,
             "abbc761f78ff4d7cb7573b5a23f96ef0",
-            None,
-            Some(5),
-            None,
+            &IntoQueryBuilderOpts {
+                max_file_size: Some(5),
+                ..IntoQueryBuilderOpts::default()
+            },
         )
         .await
         .is_ok());
@@ -320,9 +376,10 @@ mod tests {
         assert!(Multipart::parse(
             data,
             "abbc761f78ff4d7cb7573b5a23f96ef0",
-            None,
-            Some(6),
-            None,
+            &IntoQueryBuilderOpts {
+                max_file_size: Some(6),
+                ..IntoQueryBuilderOpts::default()
+            },
         )
         .await
         .is_ok());
@@ -330,9 +387,10 @@ mod tests {
         assert!(Multipart::parse(
             data,
             "abbc761f78ff4d7cb7573b5a23f96ef0",
-            None,
-            Some(4),
-            None,
+            &IntoQueryBuilderOpts {
+                max_file_size: Some(4),
+                ..IntoQueryBuilderOpts::default()
+            },
         )
         .await
         .is_err());
@@ -355,9 +413,10 @@ mod tests {
         assert!(Multipart::parse(
             data,
             "abbc761f78ff4d7cb7573b5a23f96ef0",
-            None,
-            None,
-            Some(1)
+            &IntoQueryBuilderOpts {
+                max_num_files: Some(1),
+                ..IntoQueryBuilderOpts::default()
+            },
         )
         .await
         .is_err());
@@ -365,9 +424,10 @@ mod tests {
         assert!(Multipart::parse(
             data,
             "abbc761f78ff4d7cb7573b5a23f96ef0",
-            None,
-            None,
-            Some(2)
+            &IntoQueryBuilderOpts {
+                max_num_files: Some(2),
+                ..IntoQueryBuilderOpts::default()
+            },
         )
         .await
         .is_ok());
@@ -375,11 +435,56 @@ mod tests {
         assert!(Multipart::parse(
             data,
             "abbc761f78ff4d7cb7573b5a23f96ef0",
-            None,
-            None,
-            Some(3)
+            &IntoQueryBuilderOpts {
+                max_num_files: Some(3),
+                ..IntoQueryBuilderOpts::default()
+            },
         )
         .await
         .is_ok());
     }
+
+    #[async_std::test]
+    async fn test_parse_limit_content_type_and_total_size() {
+        let data: &[u8] = b"--abbc761f78ff4d7cb7573b5a23f96ef0\r\n\
+             Content-Disposition: form-data; name=\"file\"; filename=\"fn.png\"\r\n\
+             Content-Type: image/png\r\n\r\n\
+             12345\
+             --abbc761f78ff4d7cb7573b5a23f96ef0\r\n\
+             Content-Disposition: form-data; name=\"file1\"; filename=\"fn1.jpg\"\r\n\
+             Content-Type: image/jpeg\r\n\r\n\
+             12345\
+             --abbc761f78ff4d7cb7573b5a23f96ef0--\r\n";
+
+        let parse = |opts: IntoQueryBuilderOpts| async move {
+            Multipart::parse(data, "abbc761f78ff4d7cb7573b5a23f96ef0", &opts).await
+        };
+
+        assert!(parse(IntoQueryBuilderOpts {
+            allowed_content_types: Some(vec!["image/*".to_string()]),
+            max_total_file_size: Some(10),
+            ..IntoQueryBuilderOpts::default()
+        })
+        .await
+        .is_ok());
+
+        assert!(matches!(
+            parse(IntoQueryBuilderOpts {
+                allowed_content_types: Some(vec!["image/png".to_string()]),
+                ..IntoQueryBuilderOpts::default()
+            })
+            .await,
+            Err(ParseRequestError::ContentTypeNotAllowed(content_type)) if content_type == "image/jpeg"
+        ));
+
+        assert!(matches!(
+            parse(IntoQueryBuilderOpts {
+                max_file_size: Some(5),
+                max_total_file_size: Some(9),
+                ..IntoQueryBuilderOpts::default()
+            })
+            .await,
+            Err(ParseRequestError::PayloadTooLarge)
+        ));
+    }
 }
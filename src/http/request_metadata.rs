@@ -0,0 +1,41 @@
+use http::{HeaderMap, Method, Uri};
+
+/// Metadata of the HTTP request that carries a GraphQL query
+///
+/// The integrations add it to the context data of every query, you can access it with
+/// `Context::data_opt::<RequestMetadata>()`.
+///
+/// # Examples
+///
+/// ```rust
+/// use async_graphql::*;
+/// use async_graphql::http::RequestMetadata;
+///
+/// struct QueryRoot;
+///
+/// #[Object]
+/// impl QueryRoot {
+///     async fn user_agent<'a>(&self, ctx: &'a Context<'_>) -> Option<&'a str> {
+///         ctx.data_opt::<RequestMetadata>()
+///             .and_then(|metadata| metadata.header("user-agent"))
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RequestMetadata {
+    /// The HTTP method
+    pub method: Method,
+
+    /// The request URI
+    pub uri: Uri,
+
+    /// The request headers
+    pub headers: HeaderMap,
+}
+
+impl RequestMetadata {
+    /// Returns the first value of the header with the specified name, if it exists and is a valid string.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|value| value.to_str().ok())
+    }
+}
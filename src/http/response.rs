@@ -0,0 +1,293 @@
+use crate::http::GQLResponse;
+use crate::{Error, QueryError, QueryResponse, Result};
+use fnv::FnvHasher;
+use http::header::{self, HeaderValue};
+use http::{Method, Response, StatusCode};
+use std::hash::Hasher;
+
+/// The media type of a GraphQL response
+///
+/// The [GraphQL over HTTP](https://github.com/graphql/graphql-over-http/blob/main/spec/GraphQLOverHTTP.md)
+/// specification defines `application/graphql-response+json`, `application/json` is used by legacy clients.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ResponseMediaType {
+    /// `application/graphql-response+json`
+    GraphQLResponseJson,
+
+    /// `application/json`
+    Json,
+}
+
+impl Default for ResponseMediaType {
+    fn default() -> Self {
+        ResponseMediaType::Json
+    }
+}
+
+impl ResponseMediaType {
+    /// Returns the media type string.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ResponseMediaType::GraphQLResponseJson => "application/graphql-response+json",
+            ResponseMediaType::Json => "application/json",
+        }
+    }
+
+    /// Choose the media type from the value of the `Accept` header of the request.
+    ///
+    /// The supported media type with the highest quality is chosen, `application/graphql-response+json` wins a tie.
+    /// `application/json` is used if the header is missing or accepts neither of them.
+    pub fn negotiate(accept: Option<&str>) -> Self {
+        let mut best = None;
+        for item in accept.unwrap_or_default().split(',') {
+            let mut params = item.split(';').map(str::trim);
+            let media_type = match params.next().unwrap_or_default() {
+                "application/graphql-response+json" => ResponseMediaType::GraphQLResponseJson,
+                "application/json" => ResponseMediaType::Json,
+                _ => continue,
+            };
+            let quality = params
+                .filter(|param| param.starts_with("q="))
+                .find_map(|param| param[2..].parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if quality <= 0.0 {
+                continue;
+            }
+            best = match best {
+                Some((best_type, best_quality))
+                    if best_quality > quality
+                        || (best_quality == quality
+                            && best_type == ResponseMediaType::GraphQLResponseJson) =>
+                {
+                    Some((best_type, best_quality))
+                }
+                _ => Some((media_type, quality)),
+            };
+        }
+        best.map(|(media_type, _)| media_type).unwrap_or_default()
+    }
+}
+
+/// Returns the status code of a GraphQL response.
+///
+/// A mutation over GET is `405 Method Not Allowed`. Otherwise `application/json` responses are always `200 OK`, and
+/// `application/graphql-response+json` responses without data are `400 Bad Request` for the errors of the request,
+/// such as parse and validation errors, and `500 Internal Server Error` for the errors of the execution.
+pub fn status_code(res: &Result<QueryResponse>, media_type: ResponseMediaType) -> StatusCode {
+    match res {
+        Ok(_) => StatusCode::OK,
+        Err(Error::Query {
+            err: QueryError::MutationNotAllowed,
+            ..
+        }) => StatusCode::METHOD_NOT_ALLOWED,
+        Err(_) if media_type == ResponseMediaType::Json => StatusCode::OK,
+        Err(Error::Query { err, .. }) => match err {
+            QueryError::FieldError { .. }
+            | QueryError::EntityNotFound
+            | QueryError::TypeNameNotExists => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
+        },
+        Err(_) => StatusCode::BAD_REQUEST,
+    }
+}
+
+/// Build the HTTP response of a GraphQL response.
+///
+/// It sets the status code with `status_code`, the `Content-Type` and `Cache-Control` headers, and the headers set by
+/// the resolvers. The integrations use it, so they behave the same.
+pub fn build_http_response(
+    res: Result<QueryResponse>,
+    media_type: ResponseMediaType,
+) -> Response<Vec<u8>> {
+    let mut resp = Response::new(Vec::new());
+    *resp.status_mut() = status_code(&res, media_type);
+    let headers = resp.headers_mut();
+
+    match &res {
+        Ok(query_response) => {
+            if let Some(value) = query_response
+                .cache_control
+                .value()
+                .and_then(|value| HeaderValue::from_str(&value).ok())
+            {
+                headers.insert(header::CACHE_CONTROL, value);
+            }
+            for (name, value) in &query_response.http_headers {
+                headers.append(name, value.clone());
+            }
+        }
+        Err(Error::Query {
+            err: QueryError::MutationNotAllowed,
+            ..
+        }) => {
+            headers.insert(header::ALLOW, HeaderValue::from_static("POST"));
+        }
+        Err(_) => {}
+    }
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(media_type.as_str()),
+    );
+
+    *resp.body_mut() = serde_json::to_vec(&GQLResponse(res)).unwrap();
+    resp
+}
+
+/// Add an `ETag` to the successful response of a GET request, and replace the response with `304 Not Modified` if the
+/// `If-None-Match` header of the request matches it.
+///
+/// The `ETag` is a hash of the media type and the body, so browsers and CDNs can revalidate the cached responses of
+/// the queries.
+pub fn with_etag(
+    mut resp: Response<Vec<u8>>,
+    method: &Method,
+    if_none_match: Option<&str>,
+) -> Response<Vec<u8>> {
+    if method != Method::GET || resp.status() != StatusCode::OK {
+        return resp;
+    }
+
+    let mut hasher = FnvHasher::default();
+    if let Some(content_type) = resp.headers().get(header::CONTENT_TYPE) {
+        hasher.write(content_type.as_bytes());
+    }
+    hasher.write(resp.body());
+    let etag = format!("\"{:016x}\"", hasher.finish());
+
+    let not_modified = if_none_match
+        .map(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+        })
+        .unwrap_or_default();
+    resp.headers_mut()
+        .insert(header::ETAG, HeaderValue::from_str(&etag).unwrap());
+    if not_modified {
+        *resp.status_mut() = StatusCode::NOT_MODIFIED;
+        resp.headers_mut().remove(header::CONTENT_TYPE);
+        resp.body_mut().clear();
+    }
+    resp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphql_parser::Pos;
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(ResponseMediaType::negotiate(None), ResponseMediaType::Json);
+        assert_eq!(
+            ResponseMediaType::negotiate(Some("*/*")),
+            ResponseMediaType::Json
+        );
+        assert_eq!(
+            ResponseMediaType::negotiate(Some("application/graphql-response+json")),
+            ResponseMediaType::GraphQLResponseJson
+        );
+        assert_eq!(
+            ResponseMediaType::negotiate(Some(
+                "application/json, application/graphql-response+json"
+            )),
+            ResponseMediaType::GraphQLResponseJson
+        );
+        assert_eq!(
+            ResponseMediaType::negotiate(Some(
+                "application/graphql-response+json;q=0.9, application/json"
+            )),
+            ResponseMediaType::Json
+        );
+        assert_eq!(
+            ResponseMediaType::negotiate(Some(
+                "application/graphql-response+json; charset=utf-8, application/json; q=0.5"
+            )),
+            ResponseMediaType::GraphQLResponseJson
+        );
+        assert_eq!(
+            ResponseMediaType::negotiate(Some("application/graphql-response+json;q=0")),
+            ResponseMediaType::Json
+        );
+    }
+
+    #[test]
+    fn test_status_code() {
+        let parse_error = Err(Error::Parse {
+            line: 1,
+            column: 1,
+            message: "error".to_string(),
+        });
+        let field_error = Err(Error::Query {
+            pos: Pos::default(),
+            path: None,
+            err: QueryError::FieldError {
+                err: "error".to_string(),
+                extended_error: None,
+            },
+        });
+        let mutation_not_allowed = Err(Error::Query {
+            pos: Pos::default(),
+            path: None,
+            err: QueryError::MutationNotAllowed,
+        });
+
+        assert_eq!(
+            status_code(&parse_error, ResponseMediaType::Json),
+            StatusCode::OK
+        );
+        assert_eq!(
+            status_code(&parse_error, ResponseMediaType::GraphQLResponseJson),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status_code(&field_error, ResponseMediaType::Json),
+            StatusCode::OK
+        );
+        assert_eq!(
+            status_code(&field_error, ResponseMediaType::GraphQLResponseJson),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert_eq!(
+            status_code(&mutation_not_allowed, ResponseMediaType::Json),
+            StatusCode::METHOD_NOT_ALLOWED
+        );
+
+        let resp = build_http_response(mutation_not_allowed, ResponseMediaType::Json);
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(resp.headers()[header::ALLOW], "POST");
+        assert_eq!(resp.headers()[header::CONTENT_TYPE], "application/json");
+    }
+
+    #[test]
+    fn test_etag() {
+        let ok = || {
+            build_http_response(
+                Ok(QueryResponse {
+                    data: serde_json::json!({"value": 1}),
+                    extensions: None,
+                    cache_control: Default::default(),
+                    http_headers: Default::default(),
+                }),
+                ResponseMediaType::Json,
+            )
+        };
+
+        let resp = with_etag(ok(), &Method::POST, None);
+        assert!(resp.headers().get(header::ETAG).is_none());
+
+        let resp = with_etag(ok(), &Method::GET, None);
+        assert_eq!(resp.status(), StatusCode::OK);
+        let etag = resp.headers()[header::ETAG].to_str().unwrap().to_string();
+
+        let resp = with_etag(ok(), &Method::GET, Some("\"other\""));
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(!resp.body().is_empty());
+
+        let resp = with_etag(ok(), &Method::GET, Some(&format!("\"other\", W/{}", etag)));
+        assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(resp.headers()[header::ETAG], etag.as_str());
+        assert!(resp.body().is_empty());
+    }
+}
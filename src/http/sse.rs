@@ -0,0 +1,55 @@
+use crate::http::GQLResponse;
+use crate::{ObjectType, QueryBuilder, QueryResponse, Result, Schema, SubscriptionType};
+use bytes::Bytes;
+use futures::{future, stream, Stream, StreamExt};
+use std::sync::Arc;
+
+/// Execute a subscription and convert its results to Server-Sent Events
+///
+/// Every result is sent as an `event: next` frame with the JSON response as data, and an
+/// `event: complete` frame is sent when the subscription ends. If an error occurs, it's sent as the
+/// last `next` frame, followed by the `complete` frame.
+///
+/// The response should be sent with the `text/event-stream` content type.
+pub fn sse_stream<Query, Mutation, Subscription>(
+    schema: Schema<Query, Mutation, Subscription>,
+    builder: QueryBuilder,
+) -> impl Stream<Item = Bytes> + Send + 'static
+where
+    Query: ObjectType + Send + Sync + 'static,
+    Mutation: ObjectType + Send + Sync + 'static,
+    Subscription: SubscriptionType + Send + Sync + 'static,
+{
+    stream::once(async move {
+        schema
+            .create_subscription_stream(
+                &builder.query_source,
+                builder.operation_name.as_deref(),
+                builder.variables,
+                builder.ctx_data.map(Arc::new),
+            )
+            .await
+    })
+    .flat_map(|res| match res {
+        Ok(stream) => stream.boxed(),
+        Err(err) => stream::once(future::ready(Err(err))).boxed(),
+    })
+    .scan(false, |failed, res: Result<QueryResponse>| {
+        if *failed {
+            return future::ready(None);
+        }
+        *failed = res.is_err();
+        future::ready(Some(next_event(res)))
+    })
+    .chain(stream::once(future::ready(Bytes::from_static(
+        b"event: complete\ndata:\n\n",
+    ))))
+}
+
+fn next_event(res: Result<QueryResponse>) -> Bytes {
+    let resp = GQLResponse(res);
+    let mut event = b"event: next\ndata: ".to_vec();
+    serde_json::to_writer(&mut event, &resp).unwrap();
+    event.extend_from_slice(b"\n\n");
+    event.into()
+}
@@ -66,6 +66,8 @@ impl<'a, R: AsyncBufRead + ?Sized + Unpin> Future for ReadUntilToken<'a, R> {
                 }
             } else {
                 match Pin::new(&mut this.reader).poll_fill_buf(cx) {
+                    // the bytes in `buf` are already consumed, return them before waiting
+                    Poll::Pending if rsz > 0 => return Poll::Ready(Ok((rsz, false))),
                     Poll::Pending => return Poll::Pending,
                     Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
                     Poll::Ready(Ok(data)) if data.is_empty() => {
@@ -0,0 +1,123 @@
+use bytes::Bytes;
+use futures::io::{AllowStdIo, Cursor};
+use futures::{AsyncRead, AsyncReadExt};
+use std::fs::File;
+use std::io::{Result, Write};
+use std::path::PathBuf;
+use tempdir::TempDir;
+
+/// A reader of the content of an uploaded file
+pub type UploadReader = Box<dyn AsyncRead + Send + Unpin>;
+
+/// The content of an uploaded file kept by an `UploadStorage`
+///
+/// It lives as long as the request, dropping it should release the resources of the file.
+pub trait UploadContent: Send + Sync {
+    /// Open a reader of the content, it can be called more than once.
+    fn reader(&self) -> Result<UploadReader>;
+}
+
+/// Where the files of a multipart request are stored while the request is executed
+///
+/// `IntoQueryBuilderOpts::upload_storage` selects the storage, the default is `TempDirUploadStorage`. Implement this
+/// trait to stream the files somewhere else, such as an object store.
+#[async_trait::async_trait]
+pub trait UploadStorage: Send + Sync {
+    /// Store a file read from `content`.
+    ///
+    /// The limits of `IntoQueryBuilderOpts` are enforced by `content`, an error returned by it must be returned as is.
+    async fn store(
+        &self,
+        filename: &str,
+        content_type: Option<&str>,
+        content: &mut (dyn AsyncRead + Send + Unpin),
+    ) -> Result<Box<dyn UploadContent>>;
+}
+
+/// Store the files in temporary directories, they are deleted with the request
+#[derive(Default)]
+pub struct TempDirUploadStorage {
+    temp_dir: Option<PathBuf>,
+}
+
+impl TempDirUploadStorage {
+    /// Create the temporary directories in `path` instead of the system temporary path.
+    pub fn new_in(path: impl Into<PathBuf>) -> Self {
+        Self {
+            temp_dir: Some(path.into()),
+        }
+    }
+}
+
+struct TempFile {
+    dir: TempDir,
+}
+
+impl TempFile {
+    fn path(&self) -> PathBuf {
+        // the name sent by the client is never used as a path
+        self.dir.path().join("content")
+    }
+}
+
+impl UploadContent for TempFile {
+    fn reader(&self) -> Result<UploadReader> {
+        Ok(Box::new(AllowStdIo::new(File::open(self.path())?)))
+    }
+}
+
+#[async_trait::async_trait]
+impl UploadStorage for TempDirUploadStorage {
+    async fn store(
+        &self,
+        _filename: &str,
+        _content_type: Option<&str>,
+        content: &mut (dyn AsyncRead + Send + Unpin),
+    ) -> Result<Box<dyn UploadContent>> {
+        let dir = match &self.temp_dir {
+            Some(temp_dir) => TempDir::new_in(temp_dir, "async-graphql")?,
+            None => TempDir::new("async-graphql")?,
+        };
+        let temp_file = TempFile { dir };
+        let mut file = File::create(temp_file.path())?;
+        let mut buf = [0; 4096];
+
+        loop {
+            let size = content.read(&mut buf).await?;
+            if size == 0 {
+                break;
+            }
+            file.write_all(&buf[..size])?;
+        }
+        Ok(Box::new(temp_file))
+    }
+}
+
+/// Keep the files in memory
+///
+/// Set `IntoQueryBuilderOpts::max_file_size` or `IntoQueryBuilderOpts::max_total_file_size` with it, every byte of
+/// the files is kept until the request is finished.
+#[derive(Default)]
+pub struct MemoryUploadStorage;
+
+struct MemoryFile(Bytes);
+
+impl UploadContent for MemoryFile {
+    fn reader(&self) -> Result<UploadReader> {
+        Ok(Box::new(Cursor::new(self.0.clone())))
+    }
+}
+
+#[async_trait::async_trait]
+impl UploadStorage for MemoryUploadStorage {
+    async fn store(
+        &self,
+        _filename: &str,
+        _content_type: Option<&str>,
+        content: &mut (dyn AsyncRead + Send + Unpin),
+    ) -> Result<Box<dyn UploadContent>> {
+        let mut data = Vec::new();
+        content.read_to_end(&mut data).await?;
+        Ok(Box::new(MemoryFile(data.into())))
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{CloseFrame, ObjectType, Schema, SubscriptionTransport, SubscriptionType};
+use bytes::Bytes;
+use futures::channel::mpsc;
+use futures::{pin_mut, select, FutureExt, Sink, SinkExt, Stream, StreamExt};
+
+/// A message of a WebSocket
+///
+/// The integrations convert the messages of their framework from and to it, pings and pongs are answered by the
+/// frameworks and are not forwarded.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum WebSocketMessage {
+    /// A text message
+    Text(String),
+
+    /// A close message
+    ///
+    /// It's received when the client closes the connection or the connection fails, and it's sent with the close
+    /// code of the transport before the socket is closed, see `SubscriptionStream::close_frame`.
+    Close(Option<CloseFrame>),
+}
+
+/// Serve a subscription connection over a WebSocket
+///
+/// The messages of the client are read from `stream` and the messages of the server are written to `sink`, the
+/// integrations only convert them from and to the messages of their framework. `ticks` is the timer of the
+/// connection, see `SubscriptionStream::timer`.
+///
+/// It returns when the client or the transport closes the connection, after the `on_disconnect` hook has run. If the
+/// transport closed the connection with a close code, a `WebSocketMessage::Close` is sent before the sink is closed.
+pub async fn serve_websocket<Query, Mutation, Subscription, T, Tx, Rx, Ticks>(
+    schema: Schema<Query, Mutation, Subscription>,
+    transport: T,
+    mut sink: Tx,
+    stream: Rx,
+    ticks: Ticks,
+) where
+    Query: ObjectType + Send + Sync + 'static,
+    Mutation: ObjectType + Send + Sync + 'static,
+    Subscription: SubscriptionType + Send + Sync + 'static,
+    T: SubscriptionTransport,
+    Tx: Sink<WebSocketMessage> + Unpin,
+    Rx: Stream<Item = WebSocketMessage> + Unpin,
+    Ticks: Stream<Item = ()> + Send + 'static,
+{
+    let (stx, srx) = schema.subscription_connection(transport);
+    let mut srx = srx.timer(ticks).fuse();
+    let mut sink_open = true;
+
+    // Both directions are driven concurrently, the connection must keep running while a message of the client waits
+    // for room in its channel.
+    {
+        let incoming = forward_incoming(stream, stx).fuse();
+        let outgoing = async {
+            while let Some(bytes) = srx.next().await {
+                if !send_bytes(&mut sink, bytes).await {
+                    sink_open = false;
+                    break;
+                }
+            }
+        }
+        .fuse();
+        pin_mut!(incoming, outgoing);
+        select! {
+            _ = incoming => {},
+            _ = outgoing => {},
+        }
+    }
+
+    // The sender of the connection is dropped with `incoming`, drain the connection so that it runs the
+    // `on_disconnect` hook, then close the socket.
+    while let Some(bytes) = srx.next().await {
+        if sink_open && !send_bytes(&mut sink, bytes).await {
+            sink_open = false;
+        }
+    }
+    if let (true, Some(close_frame)) = (sink_open, srx.get_ref().close_frame()) {
+        let _ = sink
+            .send(WebSocketMessage::Close(Some(close_frame.clone())))
+            .await;
+    }
+    let _ = sink.close().await;
+}
+
+/// Forward the messages of the client to the connection until the client closes the connection.
+async fn forward_incoming<Rx>(mut stream: Rx, mut stx: mpsc::Sender<Bytes>)
+where
+    Rx: Stream<Item = WebSocketMessage> + Unpin,
+{
+    while let Some(WebSocketMessage::Text(text)) = stream.next().await {
+        if stx.send(Bytes::from(text)).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Send a message of the connection to the client, returns `false` if the socket is closed.
+async fn send_bytes<Tx: Sink<WebSocketMessage> + Unpin>(sink: &mut Tx, bytes: Bytes) -> bool {
+    // the transports only send JSON, they can't send invalid UTF-8
+    match String::from_utf8(bytes.to_vec()) {
+        Ok(text) => sink.send(WebSocketMessage::Text(text)).await.is_ok(),
+        Err(_) => false,
+    }
+}
@@ -79,7 +79,9 @@ extern crate serde_derive;
 
 mod base;
 mod context;
+mod data_factory;
 mod error;
+mod look_ahead;
 mod model;
 mod mutation_resolver;
 mod query;
@@ -111,22 +113,27 @@ pub use base::{ScalarType, Type};
 pub use context::{
     Context, ContextBase, Data, Environment, QueryPathNode, QueryPathSegment, Variables,
 };
+pub use data_factory::DataFactory;
 pub use error::{
     Error, ErrorExtensions, FieldError, FieldResult, ParseRequestError, QueryError, ResultExt,
+    SchemaError,
 };
 pub use graphql_parser::query::Value;
 pub use graphql_parser::Pos;
+pub use look_ahead::Lookahead;
 pub use query::{IntoQueryBuilder, IntoQueryBuilderOpts, QueryBuilder, QueryResponse};
 pub use registry::CacheControl;
 pub use scalars::{Any, Json, ID};
 pub use schema::Schema;
 pub use subscription::{
-    SimpleBroker, SubscriptionStream, SubscriptionStreams, SubscriptionTransport,
-    WebSocketTransport,
+    Broker, CloseFrame, GraphQLTransportWS, MemoryBroker, OverflowPolicy, SimpleBroker,
+    SubscriptionStream, SubscriptionStreams, SubscriptionTransport, WebSocketHooks,
+    WebSocketProtocols, WebSocketTransport,
 };
 pub use types::{
-    Connection, Cursor, DataSource, EmptyEdgeFields, EmptyMutation, EmptySubscription, PageInfo,
-    QueryOperation, Upload,
+    Connection, ConnectionNameType, Cursor, CursorType, DataSource, DefaultConnectionName,
+    EmptyEdgeFields, EmptyMutation, EmptySubscription, GlobalId, IterDataSource, KeysetDirection,
+    KeysetQuery, PageInfo, QueryOperation, StreamDataSource, TupleCursorError, Upload, UploadValue,
 };
 pub use validation::ValidationMode;
 
@@ -180,6 +187,15 @@ pub use types::{EnumItem, EnumType};
 /// | default     | Argument default value    | string   | Y        |
 /// | validator   | Input value validator     | [`InputValueValidator`](validators/trait.InputValueValidator.html) | Y        |
 ///
+/// # Relay nodes
+///
+/// A method of the query root marked with `#[node]` is a loader of Relay nodes. It takes the id of
+/// the object and returns the object, `Option<T>` or `FieldResult` of them. The returned type
+/// implements the `Node` interface, and the `node(id: ID!)` and `nodes(ids: [ID!]!)` fields are
+/// added to the query root. The object should use [`GlobalId`](struct.GlobalId.html) as the type
+/// of its `id` field. Only the query root loads nodes, building a schema fails if another object
+/// has a `#[node]` method.
+///
 /// # The field returns the value type
 ///
 /// - A scalar value, such as `i32`, `bool`
@@ -0,0 +1,174 @@
+use crate::Variables;
+use graphql_parser::query::{
+    Directive, Field, FragmentDefinition, Selection, SelectionSet, Value, VariableDefinition,
+};
+use std::collections::HashMap;
+
+/// A selection performed by a query
+///
+/// Fragment spreads and inline fragments are expanded, so fields selected through them are
+/// visible here. Type conditions are ignored, which means the look-ahead is a superset of the
+/// fields that will actually be resolved.
+pub struct Lookahead<'a> {
+    fragments: &'a HashMap<String, FragmentDefinition>,
+    variables: &'a Variables,
+    variable_definitions: &'a [VariableDefinition],
+    fields: Vec<&'a Field>,
+}
+
+impl<'a> Lookahead<'a> {
+    pub(crate) fn new(
+        fragments: &'a HashMap<String, FragmentDefinition>,
+        variables: &'a Variables,
+        variable_definitions: &'a [VariableDefinition],
+        field: &'a Field,
+    ) -> Self {
+        Self {
+            fragments,
+            variables,
+            variable_definitions,
+            fields: vec![field],
+        }
+    }
+
+    /// Get the subfields of the selection set with the specified name. This will ignore aliases.
+    ///
+    /// If the field is selected several times (for example with different aliases), all of them
+    /// are merged into the returned `Lookahead`.
+    pub fn field(&self, name: &str) -> Self {
+        let mut fields = Vec::new();
+        for field in &self.fields {
+            self.collect_fields(&field.selection_set, &mut |field| {
+                if field.name == name {
+                    fields.push(field);
+                }
+            });
+        }
+        Self {
+            fragments: self.fragments,
+            variables: self.variables,
+            variable_definitions: self.variable_definitions,
+            fields,
+        }
+    }
+
+    /// Returns true if field exists otherwise return false.
+    #[inline]
+    pub fn exists(&self) -> bool {
+        !self.fields.is_empty()
+    }
+
+    /// Returns true if the subfield with the specified name is selected.
+    #[inline]
+    pub fn is_selected(&self, name: &str) -> bool {
+        self.field(name).exists()
+    }
+
+    /// Returns the names of all selected subfields, without duplicates and in query order.
+    pub fn field_names(&self) -> Vec<&'a str> {
+        let mut names: Vec<&'a str> = Vec::new();
+        for field in &self.fields {
+            self.collect_fields(&field.selection_set, &mut |field| {
+                if !names.contains(&field.name.as_str()) {
+                    names.push(field.name.as_str());
+                }
+            });
+        }
+        names
+    }
+
+    /// Returns the arguments of the field, with the variables replaced by their values.
+    ///
+    /// If the field was selected several times, the arguments of the first selection are returned.
+    pub fn arguments(&self) -> Vec<(&'a str, Value)> {
+        self.fields
+            .first()
+            .map(|field| {
+                field
+                    .arguments
+                    .iter()
+                    .map(|(name, value)| (name.as_str(), self.resolve_value(value)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the argument of the field with the specified name, with the variables replaced by their values.
+    pub fn argument(&self, name: &str) -> Option<Value> {
+        self.fields.first().and_then(|field| {
+            field
+                .arguments
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, value)| self.resolve_value(value))
+        })
+    }
+
+    fn collect_fields<F: FnMut(&'a Field)>(&self, selection_set: &'a SelectionSet, f: &mut F) {
+        for selection in &selection_set.items {
+            match selection {
+                Selection::Field(field) => {
+                    if !self.is_skip(&field.directives) {
+                        f(field);
+                    }
+                }
+                Selection::FragmentSpread(fragment_spread) => {
+                    if self.is_skip(&fragment_spread.directives) {
+                        continue;
+                    }
+                    if let Some(fragment) =
+                        self.fragments.get(fragment_spread.fragment_name.as_str())
+                    {
+                        self.collect_fields(&fragment.selection_set, f);
+                    }
+                }
+                Selection::InlineFragment(inline_fragment) => {
+                    if self.is_skip(&inline_fragment.directives) {
+                        continue;
+                    }
+                    self.collect_fields(&inline_fragment.selection_set, f);
+                }
+            }
+        }
+    }
+
+    fn is_skip(&self, directives: &[Directive]) -> bool {
+        directives.iter().any(|directive| {
+            let value = directive
+                .arguments
+                .iter()
+                .find(|(name, _)| name == "if")
+                .map(|(_, value)| self.resolve_value(value));
+            matches!(
+                (directive.name.as_str(), value),
+                ("skip", Some(Value::Boolean(true))) | ("include", Some(Value::Boolean(false)))
+            )
+        })
+    }
+
+    fn var_value(&self, name: &str) -> Value {
+        self.variables
+            .get(name)
+            .or_else(|| {
+                self.variable_definitions
+                    .iter()
+                    .find(|def| def.name == name)
+                    .and_then(|def| def.default_value.as_ref())
+            })
+            .cloned()
+            .unwrap_or(Value::Null)
+    }
+
+    fn resolve_value(&self, value: &Value) -> Value {
+        match value {
+            Value::Variable(name) => self.var_value(name),
+            Value::List(ls) => Value::List(ls.iter().map(|v| self.resolve_value(v)).collect()),
+            Value::Object(obj) => Value::Object(
+                obj.iter()
+                    .map(|(name, v)| (name.clone(), self.resolve_value(v)))
+                    .collect(),
+            ),
+            _ => value.clone(),
+        }
+    }
+}
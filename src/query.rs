@@ -1,34 +1,60 @@
 use crate::context::{Data, ResolveId};
+use crate::data_factory::LazyData;
 use crate::error::ParseRequestError;
+use crate::extensions::{extension_results, BoxExtension};
+use crate::http::{CsrfGuard, UploadContent, UploadStorage};
 use crate::mutation_resolver::do_mutation_resolve;
 use crate::registry::CacheControl;
+use crate::schema::parse_document;
 use crate::validation::{check_rules, CheckResult};
 use crate::{do_resolve, ContextBase, Error, Result, Schema};
-use crate::{ObjectType, QueryError, Variables};
+use crate::{ObjectType, QueryError, UploadValue, Variables};
 use graphql_parser::query::{
     Definition, Document, OperationDefinition, SelectionSet, VariableDefinition,
 };
-use graphql_parser::{parse_query, Pos};
-use itertools::Itertools;
+use graphql_parser::Pos;
+use http::HeaderMap;
+use parking_lot::Mutex;
 use std::any::Any;
 use std::collections::HashMap;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use std::sync::atomic::AtomicUsize;
-use tempdir::TempDir;
+use std::sync::Arc;
 
 /// IntoQueryBuilder options
 #[derive(Default, Clone)]
 pub struct IntoQueryBuilderOpts {
-    /// A temporary path to store the contents of all files.
+    /// A temporary path to store the contents of all files, it's used by the default storage.
     ///
     /// If None, the system temporary path is used.
     pub temp_dir: Option<PathBuf>,
 
+    /// The storage of the uploaded files.
+    ///
+    /// If None, the files are stored with `TempDirUploadStorage` in `temp_dir`.
+    pub upload_storage: Option<Arc<dyn UploadStorage>>,
+
     /// Maximum file size.
     pub max_file_size: Option<usize>,
 
+    /// Maximum total size of the files of a request.
+    pub max_total_file_size: Option<usize>,
+
+    /// The allowed content types of the files, such as `image/png` or `image/*`.
+    ///
+    /// A file without a content type is `application/octet-stream`. If None, all content types are allowed.
+    pub allowed_content_types: Option<Vec<String>>,
+
     /// Maximum number of files.
     pub max_num_files: Option<usize>,
+
+    /// Maximum size of the request body in bytes.
+    ///
+    /// It's checked while the body is read, a larger body fails with `ParseRequestError::PayloadTooLarge`.
+    pub max_body_size: Option<usize>,
+
+    /// The guard against cross-site request forgery checked by the integrations, it's enabled by default.
+    pub csrf_guard: CsrfGuard,
 }
 
 #[allow(missing_docs)]
@@ -55,6 +81,10 @@ pub struct QueryResponse {
 
     /// Cache control value
     pub cache_control: CacheControl,
+
+    /// Headers added by the resolvers with `Context::insert_http_header` and `Context::set_cookie`,
+    /// the integrations apply them to the HTTP response.
+    pub http_headers: HeaderMap,
 }
 
 /// Query builder
@@ -63,7 +93,8 @@ pub struct QueryBuilder {
     pub(crate) operation_name: Option<String>,
     pub(crate) variables: Variables,
     pub(crate) ctx_data: Option<Data>,
-    pub(crate) files_holder: Option<TempDir>,
+    pub(crate) uploads: Vec<(String, UploadValue)>,
+    pub(crate) mutation_disabled: bool,
 }
 
 impl QueryBuilder {
@@ -74,7 +105,8 @@ impl QueryBuilder {
             operation_name: None,
             variables: Default::default(),
             ctx_data: None,
-            files_holder: None,
+            uploads: Vec::new(),
+            mutation_disabled: false,
         }
     }
 
@@ -91,6 +123,16 @@ impl QueryBuilder {
         QueryBuilder { variables, ..self }
     }
 
+    /// Only allow queries, executing a mutation returns `QueryError::MutationNotAllowed`.
+    ///
+    /// The builders of HTTP GET requests disable mutations, as the GraphQL over HTTP specification requires.
+    pub fn disable_mutation(self) -> Self {
+        QueryBuilder {
+            mutation_disabled: true,
+            ..self
+        }
+    }
+
     /// Add a context data that can be accessed in the `Context`, you access it with `Context::data`.
     ///
     /// **This data is only valid for this query**
@@ -105,47 +147,69 @@ impl QueryBuilder {
         self
     }
 
-    /// Set file holder
-    pub fn set_files_holder(&mut self, files_holder: TempDir) {
-        self.files_holder = Some(files_holder);
-    }
-
-    /// Set uploaded file path
+    /// Set an uploaded file to the variable at `var_path`, such as `variables.file`.
+    ///
+    /// The content is kept until the query is finished.
     pub fn set_upload(
         &mut self,
         var_path: &str,
         filename: &str,
         content_type: Option<&str>,
-        path: &Path,
+        content: Arc<dyn UploadContent>,
     ) {
-        self.variables
-            .set_upload(var_path, filename, content_type, path);
+        self.variables.set_upload(var_path);
+        self.uploads.push((
+            var_path.to_string(),
+            UploadValue {
+                filename: filename.to_string(),
+                content_type: content_type.map(ToString::to_string),
+                content,
+            },
+        ));
     }
 
     /// Execute the query.
     pub async fn execute<Query, Mutation, Subscription>(
+        mut self,
+        schema: &Schema<Query, Mutation, Subscription>,
+    ) -> Result<QueryResponse>
+    where
+        Query: ObjectType + Send + Sync,
+        Mutation: ObjectType + Send + Sync,
+    {
+        let ctx_data = self.ctx_data.take();
+        self.execute_with_data(schema, ctx_data.as_ref()).await
+    }
+
+    /// Execute the query with a context data shared with other queries, such as the data of a WebSocket connection.
+    /// The data added with `QueryBuilder::data` is ignored.
+    pub(crate) async fn execute_with_data<Query, Mutation, Subscription>(
         self,
         schema: &Schema<Query, Mutation, Subscription>,
+        ctx_data: Option<&Data>,
     ) -> Result<QueryResponse>
     where
         Query: ObjectType + Send + Sync,
         Mutation: ObjectType + Send + Sync,
     {
-        // create extension instances
-        let extensions = schema
-            .0
-            .extensions
-            .iter()
-            .map(|factory| factory())
-            .collect_vec();
-
-        // parse query source
-        extensions
-            .iter()
-            .for_each(|e| e.parse_start(&self.query_source));
-        let document = parse_query(&self.query_source).map_err(Into::<Error>::into)?;
-        extensions.iter().for_each(|e| e.parse_end());
+        let extensions = schema.create_extensions();
+        let document = parse_document(&extensions, &self.query_source)?;
+        self.execute_document(schema, ctx_data, extensions, document)
+            .await
+    }
 
+    /// Execute the query of a document parsed with `parse_document`.
+    pub(crate) async fn execute_document<Query, Mutation, Subscription>(
+        self,
+        schema: &Schema<Query, Mutation, Subscription>,
+        ctx_data: Option<&Data>,
+        extensions: Vec<BoxExtension>,
+        document: Document,
+    ) -> Result<QueryResponse>
+    where
+        Query: ObjectType + Send + Sync,
+        Mutation: ObjectType + Send + Sync,
+    {
         // check rules
         extensions.iter().for_each(|e| e.validation_start());
         let CheckResult {
@@ -170,6 +234,8 @@ impl QueryBuilder {
 
         // execute
         let inc_resolve_id = AtomicUsize::default();
+        let http_headers = Mutex::new(HeaderMap::new());
+        let lazy_data = LazyData::new(&schema.0.data_factories);
         let mut fragments = HashMap::new();
         let (selection_set, variable_definitions, is_query) =
             current_operation(&document, self.operation_name.as_deref()).ok_or_else(|| {
@@ -179,6 +245,9 @@ impl QueryBuilder {
                     err: QueryError::MissingOperation,
                 }
             })?;
+        if !is_query && self.mutation_disabled {
+            return Err(QueryError::MutationNotAllowed.into_error(Pos::default()));
+        }
 
         for definition in &document.definitions {
             if let Definition::Fragment(fragment) = &definition {
@@ -196,8 +265,13 @@ impl QueryBuilder {
             variable_definitions,
             registry: &schema.0.registry,
             data: &schema.0.data,
-            ctx_data: self.ctx_data.as_ref(),
+            ctx_data,
+            http_headers: &http_headers,
+            data_factories: &schema.0.data_factories,
+            lazy_data: &lazy_data,
+            creating_data: None,
             fragments: &fragments,
+            uploads: &self.uploads,
         };
 
         extensions.iter().for_each(|e| e.execution_start());
@@ -210,23 +284,9 @@ impl QueryBuilder {
 
         let res = QueryResponse {
             data,
-            extensions: if !extensions.is_empty() {
-                Some(
-                    extensions
-                        .iter()
-                        .filter_map(|e| {
-                            if let Some(name) = e.name() {
-                                e.result().map(|res| (name.to_string(), res))
-                            } else {
-                                None
-                            }
-                        })
-                        .collect::<serde_json::Map<_, _>>(),
-                )
-            } else {
-                None
-            },
+            extensions: extension_results(&extensions),
             cache_control,
+            http_headers: http_headers.into_inner(),
         };
         Ok(res)
     }
@@ -336,9 +336,11 @@ pub struct Registry {
     pub types: HashMap<String, Type>,
     pub directives: HashMap<String, Directive>,
     pub implements: HashMap<String, HashSet<String>>,
+    pub node_types: HashSet<String>,
     pub query_type: String,
     pub mutation_type: Option<String>,
     pub subscription_type: Option<String>,
+    pub errors: Vec<String>,
 }
 
 impl Registry {
@@ -348,23 +350,80 @@ impl Registry {
     ) -> String {
         let name = T::type_name();
         if !self.types.contains_key(name.as_ref()) {
-            self.types.insert(
-                name.to_string(),
-                Type::Object {
-                    name: "".to_string(),
-                    description: None,
-                    fields: Default::default(),
-                    cache_control: Default::default(),
-                    extends: false,
-                    keys: None,
-                },
-            );
+            self.types
+                .insert(name.to_string(), Self::placeholder_type());
             let ty = f(self);
             self.types.insert(name.to_string(), ty);
         }
         T::qualified_type_name()
     }
 
+    fn placeholder_type() -> Type {
+        Type::Object {
+            name: "".to_string(),
+            description: None,
+            fields: Default::default(),
+            cache_control: Default::default(),
+            extends: false,
+            keys: None,
+        }
+    }
+
+    /// Build the type of `T` without registering it.
+    ///
+    /// `T` is built in a separate registry, so it is built completely even if it is being registered, and the
+    /// types that it uses are copied into this registry. `T` is only registered if one of these types refers to it.
+    pub fn build_type<T: crate::Type>(&mut self) -> Type {
+        let name = T::type_name();
+        let mut registry = Registry {
+            types: self
+                .types
+                .keys()
+                .filter(|type_name| type_name.as_str() != name.as_ref())
+                .map(|type_name| (type_name.clone(), Self::placeholder_type()))
+                .collect(),
+            directives: Default::default(),
+            implements: Default::default(),
+            node_types: Default::default(),
+            query_type: self.query_type.clone(),
+            mutation_type: self.mutation_type.clone(),
+            subscription_type: self.subscription_type.clone(),
+            errors: Default::default(),
+        };
+        T::create_type_info(&mut registry);
+        let ty = registry
+            .types
+            .remove(name.as_ref())
+            .unwrap_or_else(Self::placeholder_type);
+        let referenced = std::iter::once(&ty)
+            .chain(registry.types.values())
+            .any(|ty| {
+                ty.fields().map_or(false, |fields| {
+                    fields
+                        .values()
+                        .any(|field| TypeName::concrete_typename(&field.ty) == name.as_ref())
+                }) || ty.possible_types().map_or(false, |possible_types| {
+                    possible_types.contains(name.as_ref())
+                })
+            });
+
+        for (type_name, ty) in registry.types {
+            self.types.entry(type_name).or_insert(ty);
+        }
+        for (type_name, interfaces) in registry.implements {
+            for interface in interfaces {
+                self.add_implements(&type_name, &interface);
+            }
+        }
+        self.node_types.extend(registry.node_types);
+        self.errors.extend(registry.errors);
+        if referenced {
+            // a field returns `T`, so it is a type of the schema
+            T::create_type_info(self);
+        }
+        ty
+    }
+
     pub fn add_directive(&mut self, directive: Directive) {
         self.directives
             .insert(directive.name.to_string(), directive);
@@ -383,6 +442,17 @@ impl Registry {
             });
     }
 
+    pub fn add_node(&mut self, loader_type: &str, ty: &str) {
+        if loader_type != self.query_type {
+            // only the query root loads nodes
+            self.errors.push(format!(
+                "The node loader of \"{}\" is defined on \"{}\", it must be defined on the query root \"{}\".",
+                ty, loader_type, self.query_type
+            ));
+        }
+        self.node_types.insert(ty.to_string());
+    }
+
     pub fn add_keys(&mut self, ty: &str, keys: &str) {
         let all_keys = match self.types.get_mut(ty) {
             Some(Type::Object { keys: all_keys, .. }) => all_keys,
@@ -621,4 +691,103 @@ impl Registry {
             );
         }
     }
+
+    pub fn create_node_types(&mut self) {
+        if self.node_types.is_empty() {
+            return;
+        }
+
+        let id_type = crate::ID::create_type_info(self);
+        for ty in self.node_types.clone() {
+            self.add_implements(&ty, "Node");
+        }
+
+        self.types.insert(
+            "Node".to_string(),
+            Type::Interface {
+                name: "Node".to_string(),
+                description: Some("An object with a globally unique ID."),
+                fields: {
+                    let mut fields = HashMap::new();
+                    fields.insert(
+                        "id".to_string(),
+                        Field {
+                            name: "id".to_string(),
+                            description: Some("The globally unique ID of the object."),
+                            args: Default::default(),
+                            ty: id_type.clone(),
+                            deprecation: None,
+                            cache_control: Default::default(),
+                            external: false,
+                            requires: None,
+                            provides: None,
+                        },
+                    );
+                    fields
+                },
+                possible_types: self.node_types.clone(),
+                extends: false,
+                keys: None,
+            },
+        );
+
+        let query_root = self.types.get_mut(&self.query_type).unwrap();
+        if let Type::Object { fields, .. } = query_root {
+            fields.insert(
+                "node".to_string(),
+                Field {
+                    name: "node".to_string(),
+                    description: Some("Fetches an object given its ID."),
+                    args: {
+                        let mut args = HashMap::new();
+                        args.insert(
+                            "id",
+                            InputValue {
+                                name: "id",
+                                description: None,
+                                ty: id_type.clone(),
+                                default_value: None,
+                                validator: None,
+                            },
+                        );
+                        args
+                    },
+                    ty: "Node".to_string(),
+                    deprecation: None,
+                    cache_control: Default::default(),
+                    external: false,
+                    requires: None,
+                    provides: None,
+                },
+            );
+
+            fields.insert(
+                "nodes".to_string(),
+                Field {
+                    name: "nodes".to_string(),
+                    description: Some("Lookup nodes by a list of IDs."),
+                    args: {
+                        let mut args = HashMap::new();
+                        args.insert(
+                            "ids",
+                            InputValue {
+                                name: "ids",
+                                description: None,
+                                ty: format!("[{}]!", id_type),
+                                default_value: None,
+                                validator: None,
+                            },
+                        );
+                        args
+                    },
+                    ty: "[Node]!".to_string(),
+                    deprecation: None,
+                    cache_control: Default::default(),
+                    external: false,
+                    requires: None,
+                    provides: None,
+                },
+            );
+        }
+    }
 }
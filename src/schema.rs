@@ -1,4 +1,5 @@
 use crate::context::Data;
+use crate::data_factory::{DataFactories, LazyData};
 use crate::extensions::{BoxExtension, Extension};
 use crate::model::__DirectiveLocation;
 use crate::query::QueryBuilder;
@@ -7,14 +8,14 @@ use crate::subscription::{create_connection, create_subscription_stream, Subscri
 use crate::types::QueryRoot;
 use crate::validation::{check_rules, ValidationMode};
 use crate::{
-    Environment, Error, ObjectType, Pos, QueryError, QueryResponse, Result, SubscriptionStream,
-    SubscriptionType, Type, Variables,
+    DataFactory, Environment, ObjectType, Pos, QueryError, QueryResponse, Result, SchemaError,
+    SubscriptionStream, SubscriptionType, Type, Variables,
 };
 use bytes::Bytes;
 use futures::channel::mpsc;
 use futures::Stream;
 use graphql_parser::parse_query;
-use graphql_parser::query::{Definition, OperationDefinition};
+use graphql_parser::query::{Definition, Document, OperationDefinition};
 use std::any::Any;
 use std::collections::HashMap;
 use std::sync::atomic::AtomicUsize;
@@ -30,6 +31,7 @@ pub(crate) struct SchemaInner<Query, Mutation, Subscription> {
     pub(crate) complexity: Option<usize>,
     pub(crate) depth: Option<usize>,
     pub(crate) extensions: Vec<Box<dyn Fn() -> BoxExtension + Send + Sync>>,
+    pub(crate) data_factories: DataFactories,
 }
 
 /// Schema builder
@@ -79,6 +81,12 @@ impl<Query: ObjectType, Mutation: ObjectType, Subscription: SubscriptionType>
         self
     }
 
+    /// Add a factory that creates data lazily for every request, you access it with `Context::data_async`.
+    pub fn data_factory<F: DataFactory>(mut self, factory: F) -> Self {
+        self.0.data_factories.insert(factory);
+        self
+    }
+
     /// Set the validation mode, default is `ValidationMode::Strict`.
     pub fn validation_mode(mut self, validation_mode: ValidationMode) -> Self {
         self.0.validation_mode = validation_mode;
@@ -86,8 +94,22 @@ impl<Query: ObjectType, Mutation: ObjectType, Subscription: SubscriptionType>
     }
 
     /// Build schema.
+    ///
+    /// # Panics
+    ///
+    /// It panics if the types of the schema are invalid, use `try_finish` to get the error instead.
     pub fn finish(self) -> Schema<Query, Mutation, Subscription> {
-        Schema(Arc::new(self.0))
+        self.try_finish().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Build schema, returns an error if the types of the schema are invalid.
+    pub fn try_finish(
+        self,
+    ) -> std::result::Result<Schema<Query, Mutation, Subscription>, SchemaError> {
+        if let Some(err) = self.0.registry.errors.first() {
+            return Err(SchemaError(err.clone()));
+        }
+        Ok(Schema(Arc::new(self.0)))
     }
 }
 
@@ -102,6 +124,13 @@ impl<Query, Mutation, Subscription> Clone for Schema<Query, Mutation, Subscripti
     }
 }
 
+impl<Query, Mutation, Subscription> Schema<Query, Mutation, Subscription> {
+    /// Create the extension instances of an operation.
+    pub(crate) fn create_extensions(&self) -> Vec<BoxExtension> {
+        self.0.extensions.iter().map(|factory| factory()).collect()
+    }
+}
+
 impl<Query, Mutation, Subscription> Schema<Query, Mutation, Subscription>
 where
     Query: ObjectType + Send + Sync + 'static,
@@ -122,6 +151,7 @@ where
             types: Default::default(),
             directives: Default::default(),
             implements: Default::default(),
+            node_types: Default::default(),
             query_type: Query::type_name().to_string(),
             mutation_type: if Mutation::is_empty() {
                 None
@@ -133,6 +163,7 @@ where
             } else {
                 Some(Subscription::type_name().to_string())
             },
+            errors: Default::default(),
         };
 
         registry.add_directive(Directive {
@@ -191,6 +222,9 @@ where
             Subscription::create_type_info(&mut registry);
         }
 
+        // relay
+        registry.create_node_types();
+
         // federation
         registry.create_federation_types();
 
@@ -207,6 +241,7 @@ where
             complexity: None,
             depth: None,
             extensions: Default::default(),
+            data_factories: Default::default(),
         })
     }
 
@@ -241,9 +276,47 @@ where
         operation_name: Option<&str>,
         variables: Variables,
         ctx_data: Option<Arc<Data>>,
-    ) -> Result<impl Stream<Item = Result<serde_json::Value>> + Send> {
-        let document = parse_query(source).map_err(Into::<Error>::into)?;
-        check_rules(&self.0.registry, &document, self.0.validation_mode)?;
+    ) -> Result<impl Stream<Item = Result<QueryResponse>> + Send> {
+        let extensions = self.create_extensions();
+        let document = match parse_document(&extensions, source) {
+            Ok(document) => document,
+            Err(err) => {
+                // the subscription ends before it starts
+                extensions.iter().for_each(|e| e.subscription_end());
+                return Err(err);
+            }
+        };
+        self.create_subscription_stream_with_document(
+            extensions,
+            document,
+            operation_name,
+            variables,
+            ctx_data,
+        )
+        .await
+    }
+
+    /// Create subscription stream of a document parsed with `parse_document`.
+    pub(crate) async fn create_subscription_stream_with_document(
+        &self,
+        extensions: Vec<BoxExtension>,
+        document: Document,
+        operation_name: Option<&str>,
+        variables: Variables,
+        ctx_data: Option<Arc<Data>>,
+    ) -> Result<impl Stream<Item = Result<QueryResponse>> + Send> {
+        // the subscription ends before it starts if it fails to validate
+        let end_subscription = |extensions: &[BoxExtension]| {
+            extensions.iter().for_each(|e| e.subscription_end());
+        };
+
+        extensions.iter().for_each(|e| e.validation_start());
+        let res = check_rules(&self.0.registry, &document, self.0.validation_mode);
+        extensions.iter().for_each(|e| e.validation_end());
+        if let Err(err) = res {
+            end_subscription(&extensions);
+            return Err(err);
+        }
 
         let mut fragments = HashMap::new();
         let mut subscription = None;
@@ -264,14 +337,20 @@ where
             }
         }
 
-        let subscription = subscription.ok_or(if let Some(name) = operation_name {
-            QueryError::UnknownOperationNamed {
-                name: name.to_string(),
+        let subscription = match subscription {
+            Some(subscription) => subscription,
+            None => {
+                end_subscription(&extensions);
+                return Err(if let Some(name) = operation_name {
+                    QueryError::UnknownOperationNamed {
+                        name: name.to_string(),
+                    }
+                    .into_error(Pos::default())
+                } else {
+                    QueryError::MissingOperation.into_error(Pos::default())
+                });
             }
-            .into_error(Pos::default())
-        } else {
-            QueryError::MissingOperation.into_error(Pos::default())
-        })?;
+        };
 
         let resolve_id = AtomicUsize::default();
         let environment = Arc::new(Environment {
@@ -279,8 +358,17 @@ where
             variable_definitions: subscription.variable_definitions,
             fragments,
             ctx_data: ctx_data.unwrap_or_default(),
+            http_headers: Default::default(),
+            lazy_data: LazyData::new(&self.0.data_factories),
+            extensions,
         });
-        let ctx = environment.create_context(self, None, &subscription.selection_set, &resolve_id);
+        let ctx = environment.create_context(
+            self,
+            None,
+            &subscription.selection_set,
+            &resolve_id,
+            &environment.extensions,
+        );
         let mut streams = Vec::new();
         create_subscription_stream(self, environment.clone(), &ctx, &mut streams).await?;
         Ok(futures::stream::select_all(streams))
@@ -297,3 +385,11 @@ where
         create_connection(self.clone(), transport)
     }
 }
+
+/// Parse the source of an operation, the extensions see the start and the end of the parse.
+pub(crate) fn parse_document(extensions: &[BoxExtension], source: &str) -> Result<Document> {
+    extensions.iter().for_each(|e| e.parse_start(source));
+    let res = parse_query(source).map_err(Into::into);
+    extensions.iter().for_each(|e| e.parse_end());
+    res
+}
@@ -0,0 +1,248 @@
+use crate::FieldResult;
+use futures::stream::BoxStream;
+use futures::task::{Context, Poll, Waker};
+use futures::{Stream, StreamExt};
+use parking_lot::Mutex;
+use slab::Slab;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// A publish/subscribe message broker
+///
+/// Subscription resolvers subscribe to a topic and return the stream, mutations publish messages
+/// to the topic. `MemoryBroker` is an in-process implementation, implement this trait to adapt an
+/// external broker such as Redis or NATS. The broker is usually put into the schema data, as
+/// `MemoryBroker<T>` or `Arc<dyn Broker<T>>`.
+///
+/// An error in the stream tells the subscriber that it has missed messages, it is the last item of the stream. The
+/// stream of a subscription field also ends at its first error, so the transports send it as the last message of the
+/// subscription, and the client is expected to subscribe again.
+///
+/// # Examples
+///
+/// ```rust
+/// use async_graphql::*;
+/// use futures::Stream;
+///
+/// struct QueryRoot;
+///
+/// #[Object]
+/// impl QueryRoot {}
+///
+/// struct MutationRoot;
+///
+/// #[Object]
+/// impl MutationRoot {
+///     async fn ship_order(&self, ctx: &Context<'_>, id: ID) -> FieldResult<bool> {
+///         let broker = ctx.data::<MemoryBroker<String>>();
+///         broker.publish(&format!("order:{}", id.as_str()), "shipped".to_string()).await?;
+///         Ok(true)
+///     }
+/// }
+///
+/// struct SubscriptionRoot;
+///
+/// #[Subscription]
+/// impl SubscriptionRoot {
+///     async fn order_status(&self, ctx: &Context<'_>, id: ID) -> FieldResult<impl Stream<Item = FieldResult<String>>> {
+///         let broker = ctx.data::<MemoryBroker<String>>();
+///         broker.subscribe(&format!("order:{}", id.as_str())).await
+///     }
+/// }
+///
+/// let schema = Schema::build(QueryRoot, MutationRoot, SubscriptionRoot)
+///     .data(MemoryBroker::<String>::new(16))
+///     .finish();
+/// ```
+#[async_trait::async_trait]
+pub trait Broker<T: Send + 'static>: Send + Sync + 'static {
+    /// Publish a message to a topic.
+    async fn publish(&self, topic: &str, msg: T) -> FieldResult<()>;
+
+    /// Subscribe to the topics that match the pattern and returns a `Stream`.
+    async fn subscribe(&self, pattern: &str) -> FieldResult<BoxStream<'static, FieldResult<T>>>;
+}
+
+/// What `MemoryBroker` does when the buffer of a subscriber is full
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum OverflowPolicy {
+    /// Drop the oldest message silently
+    DropOldest,
+
+    /// Drop the new messages, and end the stream of the subscriber with an error with the number of missed messages
+    /// after the buffered messages
+    Terminate,
+}
+
+struct Subscriber<T> {
+    pattern: String,
+    queue: VecDeque<T>,
+    lagged: usize,
+    terminated: bool,
+    waker: Option<Waker>,
+}
+
+/// An in-process broker
+///
+/// A topic pattern can contain `*`, which matches any sequence of characters, so `order:*`
+/// matches `order:1` and `order:2`. Every subscriber has a buffer of `capacity` messages, when
+/// it's full the `OverflowPolicy` applies.
+///
+/// Cloning the broker is cheap, the clones share the subscribers.
+pub struct MemoryBroker<T> {
+    subscribers: Arc<Mutex<Slab<Subscriber<T>>>>,
+    capacity: usize,
+    policy: OverflowPolicy,
+}
+
+impl<T> Clone for MemoryBroker<T> {
+    fn clone(&self) -> Self {
+        Self {
+            subscribers: self.subscribers.clone(),
+            capacity: self.capacity,
+            policy: self.policy,
+        }
+    }
+}
+
+impl<T> MemoryBroker<T> {
+    /// Create a broker with the buffer size of every subscriber, the policy is `OverflowPolicy::DropOldest`.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            subscribers: Default::default(),
+            capacity: capacity.max(1),
+            policy: OverflowPolicy::DropOldest,
+        }
+    }
+
+    /// Set the policy when the buffer of a subscriber is full.
+    pub fn overflow_policy(self, policy: OverflowPolicy) -> Self {
+        Self { policy, ..self }
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: Clone + Send + 'static> Broker<T> for MemoryBroker<T> {
+    async fn publish(&self, topic: &str, msg: T) -> FieldResult<()> {
+        let mut subscribers = self.subscribers.lock();
+        for (_, subscriber) in subscribers.iter_mut() {
+            if subscriber.terminated || !topic_matches(&subscriber.pattern, topic) {
+                continue;
+            }
+            if subscriber.lagged > 0 {
+                // the stream ends after the messages in the queue
+                subscriber.lagged += 1;
+                continue;
+            }
+            if subscriber.queue.len() >= self.capacity {
+                if self.policy == OverflowPolicy::Terminate {
+                    subscriber.lagged = 1;
+                    continue;
+                }
+                subscriber.queue.pop_front();
+            }
+            subscriber.queue.push_back(msg.clone());
+            if let Some(waker) = subscriber.waker.take() {
+                waker.wake();
+            }
+        }
+        Ok(())
+    }
+
+    async fn subscribe(&self, pattern: &str) -> FieldResult<BoxStream<'static, FieldResult<T>>> {
+        let id = self.subscribers.lock().insert(Subscriber {
+            pattern: pattern.to_string(),
+            queue: VecDeque::with_capacity(self.capacity),
+            lagged: 0,
+            terminated: false,
+            waker: None,
+        });
+        Ok(MemoryBrokerStream {
+            id,
+            subscribers: self.subscribers.clone(),
+        }
+        .boxed())
+    }
+}
+
+struct MemoryBrokerStream<T> {
+    id: usize,
+    subscribers: Arc<Mutex<Slab<Subscriber<T>>>>,
+}
+
+impl<T> Drop for MemoryBrokerStream<T> {
+    fn drop(&mut self) {
+        self.subscribers.lock().remove(self.id);
+    }
+}
+
+impl<T> Stream for MemoryBrokerStream<T> {
+    type Item = FieldResult<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut subscribers = self.subscribers.lock();
+        let subscriber = &mut subscribers[self.id];
+        if subscriber.terminated {
+            return Poll::Ready(None);
+        }
+        match subscriber.queue.pop_front() {
+            Some(msg) => Poll::Ready(Some(Ok(msg))),
+            None if subscriber.lagged > 0 => {
+                subscriber.terminated = true;
+                Poll::Ready(Some(Err(format!(
+                    "The subscriber lagged behind and missed {} messages",
+                    subscriber.lagged
+                )
+                .into())))
+            }
+            None => {
+                subscriber.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Match a topic against a pattern where `*` matches any sequence of characters.
+fn topic_matches(pattern: &str, topic: &str) -> bool {
+    let (pattern, topic) = (pattern.as_bytes(), topic.as_bytes());
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+
+    while t < topic.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == topic[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topic_matches() {
+        assert!(topic_matches("order:1", "order:1"));
+        assert!(!topic_matches("order:1", "order:10"));
+        assert!(topic_matches("order:*", "order:10"));
+        assert!(topic_matches("order:*", "order:"));
+        assert!(!topic_matches("order:*", "user:1"));
+        assert!(topic_matches("*:created", "order:created"));
+        assert!(topic_matches("*", "anything"));
+        assert!(topic_matches("a*b*c", "aXXbYYbc"));
+        assert!(!topic_matches("a*b*c", "aXXbYY"));
+    }
+}
@@ -1,28 +1,153 @@
-use crate::{ObjectType, Result, Schema, SubscriptionType};
+use crate::{FieldError, FieldResult, ObjectType, QueryResponse, Result, Schema, SubscriptionType};
 use bytes::Bytes;
 use futures::channel::mpsc;
-use futures::task::{AtomicWaker, Context, Poll};
-use futures::Stream;
+use futures::future::BoxFuture;
+use futures::stream::BoxStream;
+use futures::task::{Context, Poll};
+use futures::{Future, FutureExt, Stream, StreamExt};
 use slab::Slab;
-use std::future::Future;
 use std::pin::Pin;
 
+/// A stream of `SubscriptionStreams`
+enum StreamEntry {
+    /// Waiting for the future that creates the stream, see `SubscriptionStreams::add_future`.
+    Starting(BoxFuture<'static, FieldResult<BoxStream<'static, Result<QueryResponse>>>>),
+
+    /// The stream is running.
+    Running(BoxStream<'static, Result<QueryResponse>>),
+}
+
 /// Use to hold all subscription stream for the `SubscriptionConnection`
 pub struct SubscriptionStreams {
-    streams: Slab<Pin<Box<dyn Stream<Item = Result<serde_json::Value>> + Send>>>,
+    streams: Slab<StreamEntry>,
+    next_id: usize,
 }
 
 #[allow(missing_docs)]
 impl SubscriptionStreams {
-    pub fn add<S: Stream<Item = Result<serde_json::Value>> + Send + 'static>(
+    pub fn add<S: Stream<Item = Result<QueryResponse>> + Send + 'static>(
         &mut self,
         stream: S,
     ) -> usize {
-        self.streams.insert(Box::pin(stream))
+        self.streams.insert(StreamEntry::Running(stream.boxed()))
+    }
+
+    /// Add a stream that is created by a future, such as one that runs an asynchronous hook first.
+    ///
+    /// The future is polled with the other streams, so it doesn't block the connection. If it fails,
+    /// `SubscriptionTransport::handle_start_error` is called and the stream is removed.
+    pub fn add_future<F, S>(&mut self, fut: F) -> usize
+    where
+        F: Future<Output = FieldResult<S>> + Send + 'static,
+        S: Stream<Item = Result<QueryResponse>> + Send + 'static,
+    {
+        self.streams.insert(StreamEntry::Starting(
+            fut.map(|res| res.map(StreamExt::boxed)).boxed(),
+        ))
     }
 
     pub fn remove(&mut self, id: usize) {
-        self.streams.remove(id);
+        drop(self.streams.remove(id));
+    }
+
+    /// Poll all streams and convert their items with the transport.
+    ///
+    /// The streams are polled in turn, starting after the stream that produced the previous message, so a busy stream
+    /// can't starve the others.
+    ///
+    /// Returns `Poll::Ready(None)` if some streams made progress without producing a message, the caller should poll
+    /// again.
+    fn poll_next<T: SubscriptionTransport>(
+        &mut self,
+        transport: &mut T,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Bytes>> {
+        let mut progress = false;
+        let mut closed = Vec::new();
+        let mut output = None;
+
+        let next_id = self.next_id;
+        let (mut ids, before): (Vec<usize>, Vec<usize>) = self
+            .streams
+            .iter()
+            .map(|(id, _)| id)
+            .partition(|id| *id >= next_id);
+        ids.extend(before);
+
+        for id in ids {
+            let stream = match &mut self.streams[id] {
+                StreamEntry::Starting(fut) => match fut.as_mut().poll(cx) {
+                    Poll::Ready(Ok(stream)) => {
+                        progress = true;
+                        self.streams[id] = StreamEntry::Running(stream);
+                        continue;
+                    }
+                    Poll::Ready(Err(err)) => {
+                        progress = true;
+                        closed.push(id);
+                        if let Some(bytes) = transport.handle_start_error(id, err) {
+                            output = Some(bytes);
+                            self.next_id = id + 1;
+                            break;
+                        }
+                        continue;
+                    }
+                    Poll::Pending => continue,
+                },
+                StreamEntry::Running(stream) => stream,
+            };
+
+            match stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(res)) => {
+                    progress = true;
+                    if res.is_err() {
+                        closed.push(id);
+                    }
+                    if let Some(bytes) = transport.handle_response(id, res) {
+                        output = Some(bytes);
+                        self.next_id = id + 1;
+                        break;
+                    }
+                }
+                Poll::Ready(None) => {
+                    progress = true;
+                    closed.push(id);
+                    if let Some(bytes) = transport.handle_stream_end(id) {
+                        output = Some(bytes);
+                        self.next_id = id + 1;
+                        break;
+                    }
+                }
+                Poll::Pending => {}
+            }
+        }
+
+        closed.into_iter().for_each(|id| self.remove(id));
+        match output {
+            Some(bytes) => Poll::Ready(Some(bytes)),
+            None if progress => Poll::Ready(None),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// The close code and the reason of a WebSocket connection closed by the transport
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CloseFrame {
+    /// The close code, such as `4400` for an invalid message.
+    pub code: u16,
+
+    /// The reason of the closure.
+    pub reason: String,
+}
+
+impl CloseFrame {
+    /// Create a close frame.
+    pub fn new(code: u16, reason: impl Into<String>) -> Self {
+        Self {
+            code,
+            reason: reason.into(),
+        }
     }
 }
 
@@ -49,7 +174,47 @@ pub trait SubscriptionTransport: Send + Sync + Unpin + 'static {
         Subscription: SubscriptionType + Sync + Send + 'static;
 
     /// When a response message is generated, you can convert the message to the format you want here.
-    fn handle_response(&mut self, id: usize, res: Result<serde_json::Value>) -> Option<Bytes>;
+    fn handle_response(&mut self, id: usize, res: Result<QueryResponse>) -> Option<Bytes>;
+
+    /// When the future of a stream added with `SubscriptionStreams::add_future` fails, you can return a message that
+    /// tells the client here.
+    fn handle_start_error(&mut self, id: usize, err: FieldError) -> Option<Bytes> {
+        let _ = (id, err);
+        None
+    }
+
+    /// When a subscription stream ends, you can return a message that tells the client here.
+    fn handle_stream_end(&mut self, id: usize) -> Option<Bytes> {
+        let _ = id;
+        None
+    }
+
+    /// Called at every tick of the connection timer, see `SubscriptionStream::timer`.
+    /// You can return a `Byte` such as a keep-alive message here. If it returns an error, the connection will be broken.
+    fn handle_tick(&mut self) -> std::result::Result<Option<Bytes>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Returns `true` if the transport has closed the connection.
+    ///
+    /// It's checked before the connection reads the next request, so a message such as a rejection returned by
+    /// `handle_request` is sent to the client before the connection is closed.
+    fn is_closed(&self) -> bool {
+        false
+    }
+
+    /// Called when `handle_request` or `handle_tick` returns an error, you can return the close code and the reason of
+    /// the WebSocket here, see `SubscriptionStream::close_frame`.
+    fn close_frame(&mut self, err: Self::Error) -> Option<CloseFrame> {
+        let _ = err;
+        None
+    }
+
+    /// Called once when the connection is closed, you can return a future that runs the cleanup here.
+    /// The `SubscriptionStream` ends after the future completes.
+    fn handle_disconnect(&mut self) -> Option<BoxFuture<'static, ()>> {
+        None
+    }
 }
 
 pub fn create_connection<Query, Mutation, Subscription, T: SubscriptionTransport>(
@@ -69,34 +234,86 @@ where
         tx_bytes,
         SubscriptionStream {
             schema,
-            transport,
-            streams: SubscriptionStreams {
-                streams: Default::default(),
-            },
             rx_bytes,
-            handle_request_fut: None,
-            waker: AtomicWaker::new(),
+            ticks: None,
+            close_frame: None,
+            state: ConnectionState::Idle(
+                transport,
+                SubscriptionStreams {
+                    streams: Default::default(),
+                    next_id: 0,
+                },
+            ),
         },
     )
 }
 
-type HandleRequestBoxFut<T> = Pin<
-    Box<
-        dyn Future<Output = std::result::Result<Option<Bytes>, <T as SubscriptionTransport>::Error>>
-            + Send
-            + 'static,
-    >,
+type HandleRequestBoxFut<T> = BoxFuture<
+    'static,
+    (
+        T,
+        SubscriptionStreams,
+        std::result::Result<Option<Bytes>, <T as SubscriptionTransport>::Error>,
+    ),
 >;
 
+/// The state of a connection
+///
+/// The transport and the streams are owned by the request future while a request is handled, and
+/// given back when it completes, so the streams are not polled in the meantime. The request handlers
+/// don't wait for slow work such as the operation hooks, they add it with `SubscriptionStreams::add_future`
+/// so it runs concurrently with the other streams and the timer.
+enum ConnectionState<T: SubscriptionTransport> {
+    /// Waiting for requests, ticks and stream items.
+    Idle(T, SubscriptionStreams),
+
+    /// Handling a request.
+    HandlingRequest(HandleRequestBoxFut<T>),
+
+    /// Running the future returned by `SubscriptionTransport::handle_disconnect`.
+    Closing(BoxFuture<'static, ()>),
+
+    /// The connection is closed.
+    Closed,
+}
+
+impl<T: SubscriptionTransport> ConnectionState<T> {
+    fn close(mut transport: T) -> Self {
+        match transport.handle_disconnect() {
+            Some(fut) => ConnectionState::Closing(fut),
+            None => ConnectionState::Closed,
+        }
+    }
+}
+
 #[allow(missing_docs)]
-#[allow(clippy::type_complexity)]
 pub struct SubscriptionStream<Query, Mutation, Subscription, T: SubscriptionTransport> {
     schema: Schema<Query, Mutation, Subscription>,
-    transport: T,
-    streams: SubscriptionStreams,
     rx_bytes: mpsc::Receiver<Bytes>,
-    handle_request_fut: Option<HandleRequestBoxFut<T>>,
-    waker: AtomicWaker,
+    ticks: Option<Pin<Box<dyn Stream<Item = ()> + Send>>>,
+    close_frame: Option<CloseFrame>,
+    state: ConnectionState<T>,
+}
+
+impl<Query, Mutation, Subscription, T: SubscriptionTransport>
+    SubscriptionStream<Query, Mutation, Subscription, T>
+{
+    /// Set the timer of the connection, `SubscriptionTransport::handle_tick` is called for every item of the stream.
+    ///
+    /// The transport uses it for time based messages such as keep-alives, so it should tick about once per second.
+    pub fn timer<S: Stream<Item = ()> + Send + 'static>(self, ticks: S) -> Self {
+        Self {
+            ticks: Some(Box::pin(ticks)),
+            ..self
+        }
+    }
+
+    /// The close frame returned by `SubscriptionTransport::close_frame` if the transport closed the connection.
+    ///
+    /// The integrations send it to the client after the stream ends.
+    pub fn close_frame(&self) -> Option<&CloseFrame> {
+        self.close_frame.as_ref()
+    }
 }
 
 impl<Query, Mutation, Subscription, T> Stream
@@ -113,68 +330,87 @@ where
         let this = &mut *self;
 
         loop {
-            // receive bytes
-            if let Some(handle_request_fut) = &mut this.handle_request_fut {
-                match handle_request_fut.as_mut().poll(cx) {
-                    Poll::Ready(Ok(bytes)) => {
-                        this.handle_request_fut = None;
-                        if let Some(bytes) = bytes {
-                            return Poll::Ready(Some(bytes));
-                        }
+            match std::mem::replace(&mut this.state, ConnectionState::Closed) {
+                ConnectionState::Idle(mut transport, mut streams) => {
+                    if transport.is_closed() {
+                        this.state = ConnectionState::close(transport);
                         continue;
                     }
-                    Poll::Ready(Err(_)) => return Poll::Ready(None),
-                    Poll::Pending => {}
-                }
-            } else {
-                match Pin::new(&mut this.rx_bytes).poll_next(cx) {
-                    Poll::Ready(Some(data)) => {
-                        // The following code I think is safe.😁
-                        let transport = &mut this.transport as *mut T;
-                        let schema = &this.schema as *const Schema<Query, Mutation, Subscription>;
-                        let streams = &mut this.streams as *mut SubscriptionStreams;
-                        unsafe {
-                            this.handle_request_fut = Some(Box::pin((*transport).handle_request(
-                                &*schema,
-                                &mut *streams,
-                                data,
-                            )));
+
+                    // timer
+                    if let Some(ticks) = &mut this.ticks {
+                        match ticks.as_mut().poll_next(cx) {
+                            Poll::Ready(Some(())) => {
+                                match transport.handle_tick() {
+                                    Ok(bytes) => {
+                                        this.state = ConnectionState::Idle(transport, streams);
+                                        if let Some(bytes) = bytes {
+                                            return Poll::Ready(Some(bytes));
+                                        }
+                                    }
+                                    Err(err) => {
+                                        this.close_frame = transport.close_frame(err);
+                                        this.state = ConnectionState::close(transport);
+                                    }
+                                }
+                                continue;
+                            }
+                            Poll::Ready(None) => this.ticks = None,
+                            Poll::Pending => {}
                         }
-                        this.waker.wake();
-                        continue;
                     }
-                    Poll::Ready(None) => return Poll::Ready(None),
-                    Poll::Pending => {}
-                }
-            }
-
-            // receive msg
-            if !this.streams.streams.is_empty() {
-                let mut closed = Vec::new();
 
-                for (id, incoming_stream) in &mut this.streams.streams {
-                    match incoming_stream.as_mut().poll_next(cx) {
-                        Poll::Ready(Some(res)) => {
-                            if res.is_err() {
-                                closed.push(id);
-                            }
-                            if let Some(bytes) = this.transport.handle_response(id, res) {
-                                return Poll::Ready(Some(bytes));
-                            }
+                    // receive bytes
+                    match Pin::new(&mut this.rx_bytes).poll_next(cx) {
+                        Poll::Ready(Some(data)) => {
+                            let schema = this.schema.clone();
+                            this.state = ConnectionState::HandlingRequest(Box::pin(async move {
+                                let res =
+                                    transport.handle_request(&schema, &mut streams, data).await;
+                                (transport, streams, res)
+                            }));
+                            continue;
                         }
                         Poll::Ready(None) => {
-                            closed.push(id);
+                            this.state = ConnectionState::close(transport);
+                            continue;
                         }
                         Poll::Pending => {}
                     }
-                }
 
-                closed.iter().for_each(|id| this.streams.remove(*id));
-                this.waker.register(cx.waker());
-                return Poll::Pending;
-            } else {
-                this.waker.register(cx.waker());
-                return Poll::Pending;
+                    // receive msg
+                    let res = streams.poll_next(&mut transport, cx);
+                    this.state = ConnectionState::Idle(transport, streams);
+                    match res {
+                        Poll::Ready(Some(bytes)) => return Poll::Ready(Some(bytes)),
+                        Poll::Ready(None) => continue,
+                        Poll::Pending => return Poll::Pending,
+                    }
+                }
+                ConnectionState::HandlingRequest(mut fut) => match fut.as_mut().poll(cx) {
+                    Poll::Ready((transport, streams, Ok(bytes))) => {
+                        this.state = ConnectionState::Idle(transport, streams);
+                        if let Some(bytes) = bytes {
+                            return Poll::Ready(Some(bytes));
+                        }
+                    }
+                    Poll::Ready((mut transport, _, Err(err))) => {
+                        this.close_frame = transport.close_frame(err);
+                        this.state = ConnectionState::close(transport);
+                    }
+                    Poll::Pending => {
+                        this.state = ConnectionState::HandlingRequest(fut);
+                        return Poll::Pending;
+                    }
+                },
+                ConnectionState::Closing(mut fut) => {
+                    if fut.as_mut().poll(cx).is_pending() {
+                        this.state = ConnectionState::Closing(fut);
+                        return Poll::Pending;
+                    }
+                    return Poll::Ready(None);
+                }
+                ConnectionState::Closed => return Poll::Ready(None),
             }
         }
     }
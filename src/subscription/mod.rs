@@ -1,11 +1,19 @@
+mod broker;
 mod connection;
 mod simple_broker;
 mod subscription_type;
+mod transport_ws;
+mod ws_hooks;
+mod ws_protocols;
 mod ws_transport;
 
+pub use broker::{Broker, MemoryBroker, OverflowPolicy};
 pub use connection::{
-    create_connection, SubscriptionStream, SubscriptionStreams, SubscriptionTransport,
+    create_connection, CloseFrame, SubscriptionStream, SubscriptionStreams, SubscriptionTransport,
 };
 pub use simple_broker::SimpleBroker;
 pub use subscription_type::{create_subscription_stream, SubscriptionType};
+pub use transport_ws::GraphQLTransportWS;
+pub use ws_hooks::WebSocketHooks;
+pub use ws_protocols::WebSocketProtocols;
 pub use ws_transport::WebSocketTransport;
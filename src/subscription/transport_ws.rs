@@ -0,0 +1,237 @@
+use crate::context::Data;
+use crate::http::{GQLError, GQLRequest, GQLResponse};
+use crate::subscription::ws_transport::{field_error_payload, start_operation};
+use crate::{
+    CloseFrame, FieldError, FieldResult, ObjectType, QueryResponse, Result, Schema,
+    SubscriptionStreams, SubscriptionTransport, SubscriptionType, WebSocketHooks,
+};
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    ConnectionInit { payload: Option<serde_json::Value> },
+    Ping { payload: Option<serde_json::Value> },
+    Pong,
+    Subscribe { id: String, payload: GQLRequest },
+    Complete { id: String },
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerMessage {
+    ConnectionAck,
+    Pong {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        payload: Option<serde_json::Value>,
+    },
+    Next {
+        id: String,
+        payload: serde_json::Value,
+    },
+    Error {
+        id: String,
+        payload: serde_json::Value,
+    },
+    Complete {
+        id: String,
+    },
+}
+
+impl ServerMessage {
+    fn to_bytes(&self) -> Bytes {
+        serde_json::to_vec(self).unwrap().into()
+    }
+}
+
+/// WebSocket transport for the [graphql-transport-ws](https://github.com/enisdenjo/graphql-ws/blob/master/PROTOCOL.md) protocol
+///
+/// This is the protocol spoken by the `graphql-ws` client library, for the older Apollo
+/// `subscriptions-transport-ws` protocol use `WebSocketTransport`.
+///
+/// The protocol violations close the connection with the close codes of the protocol, see
+/// `SubscriptionStream::close_frame`. The connection init timeout needs a timer, see `SubscriptionStream::timer`.
+pub struct GraphQLTransportWS {
+    initialized: bool,
+    id_to_sid: HashMap<String, usize>,
+    sid_to_id: HashMap<usize, String>,
+    data: Arc<Data>,
+    hooks: WebSocketHooks,
+    connection_init_timeout: Option<Duration>,
+    created_at: Instant,
+}
+
+impl Default for GraphQLTransportWS {
+    fn default() -> Self {
+        Self {
+            initialized: false,
+            id_to_sid: Default::default(),
+            sid_to_id: Default::default(),
+            data: Default::default(),
+            hooks: Default::default(),
+            connection_init_timeout: Some(Duration::from_secs(10)),
+            created_at: Instant::now(),
+        }
+    }
+}
+
+impl GraphQLTransportWS {
+    /// Creates a websocket transport and sets the function that converts the `payload` of the `connection_init` message to `Data`.
+    pub fn new<F: Fn(serde_json::Value) -> FieldResult<Data> + Send + Sync + 'static>(
+        init_context_data: F,
+    ) -> Self {
+        GraphQLTransportWS {
+            hooks: WebSocketHooks::from_init_context_data(init_context_data),
+            ..GraphQLTransportWS::default()
+        }
+    }
+
+    /// Set the lifecycle hooks of the connection, they replace the function passed to `new`.
+    ///
+    /// The protocol has no rejection message, if `on_connect` fails the connection is closed with the code `4403`.
+    pub fn hooks(self, hooks: WebSocketHooks) -> Self {
+        Self { hooks, ..self }
+    }
+
+    /// Set how long to wait for the `connection_init` message before closing the connection with the code `4408`,
+    /// `None` waits forever. The default is 10 seconds.
+    pub fn connection_init_timeout(self, timeout: Option<Duration>) -> Self {
+        Self {
+            connection_init_timeout: timeout,
+            ..self
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl SubscriptionTransport for GraphQLTransportWS {
+    type Error = CloseFrame;
+
+    async fn handle_request<Query, Mutation, Subscription>(
+        &mut self,
+        schema: &Schema<Query, Mutation, Subscription>,
+        streams: &mut SubscriptionStreams,
+        data: Bytes,
+    ) -> std::result::Result<Option<Bytes>, Self::Error>
+    where
+        Query: ObjectType + Sync + Send + 'static,
+        Mutation: ObjectType + Sync + Send + 'static,
+        Subscription: SubscriptionType + Sync + Send + 'static,
+    {
+        let msg = serde_json::from_slice::<ClientMessage>(&data)
+            .map_err(|err| CloseFrame::new(4400, err.to_string()))?;
+        match msg {
+            ClientMessage::ConnectionInit { payload } => {
+                if self.initialized {
+                    return Err(CloseFrame::new(4429, "Too many initialisation requests"));
+                }
+                self.data = Arc::new(
+                    self.hooks
+                        .connect(payload)
+                        .await
+                        .map_err(|_| CloseFrame::new(4403, "Forbidden"))?,
+                );
+                self.initialized = true;
+                Ok(Some(ServerMessage::ConnectionAck.to_bytes()))
+            }
+            ClientMessage::Ping { payload } => Ok(Some(ServerMessage::Pong { payload }.to_bytes())),
+            ClientMessage::Pong => Ok(None),
+            ClientMessage::Subscribe { id, payload } => {
+                if !self.initialized {
+                    return Err(CloseFrame::new(4401, "Unauthorized"));
+                }
+                if self.id_to_sid.contains_key(&id) {
+                    return Err(CloseFrame::new(
+                        4409,
+                        format!("Subscriber for {} already exists", id),
+                    ));
+                }
+
+                let stream_id = streams.add_future(start_operation(
+                    schema,
+                    &self.hooks,
+                    self.data.clone(),
+                    payload,
+                ));
+                self.id_to_sid.insert(id.clone(), stream_id);
+                self.sid_to_id.insert(stream_id, id);
+                Ok(None)
+            }
+            ClientMessage::Complete { id } => {
+                if let Some(sid) = self.id_to_sid.remove(&id) {
+                    self.sid_to_id.remove(&sid);
+                    streams.remove(sid);
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    fn handle_tick(&mut self) -> std::result::Result<Option<Bytes>, Self::Error> {
+        if let (false, Some(timeout)) = (self.initialized, self.connection_init_timeout) {
+            if self.created_at.elapsed() >= timeout {
+                return Err(CloseFrame::new(4408, "Connection initialisation timeout"));
+            }
+        }
+        Ok(None)
+    }
+
+    fn close_frame(&mut self, err: Self::Error) -> Option<CloseFrame> {
+        Some(err)
+    }
+
+    fn handle_response(&mut self, id: usize, res: Result<QueryResponse>) -> Option<Bytes> {
+        match res {
+            Ok(resp) => {
+                let id = self.sid_to_id.get(&id)?.clone();
+                Some(
+                    ServerMessage::Next {
+                        id,
+                        payload: serde_json::to_value(GQLResponse(Ok(resp))).unwrap(),
+                    }
+                    .to_bytes(),
+                )
+            }
+            Err(err) => {
+                let id = self.sid_to_id.remove(&id)?;
+                self.id_to_sid.remove(&id);
+                Some(
+                    ServerMessage::Error {
+                        id,
+                        payload: serde_json::to_value(GQLError(&err)).unwrap(),
+                    }
+                    .to_bytes(),
+                )
+            }
+        }
+    }
+
+    fn handle_start_error(&mut self, id: usize, err: FieldError) -> Option<Bytes> {
+        let id = self.sid_to_id.remove(&id)?;
+        self.id_to_sid.remove(&id);
+        Some(
+            ServerMessage::Error {
+                id,
+                payload: serde_json::Value::Array(vec![field_error_payload(err)]),
+            }
+            .to_bytes(),
+        )
+    }
+
+    fn handle_stream_end(&mut self, id: usize) -> Option<Bytes> {
+        let id = self.sid_to_id.remove(&id)?;
+        self.id_to_sid.remove(&id);
+        Some(ServerMessage::Complete { id }.to_bytes())
+    }
+
+    fn handle_disconnect(&mut self) -> Option<BoxFuture<'static, ()>> {
+        if !self.initialized {
+            return None;
+        }
+        self.hooks.disconnect(self.data.clone())
+    }
+}
@@ -0,0 +1,135 @@
+use crate::context::Data;
+use crate::http::GQLRequest;
+use crate::FieldResult;
+use futures::future::BoxFuture;
+use futures::{Future, FutureExt};
+use std::sync::Arc;
+
+type OnConnectFn = dyn Fn(serde_json::Value) -> BoxFuture<'static, FieldResult<Data>> + Send + Sync;
+type OnOperationFn =
+    dyn Fn(Arc<Data>, GQLRequest) -> BoxFuture<'static, FieldResult<GQLRequest>> + Send + Sync;
+type OnDisconnectFn = dyn Fn(Arc<Data>) -> BoxFuture<'static, ()> + Send + Sync;
+
+/// Lifecycle hooks of a WebSocket connection
+///
+/// The hooks are shared by all connections created with them, cloning is cheap. Both
+/// `WebSocketTransport` and `GraphQLTransportWS` accept them with their `hooks` method.
+///
+/// # Examples
+///
+/// ```rust
+/// use async_graphql::*;
+///
+/// struct Token(String);
+///
+/// let hooks = WebSocketHooks::default()
+///     .on_connect(|payload| async move {
+///         let token = payload["token"].as_str().ok_or("Missing token")?.to_string();
+///         // Validate the token against the auth service here.
+///         let mut data = Data::default();
+///         data.insert(Token(token));
+///         Ok(data)
+///     })
+///     .on_operation(|data, request| async move {
+///         if data.get::<Token>().is_none() {
+///             return Err("Forbidden".into());
+///         }
+///         Ok(request)
+///     })
+///     .on_disconnect(|_data| async move {
+///         // Release the resources of the connection here.
+///     });
+/// let transport = WebSocketTransport::default().hooks(hooks);
+/// ```
+#[derive(Clone, Default)]
+pub struct WebSocketHooks {
+    on_connect: Option<Arc<OnConnectFn>>,
+    on_operation: Option<Arc<OnOperationFn>>,
+    on_disconnect: Option<Arc<OnDisconnectFn>>,
+}
+
+impl WebSocketHooks {
+    /// Set the function called with the payload of the `connection_init` message, the payload is `null` if the client
+    /// didn't send one.
+    ///
+    /// It returns the data of the connection, if it returns an error the connection is rejected and the error is sent
+    /// to the client as the payload of the rejection.
+    pub fn on_connect<F, R>(self, f: F) -> Self
+    where
+        F: Fn(serde_json::Value) -> R + Send + Sync + 'static,
+        R: Future<Output = FieldResult<Data>> + Send + 'static,
+    {
+        Self {
+            on_connect: Some(Arc::new(move |payload| f(payload).boxed())),
+            ..self
+        }
+    }
+
+    /// Set the function called with the data of the connection and the request before every operation is started.
+    ///
+    /// It can return a modified request, if it returns an error the operation is not started and the error is sent to
+    /// the client.
+    pub fn on_operation<F, R>(self, f: F) -> Self
+    where
+        F: Fn(Arc<Data>, GQLRequest) -> R + Send + Sync + 'static,
+        R: Future<Output = FieldResult<GQLRequest>> + Send + 'static,
+    {
+        Self {
+            on_operation: Some(Arc::new(move |data, request| f(data, request).boxed())),
+            ..self
+        }
+    }
+
+    /// Set the function called with the data of the connection when a connection accepted by `on_connect` is closed.
+    pub fn on_disconnect<F, R>(self, f: F) -> Self
+    where
+        F: Fn(Arc<Data>) -> R + Send + Sync + 'static,
+        R: Future<Output = ()> + Send + 'static,
+    {
+        Self {
+            on_disconnect: Some(Arc::new(move |data| f(data).boxed())),
+            ..self
+        }
+    }
+
+    /// Create the hooks from the synchronous function that converts the `connection_init` payload to `Data`, it isn't
+    /// called if the client didn't send a payload.
+    ///
+    /// It's the `init_context_data` function of the transports and the integrations.
+    pub fn from_init_context_data<F>(init_context_data: F) -> Self
+    where
+        F: Fn(serde_json::Value) -> FieldResult<Data> + Send + Sync + 'static,
+    {
+        Self::default().on_connect(move |payload| {
+            futures::future::ready(if payload.is_null() {
+                Ok(Data::default())
+            } else {
+                init_context_data(payload)
+            })
+        })
+    }
+
+    pub(crate) async fn connect(&self, payload: Option<serde_json::Value>) -> FieldResult<Data> {
+        match &self.on_connect {
+            Some(on_connect) => on_connect(payload.unwrap_or_default()).await,
+            None => Ok(Data::default()),
+        }
+    }
+
+    pub(crate) async fn operation(
+        &self,
+        data: Arc<Data>,
+        request: GQLRequest,
+    ) -> FieldResult<GQLRequest> {
+        match &self.on_operation {
+            Some(on_operation) => on_operation(data, request).await,
+            None => Ok(request),
+        }
+    }
+
+    pub(crate) fn disconnect(&self, data: Arc<Data>) -> Option<BoxFuture<'static, ()>> {
+        self.on_disconnect
+            .as_ref()
+            .map(|on_disconnect| on_disconnect(data))
+    }
+}
@@ -0,0 +1,36 @@
+/// The WebSocket subprotocols of GraphQL subscriptions
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum WebSocketProtocols {
+    /// The deprecated Apollo [subscriptions-transport-ws](https://github.com/apollographql/subscriptions-transport-ws/blob/master/PROTOCOL.md) protocol, see `WebSocketTransport`.
+    SubscriptionsTransportWS,
+
+    /// The [graphql-transport-ws](https://github.com/enisdenjo/graphql-ws/blob/master/PROTOCOL.md) protocol, see `GraphQLTransportWS`.
+    GraphQLTransportWS,
+}
+
+impl WebSocketProtocols {
+    /// Returns the value of the `Sec-WebSocket-Protocol` header for this protocol.
+    pub fn sec_websocket_protocol(&self) -> &'static str {
+        match self {
+            WebSocketProtocols::SubscriptionsTransportWS => "graphql-ws",
+            WebSocketProtocols::GraphQLTransportWS => "graphql-transport-ws",
+        }
+    }
+
+    /// Select a protocol from the value of the `Sec-WebSocket-Protocol` request header.
+    ///
+    /// The first protocol requested by the client that is supported wins. If the header is missing,
+    /// or contains no supported protocol, `SubscriptionsTransportWS` is selected for compatibility
+    /// with older clients.
+    pub fn negotiate(sec_websocket_protocol: Option<&str>) -> Self {
+        sec_websocket_protocol
+            .into_iter()
+            .flat_map(|value| value.split(','))
+            .find_map(|protocol| match protocol.trim() {
+                "graphql-ws" => Some(WebSocketProtocols::SubscriptionsTransportWS),
+                "graphql-transport-ws" => Some(WebSocketProtocols::GraphQLTransportWS),
+                _ => None,
+            })
+            .unwrap_or(WebSocketProtocols::SubscriptionsTransportWS)
+    }
+}
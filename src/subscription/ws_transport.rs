@@ -1,12 +1,18 @@
 use crate::context::Data;
 use crate::http::{GQLError, GQLRequest, GQLResponse};
+use crate::schema::parse_document;
 use crate::{
-    FieldError, FieldResult, ObjectType, QueryResponse, Result, Schema, SubscriptionStreams,
-    SubscriptionTransport, SubscriptionType, Variables,
+    FieldError, FieldResult, ObjectType, QueryBuilder, QueryResponse, Result, Schema,
+    SubscriptionStreams, SubscriptionTransport, SubscriptionType, Variables, WebSocketHooks,
 };
 use bytes::Bytes;
+use futures::future::BoxFuture;
+use futures::stream::BoxStream;
+use futures::{Future, StreamExt};
+use graphql_parser::query::{Definition, Document, OperationDefinition};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 #[derive(Serialize, Deserialize)]
 struct OperationMessage {
@@ -21,12 +27,38 @@ struct OperationMessage {
 }
 
 /// WebSocket transport
-#[derive(Default)]
+///
+/// It implements the Apollo [subscriptions-transport-ws](https://github.com/apollographql/subscriptions-transport-ws/blob/master/PROTOCOL.md) protocol.
+///
+/// The keep-alive messages and the connection init timeout need a timer, see `SubscriptionStream::timer`.
 pub struct WebSocketTransport {
     id_to_sid: HashMap<String, usize>,
     sid_to_id: HashMap<usize, String>,
     data: Arc<Data>,
-    init_context_data: Option<Box<dyn Fn(serde_json::Value) -> FieldResult<Data> + Send + Sync>>,
+    hooks: WebSocketHooks,
+    keep_alive_interval: Option<Duration>,
+    connection_init_timeout: Option<Duration>,
+    created_at: Instant,
+    last_keep_alive: Option<Instant>,
+    initialized: bool,
+    closed: bool,
+}
+
+impl Default for WebSocketTransport {
+    fn default() -> Self {
+        Self {
+            id_to_sid: Default::default(),
+            sid_to_id: Default::default(),
+            data: Default::default(),
+            hooks: Default::default(),
+            keep_alive_interval: Some(Duration::from_secs(10)),
+            connection_init_timeout: Some(Duration::from_secs(10)),
+            created_at: Instant::now(),
+            last_keep_alive: None,
+            initialized: false,
+            closed: false,
+        }
+    }
 }
 
 impl WebSocketTransport {
@@ -35,10 +67,32 @@ impl WebSocketTransport {
         init_context_data: F,
     ) -> Self {
         WebSocketTransport {
-            init_context_data: Some(Box::new(init_context_data)),
+            hooks: WebSocketHooks::from_init_context_data(init_context_data),
             ..WebSocketTransport::default()
         }
     }
+
+    /// Set the lifecycle hooks of the connection, they replace the function passed to `new`.
+    pub fn hooks(self, hooks: WebSocketHooks) -> Self {
+        Self { hooks, ..self }
+    }
+
+    /// Set the interval of the `ka` messages, `None` disables them. The default is 10 seconds.
+    pub fn keep_alive_interval(self, interval: Option<Duration>) -> Self {
+        Self {
+            keep_alive_interval: interval,
+            ..self
+        }
+    }
+
+    /// Set how long to wait for the `connection_init` message before closing the connection, `None` waits forever.
+    /// The default is 10 seconds.
+    pub fn connection_init_timeout(self, timeout: Option<Duration>) -> Self {
+        Self {
+            connection_init_timeout: timeout,
+            ..self
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -56,14 +110,31 @@ impl SubscriptionTransport for WebSocketTransport {
         Mutation: ObjectType + Sync + Send + 'static,
         Subscription: SubscriptionType + Sync + Send + 'static,
     {
+        if self.closed {
+            return Err("connection closed".into());
+        }
+
         match serde_json::from_slice::<OperationMessage>(&data) {
             Ok(msg) => match msg.ty.as_str() {
                 "connection_init" => {
-                    if let Some(payload) = msg.payload {
-                        if let Some(init_context_data) = &self.init_context_data {
-                            self.data = Arc::new(init_context_data(payload)?);
+                    match self.hooks.connect(msg.payload).await {
+                        Ok(data) => self.data = Arc::new(data),
+                        Err(err) => {
+                            // The connection is closed after the rejection is sent.
+                            self.closed = true;
+                            return Ok(Some(
+                                serde_json::to_vec(&OperationMessage {
+                                    ty: "connection_error".to_string(),
+                                    id: None,
+                                    payload: Some(field_error_payload(err)),
+                                })
+                                .unwrap()
+                                .into(),
+                            ));
                         }
                     }
+                    // the first `ka` is sent at the next tick
+                    self.initialized = true;
                     Ok(Some(
                         serde_json::to_vec(&OperationMessage {
                             ty: "connection_ack".to_string(),
@@ -76,39 +147,31 @@ impl SubscriptionTransport for WebSocketTransport {
                 }
                 "start" => {
                     if let (Some(id), Some(payload)) = (msg.id, msg.payload) {
+                        if self.id_to_sid.contains_key(&id) {
+                            // the operation that is running keeps the id
+                            let err = format!("Subscriber for {} already exists", id).into();
+                            return Ok(Some(
+                                serde_json::to_vec(&OperationMessage {
+                                    ty: "error".to_string(),
+                                    id: Some(id),
+                                    payload: Some(serde_json::Value::Array(vec![
+                                        field_error_payload(err),
+                                    ])),
+                                })
+                                .unwrap()
+                                .into(),
+                            ));
+                        }
                         if let Ok(request) = serde_json::from_value::<GQLRequest>(payload) {
-                            let variables = request
-                                .variables
-                                .map(|value| Variables::parse_from_json(value).ok())
-                                .flatten()
-                                .unwrap_or_default();
-                            match schema
-                                .create_subscription_stream(
-                                    &request.query,
-                                    request.operation_name.as_deref(),
-                                    variables,
-                                    Some(self.data.clone()),
-                                )
-                                .await
-                            {
-                                Ok(stream) => {
-                                    let stream_id = streams.add(stream);
-                                    self.id_to_sid.insert(id.clone(), stream_id);
-                                    self.sid_to_id.insert(stream_id, id);
-                                    Ok(None)
-                                }
-                                Err(err) => Ok(Some(
-                                    serde_json::to_vec(&OperationMessage {
-                                        ty: "error".to_string(),
-                                        id: Some(id),
-                                        payload: Some(
-                                            serde_json::to_value(GQLError(&err)).unwrap(),
-                                        ),
-                                    })
-                                    .unwrap()
-                                    .into(),
-                                )),
-                            }
+                            let stream_id = streams.add_future(start_operation(
+                                schema,
+                                &self.hooks,
+                                self.data.clone(),
+                                request,
+                            ));
+                            self.id_to_sid.insert(id.clone(), stream_id);
+                            self.sid_to_id.insert(stream_id, id);
+                            Ok(None)
                         } else {
                             Ok(None)
                         }
@@ -132,37 +195,211 @@ impl SubscriptionTransport for WebSocketTransport {
         }
     }
 
-    fn handle_response(&mut self, id: usize, res: Result<serde_json::Value>) -> Option<Bytes> {
-        if let Some(id) = self.sid_to_id.get(&id) {
-            match res {
-                Ok(value) => Some(
-                    serde_json::to_vec(&OperationMessage {
-                        ty: "data".to_string(),
-                        id: Some(id.clone()),
-                        payload: Some(
-                            serde_json::to_value(GQLResponse(Ok(QueryResponse {
-                                data: value,
-                                extensions: None,
-                                cache_control: Default::default(),
-                            })))
-                            .unwrap(),
-                        ),
-                    })
-                    .unwrap()
-                    .into(),
-                ),
-                Err(err) => Some(
+    fn handle_tick(&mut self) -> std::result::Result<Option<Bytes>, Self::Error> {
+        let now = Instant::now();
+        if !self.initialized {
+            if let Some(timeout) = self.connection_init_timeout {
+                if now.duration_since(self.created_at) >= timeout {
+                    return Err("connection initialisation timeout".into());
+                }
+            }
+            return Ok(None);
+        }
+
+        match (self.keep_alive_interval, self.last_keep_alive) {
+            (Some(_), None) => {
+                self.last_keep_alive = Some(now);
+                Ok(Some(keep_alive_message()))
+            }
+            (Some(interval), Some(last)) if now.duration_since(last) >= interval => {
+                self.last_keep_alive = Some(now);
+                Ok(Some(keep_alive_message()))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    fn handle_start_error(&mut self, id: usize, err: FieldError) -> Option<Bytes> {
+        let id = self.sid_to_id.remove(&id)?;
+        self.id_to_sid.remove(&id);
+        Some(
+            serde_json::to_vec(&OperationMessage {
+                ty: "error".to_string(),
+                id: Some(id),
+                payload: Some(serde_json::Value::Array(vec![field_error_payload(err)])),
+            })
+            .unwrap()
+            .into(),
+        )
+    }
+
+    fn handle_stream_end(&mut self, id: usize) -> Option<Bytes> {
+        let id = self.sid_to_id.remove(&id)?;
+        self.id_to_sid.remove(&id);
+        Some(
+            serde_json::to_vec(&OperationMessage {
+                ty: "complete".to_string(),
+                id: Some(id),
+                payload: None,
+            })
+            .unwrap()
+            .into(),
+        )
+    }
+
+    fn handle_disconnect(&mut self) -> Option<BoxFuture<'static, ()>> {
+        if !self.initialized {
+            return None;
+        }
+        self.hooks.disconnect(self.data.clone())
+    }
+
+    fn handle_response(&mut self, id: usize, res: Result<QueryResponse>) -> Option<Bytes> {
+        match res {
+            Ok(resp) => Some(
+                serde_json::to_vec(&OperationMessage {
+                    ty: "data".to_string(),
+                    id: Some(self.sid_to_id.get(&id)?.clone()),
+                    payload: Some(serde_json::to_value(GQLResponse(Ok(resp))).unwrap()),
+                })
+                .unwrap()
+                .into(),
+            ),
+            Err(err) => {
+                let id = self.sid_to_id.remove(&id)?;
+                self.id_to_sid.remove(&id);
+                Some(
                     serde_json::to_vec(&OperationMessage {
                         ty: "error".to_string(),
-                        id: Some(id.to_string()),
+                        id: Some(id),
                         payload: Some(serde_json::to_value(GQLError(&err)).unwrap()),
                     })
                     .unwrap()
                     .into(),
-                ),
+                )
+            }
+        }
+    }
+}
+
+fn keep_alive_message() -> Bytes {
+    serde_json::to_vec(&OperationMessage {
+        ty: "ka".to_string(),
+        id: None,
+        payload: None,
+    })
+    .unwrap()
+    .into()
+}
+
+pub(crate) fn field_error_payload(err: FieldError) -> serde_json::Value {
+    let mut payload = serde_json::Map::new();
+    payload.insert("message".to_string(), err.0.into());
+    if let Some(extensions) = err.1 {
+        payload.insert("extensions".to_string(), extensions);
+    }
+    payload.into()
+}
+
+/// Create the stream of an operation started on a WebSocket connection.
+///
+/// Queries and mutations are executed with `QueryBuilder::execute` and produce a single response, so
+/// the transport replies with one `data` message followed by `complete`.
+pub(crate) async fn create_operation_stream<Query, Mutation, Subscription>(
+    schema: &Schema<Query, Mutation, Subscription>,
+    request: GQLRequest,
+    data: Arc<Data>,
+) -> Result<BoxStream<'static, Result<QueryResponse>>>
+where
+    Query: ObjectType + Sync + Send + 'static,
+    Mutation: ObjectType + Sync + Send + 'static,
+    Subscription: SubscriptionType + Sync + Send + 'static,
+{
+    let variables = request
+        .variables
+        .and_then(|value| Variables::parse_from_json(value).ok())
+        .unwrap_or_default();
+
+    // the query is parsed once, for the operation that it selects
+    let extensions = schema.create_extensions();
+    let document = parse_document(&extensions, &request.query)?;
+
+    if is_subscription(&document, request.operation_name.as_deref()) {
+        return Ok(schema
+            .create_subscription_stream_with_document(
+                extensions,
+                document,
+                request.operation_name.as_deref(),
+                variables,
+                Some(data),
+            )
+            .await?
+            .boxed());
+    }
+
+    let mut builder = QueryBuilder::new(request.query).variables(variables);
+    if let Some(operation_name) = request.operation_name {
+        builder = builder.operator_name(operation_name);
+    }
+    let schema = schema.clone();
+    Ok(futures::stream::once(async move {
+        builder
+            .execute_document(&schema, Some(&data), extensions, document)
+            .await
+    })
+    .boxed())
+}
+
+/// Start an operation on a WebSocket connection, the future is added with `SubscriptionStreams::add_future` so a slow
+/// `on_operation` hook doesn't block the connection.
+///
+/// An error of the hook fails the future, an error of the operation is the only item of the stream.
+pub(crate) fn start_operation<Query, Mutation, Subscription>(
+    schema: &Schema<Query, Mutation, Subscription>,
+    hooks: &WebSocketHooks,
+    data: Arc<Data>,
+    request: GQLRequest,
+) -> impl Future<Output = FieldResult<BoxStream<'static, Result<QueryResponse>>>> + Send + 'static
+where
+    Query: ObjectType + Sync + Send + 'static,
+    Mutation: ObjectType + Sync + Send + 'static,
+    Subscription: SubscriptionType + Sync + Send + 'static,
+{
+    let schema = schema.clone();
+    let hooks = hooks.clone();
+    async move {
+        let request = hooks.operation(data.clone(), request).await?;
+        Ok(
+            match create_operation_stream(&schema, request, data).await {
+                Ok(stream) => stream,
+                Err(err) => futures::stream::once(async move { Err(err) }).boxed(),
+            },
+        )
+    }
+}
+
+/// Returns `true` if the operation selected by `operation_name` is a subscription, or if there is
+/// no such operation so that `Schema::create_subscription_stream` reports the error.
+fn is_subscription(document: &Document, operation_name: Option<&str>) -> bool {
+    for definition in &document.definitions {
+        let (name, is_subscription) = match definition {
+            Definition::Operation(OperationDefinition::SelectionSet(_)) => return false,
+            Definition::Operation(OperationDefinition::Query(query)) => (&query.name, false),
+            Definition::Operation(OperationDefinition::Mutation(mutation)) => {
+                (&mutation.name, false)
+            }
+            Definition::Operation(OperationDefinition::Subscription(subscription)) => {
+                (&subscription.name, true)
             }
-        } else {
-            None
+            Definition::Fragment(_) => continue,
+        };
+        if name.is_none() || operation_name.is_none() || name.as_deref() == operation_name {
+            return is_subscription;
         }
     }
+    true
 }
@@ -1,14 +1,16 @@
-use crate::types::connection::cursor::Cursor;
+use crate::types::connection::cursor::{Cursor, CursorType};
 use crate::types::connection::edge::Edge;
+use crate::types::connection::name::{ConnectionNameType, DefaultConnectionName};
 use crate::types::connection::page_info::PageInfo;
 use crate::{
-    do_resolve, registry, Context, ContextSelectionSet, EmptyEdgeFields, Error, ObjectType,
-    OutputValueType, Pos, QueryError, Result, Type,
+    do_resolve, registry, Context, ContextSelectionSet, EmptyEdgeFields, ObjectType,
+    OutputValueType, Pos, Result, Type,
 };
 use inflector::Inflector;
 use itertools::Itertools;
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::marker::PhantomData;
 
 /// Connection type
 ///
@@ -16,7 +18,18 @@ use std::collections::HashMap;
 /// If the `T` type is `OutputValueType`, you can return the value as a field function directly,
 /// otherwise you can use the `Connection::map` function to convert to a type that implements `OutputValueType`.
 /// `E` is an extension object type that extends the edge fields.
-pub struct Connection<T, E: ObjectType + Sync + Send = EmptyEdgeFields> {
+/// `C` is the type of the cursors, it is encoded with `CursorType::encode_cursor`.
+/// `A` is an extension object type that adds fields to the connection, building a schema fails if one of them has
+/// the name of a field of the connection.
+/// `N` names the connection and edge types, see `ConnectionNameType`.
+/// All of the parameters except `T` have defaults, so `Connection<T>` and `Connection<T, E>` are the usual connections.
+pub struct Connection<
+    T,
+    E: ObjectType + Sync + Send = EmptyEdgeFields,
+    C = Cursor,
+    A = EmptyEdgeFields,
+    N = DefaultConnectionName,
+> {
     /// The total number of records.
     pub total_count: Option<usize>,
 
@@ -24,31 +37,75 @@ pub struct Connection<T, E: ObjectType + Sync + Send = EmptyEdgeFields> {
     pub page_info: PageInfo,
 
     /// All records of the current page.
-    pub nodes: Vec<(Cursor, E, T)>,
+    pub nodes: Vec<(C, E, T)>,
+
+    /// The additional fields of the connection.
+    pub additional_fields: A,
+
+    _name: PhantomData<N>,
 }
 
-impl<T, E: ObjectType + Sync + Send> Connection<T, E> {
+impl<T, E, C, A, N> Connection<T, E, C, A, N>
+where
+    E: ObjectType + Sync + Send,
+    C: CursorType,
+    A: Default,
+{
     /// Create a connection object.
     pub fn new(
         total_count: Option<usize>,
         has_previous_page: bool,
         has_next_page: bool,
-        nodes: Vec<(Cursor, E, T)>,
+        nodes: Vec<(C, E, T)>,
     ) -> Self {
         Connection {
             total_count,
             page_info: PageInfo {
                 has_previous_page,
                 has_next_page,
-                start_cursor: nodes.first().map(|(cursor, _, _)| cursor.clone()),
-                end_cursor: nodes.last().map(|(cursor, _, _)| cursor.clone()),
+                start_cursor: nodes
+                    .first()
+                    .map(|(cursor, _, _)| cursor.encode_cursor().into()),
+                end_cursor: nodes
+                    .last()
+                    .map(|(cursor, _, _)| cursor.encode_cursor().into()),
             },
             nodes,
+            additional_fields: Default::default(),
+            _name: PhantomData,
+        }
+    }
+}
+
+impl<T, E, C, A, N> Connection<T, E, C, A, N>
+where
+    E: ObjectType + Sync + Send,
+    C: CursorType,
+{
+    /// Replace the additional fields of the connection.
+    pub fn with_additional_fields<A2>(self, additional_fields: A2) -> Connection<T, E, C, A2, N> {
+        Connection {
+            total_count: self.total_count,
+            page_info: self.page_info,
+            nodes: self.nodes,
+            additional_fields,
+            _name: PhantomData,
+        }
+    }
+
+    /// Change the names of the connection and edge types.
+    pub fn with_name<N2: ConnectionNameType>(self) -> Connection<T, E, C, A, N2> {
+        Connection {
+            total_count: self.total_count,
+            page_info: self.page_info,
+            nodes: self.nodes,
+            additional_fields: self.additional_fields,
+            _name: PhantomData,
         }
     }
 
     /// Convert node type.
-    pub fn map<O, F>(self, mut f: F) -> Connection<O, E>
+    pub fn map<O, F>(self, mut f: F) -> Connection<O, E, C, A, N>
     where
         F: FnMut(T) -> O,
     {
@@ -60,6 +117,8 @@ impl<T, E: ObjectType + Sync + Send> Connection<T, E> {
                 .into_iter()
                 .map(|(cursor, edge_type, node)| (cursor, edge_type, f(node)))
                 .collect(),
+            additional_fields: self.additional_fields,
+            _name: PhantomData,
         }
     }
 
@@ -71,15 +130,16 @@ impl<T, E: ObjectType + Sync + Send> Connection<T, E> {
 
     #[doc(hidden)]
     #[inline]
-    pub async fn edges(&self) -> Option<Vec<Option<Edge<'_, T, E>>>> {
+    pub async fn edges(&self) -> Option<Vec<Option<Edge<'_, T, E, N>>>> {
         Some(
             self.nodes
                 .iter()
                 .map(|(cursor, extra_type, node)| {
                     Some(Edge {
-                        cursor,
+                        cursor: cursor.encode_cursor(),
                         extra_type,
                         node,
+                        _name: PhantomData,
                     })
                 })
                 .collect_vec(),
@@ -91,15 +151,47 @@ impl<T, E: ObjectType + Sync + Send> Connection<T, E> {
     pub async fn total_count(&self) -> Option<i32> {
         self.total_count.map(|n| n as i32)
     }
+
+    #[doc(hidden)]
+    #[inline]
+    pub async fn nodes(&self) -> Vec<&T> {
+        self.nodes.iter().map(|(_, _, item)| item).collect_vec()
+    }
 }
 
-impl<T: OutputValueType + Send + Sync, E: ObjectType + Sync + Send> Type for Connection<T, E> {
+impl<T, E, C, A, N> Type for Connection<T, E, C, A, N>
+where
+    T: OutputValueType + Send + Sync,
+    E: ObjectType + Sync + Send,
+    C: CursorType + Send + Sync,
+    A: ObjectType + Sync + Send,
+    N: ConnectionNameType,
+{
     fn type_name() -> Cow<'static, str> {
-        Cow::Owned(format!("{}Connection", T::type_name()))
+        Cow::Owned(N::connection_type_name::<T>())
     }
 
     fn create_type_info(registry: &mut registry::Registry) -> String {
-        registry.create_type::<Self, _>(|registry| registry::Type::Object {
+        registry.create_type::<Self, _>(|registry| {
+            let additional_fields = match registry.build_type::<A>() {
+                registry::Type::Object { fields, .. } => fields,
+                _ => Default::default(),
+            };
+
+            let elements_name = T::type_name().to_plural().to_camel_case();
+            let reserved = ["pageInfo", "edges", "totalCount", "nodes", &elements_name];
+            for name in additional_fields.keys() {
+                if reserved.contains(&name.as_str()) {
+                    registry.errors.push(format!(
+                        "The additional field \"{}\" of \"{}\" conflicts with a field of the connection \"{}\".",
+                        name,
+                        A::type_name(),
+                        Self::type_name()
+                    ));
+                }
+            }
+
+            registry::Type::Object {
             name: Self::type_name().to_string(),
             description: None,
             fields: {
@@ -126,7 +218,7 @@ impl<T: OutputValueType + Send + Sync, E: ObjectType + Sync + Send> Type for Con
                         name: "edges".to_string(),
                         description: Some("A list of edges."),
                         args: Default::default(),
-                        ty: <Option::<Vec<Option<Edge<T,E>>>> as Type>::create_type_info(registry),
+                        ty: <Option::<Vec<Option<Edge<T,E,N>>>> as Type>::create_type_info(registry),
                         deprecation: None,
                         cache_control: Default::default(),
                         external: false,
@@ -150,7 +242,6 @@ impl<T: OutputValueType + Send + Sync, E: ObjectType + Sync + Send> Type for Con
                     },
                 );
 
-                let elements_name = T::type_name().to_plural().to_camel_case();
                 fields.insert(elements_name.clone(),registry::Field{
                     name: elements_name,
                     description: Some(r#"A list of all of the objects returned in the connection. This is a convenience field provided for quickly exploring the API; rather than querying for "{ edges { node } }" when no edge data is needed, this field can be be used instead. Note that when clients like Relay need to fetch the "cursor" field on the edge to enable efficient pagination, this shortcut cannot be used, and the full "{ edges { node } }" version should be used instead."#),
@@ -163,18 +254,39 @@ impl<T: OutputValueType + Send + Sync, E: ObjectType + Sync + Send> Type for Con
                     provides: None
                 });
 
+                fields.insert(
+                    "nodes".to_string(),
+                    registry::Field {
+                        name: "nodes".to_string(),
+                        description: Some(r#"A list of the objects of the edges, a shortcut for "{ edges { node } }" when no edge data is needed."#),
+                        args: Default::default(),
+                        ty: Vec::<T>::create_type_info(registry),
+                        deprecation: None,
+                        cache_control: Default::default(),
+                        external: false,
+                        requires: None,
+                        provides: None
+                    },
+                );
+
+                fields.extend(additional_fields);
                 fields
             },
             cache_control: Default::default(),
             extends: false,
             keys: None
-        })
+        }})
     }
 }
 
 #[async_trait::async_trait]
-impl<T: OutputValueType + Send + Sync, E: ObjectType + Sync + Send> ObjectType
-    for Connection<T, E>
+impl<T, E, C, A, N> ObjectType for Connection<T, E, C, A, N>
+where
+    T: OutputValueType + Send + Sync,
+    E: ObjectType + Sync + Send,
+    C: CursorType + Send + Sync,
+    A: ObjectType + Sync + Send,
+    N: ConnectionNameType,
 {
     async fn resolve_field(&self, ctx: &Context<'_>) -> Result<serde_json::Value> {
         if ctx.name.as_str() == "pageInfo" {
@@ -187,26 +299,25 @@ impl<T: OutputValueType + Send + Sync, E: ObjectType + Sync + Send> ObjectType
             let ctx_obj = ctx.with_selection_set(&ctx.selection_set);
             return OutputValueType::resolve(&self.total_count().await, &ctx_obj, ctx.position)
                 .await;
-        } else if ctx.name.as_str() == T::type_name().to_plural().to_camel_case() {
+        } else if ctx.name.as_str() == "nodes"
+            || ctx.name.as_str() == T::type_name().to_plural().to_camel_case()
+        {
             let ctx_obj = ctx.with_selection_set(&ctx.selection_set);
-            let items = self.nodes.iter().map(|(_, _, item)| item).collect_vec();
-            return OutputValueType::resolve(&items, &ctx_obj, ctx.position).await;
+            return OutputValueType::resolve(&self.nodes().await, &ctx_obj, ctx.position).await;
         }
 
-        Err(Error::Query {
-            pos: ctx.position,
-            path: None,
-            err: QueryError::FieldNotFound {
-                field_name: ctx.name.clone(),
-                object: Connection::<T, E>::type_name().to_string(),
-            },
-        })
+        self.additional_fields.resolve_field(ctx).await
     }
 }
 
 #[async_trait::async_trait]
-impl<T: OutputValueType + Send + Sync, E: ObjectType + Sync + Send> OutputValueType
-    for Connection<T, E>
+impl<T, E, C, A, N> OutputValueType for Connection<T, E, C, A, N>
+where
+    T: OutputValueType + Send + Sync,
+    E: ObjectType + Sync + Send,
+    C: CursorType + Send + Sync,
+    A: ObjectType + Sync + Send,
+    N: ConnectionNameType,
 {
     async fn resolve(&self, ctx: &ContextSelectionSet<'_>, _pos: Pos) -> Result<serde_json::Value> {
         do_resolve(ctx, self).await
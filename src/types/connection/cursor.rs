@@ -1,5 +1,9 @@
-use crate::{Result, ScalarType, Value};
+use crate::{FieldResult, Result, ScalarType, Value};
 use async_graphql_derive::Scalar;
+use chrono::{DateTime, Utc};
+use std::convert::Infallible;
+use std::fmt::{self, Display, Formatter};
+use std::num::ParseIntError;
 use std::ops::{Deref, DerefMut};
 
 /// Cursor scalar
@@ -64,3 +68,125 @@ impl ScalarType for Cursor {
         Ok(self.0.to_string().into())
     }
 }
+
+/// Cursor type
+///
+/// A type that implements `CursorType` can be used as the cursor of a `Connection`, the cursor
+/// passed by the client is decoded into it before the query is executed.
+pub trait CursorType: Sized {
+    /// Error type for `decode_cursor`.
+    type Error: Display;
+
+    /// Decode the cursor from a string.
+    fn decode_cursor(s: &str) -> std::result::Result<Self, Self::Error>;
+
+    /// Encode the cursor to a string.
+    fn encode_cursor(&self) -> String;
+}
+
+impl CursorType for Cursor {
+    type Error = Infallible;
+
+    fn decode_cursor(s: &str) -> std::result::Result<Self, Self::Error> {
+        Ok(s.into())
+    }
+
+    fn encode_cursor(&self) -> String {
+        self.0.clone()
+    }
+}
+
+impl CursorType for String {
+    type Error = Infallible;
+
+    fn decode_cursor(s: &str) -> std::result::Result<Self, Self::Error> {
+        Ok(s.to_string())
+    }
+
+    fn encode_cursor(&self) -> String {
+        self.clone()
+    }
+}
+
+macro_rules! int_cursor_type {
+    ($($ty:ty),*) => {
+        $(
+        impl CursorType for $ty {
+            type Error = ParseIntError;
+
+            fn decode_cursor(s: &str) -> std::result::Result<Self, Self::Error> {
+                s.parse()
+            }
+
+            fn encode_cursor(&self) -> String {
+                self.to_string()
+            }
+        }
+        )*
+    };
+}
+
+int_cursor_type!(i32, i64, u32, u64, usize);
+
+impl CursorType for DateTime<Utc> {
+    type Error = chrono::ParseError;
+
+    fn decode_cursor(s: &str) -> std::result::Result<Self, Self::Error> {
+        Ok(DateTime::parse_from_rfc3339(s)?.with_timezone(&Utc))
+    }
+
+    fn encode_cursor(&self) -> String {
+        self.to_rfc3339()
+    }
+}
+
+/// The error of decoding a tuple cursor
+#[derive(Debug)]
+pub struct TupleCursorError(String);
+
+impl Display for TupleCursorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+macro_rules! tuple_cursor_type {
+    ($($name:ident: $idx:tt),*) => {
+        impl<$($name: CursorType),*> CursorType for ($($name,)*) {
+            type Error = TupleCursorError;
+
+            fn decode_cursor(s: &str) -> std::result::Result<Self, Self::Error> {
+                let mut parts = s.split(':');
+                let res = ($(
+                    {
+                        let part = parts
+                            .next()
+                            .and_then(|part| base64::decode(part).ok())
+                            .and_then(|part| String::from_utf8(part).ok())
+                            .ok_or_else(|| TupleCursorError("Invalid tuple cursor.".to_string()))?;
+                        $name::decode_cursor(&part)
+                            .map_err(|err| TupleCursorError(err.to_string()))?
+                    },
+                )*);
+                if parts.next().is_some() {
+                    return Err(TupleCursorError("Invalid tuple cursor.".to_string()));
+                }
+                Ok(res)
+            }
+
+            fn encode_cursor(&self) -> String {
+                vec![$(base64::encode(self.$idx.encode_cursor())),*].join(":")
+            }
+        }
+    };
+}
+
+tuple_cursor_type!(A: 0, B: 1);
+tuple_cursor_type!(A: 0, B: 1, C: 2);
+tuple_cursor_type!(A: 0, B: 1, C: 2, D: 3);
+
+/// Decode the cursor passed by the client, returns a field error if it is invalid.
+pub(crate) fn decode_cursor<C: CursorType>(cursor: &Cursor) -> FieldResult<C> {
+    C::decode_cursor(cursor)
+        .map_err(|err| format!("Invalid cursor \"{}\": {}", cursor.0, err).into())
+}
@@ -1,20 +1,24 @@
+use crate::types::connection::name::ConnectionNameType;
 use crate::{
     do_resolve, registry, Context, ContextSelectionSet, ObjectType, OutputValueType, Result, Type,
 };
 use graphql_parser::Pos;
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::marker::PhantomData;
 
-pub struct Edge<'a, T, E> {
-    pub cursor: &'a str,
+pub struct Edge<'a, T, E, N> {
+    pub cursor: String,
     pub node: &'a T,
     pub extra_type: &'a E,
+    pub _name: PhantomData<N>,
 }
 
-impl<'a, T, E> Edge<'a, T, E>
+impl<'a, T, E, N> Edge<'a, T, E, N>
 where
     T: OutputValueType + Send + Sync + 'a,
     E: ObjectType + Sync + Send + 'a,
+    N: ConnectionNameType,
 {
     #[doc(hidden)]
     #[inline]
@@ -25,17 +29,18 @@ where
     #[doc(hidden)]
     #[inline]
     pub async fn cursor(&self) -> &str {
-        self.cursor
+        &self.cursor
     }
 }
 
-impl<'a, T, E> Type for Edge<'a, T, E>
+impl<'a, T, E, N> Type for Edge<'a, T, E, N>
 where
     T: OutputValueType + Send + Sync + 'a,
     E: ObjectType + Sync + Send + 'a,
+    N: ConnectionNameType,
 {
     fn type_name() -> Cow<'static, str> {
-        Cow::Owned(format!("{}Edge", T::type_name()))
+        Cow::Owned(N::edge_type_name::<T>())
     }
 
     fn create_type_info(registry: &mut registry::Registry) -> String {
@@ -97,10 +102,11 @@ where
 }
 
 #[async_trait::async_trait]
-impl<'a, T, E> ObjectType for Edge<'a, T, E>
+impl<'a, T, E, N> ObjectType for Edge<'a, T, E, N>
 where
     T: OutputValueType + Send + Sync + 'a,
     E: ObjectType + Sync + Send + 'a,
+    N: ConnectionNameType,
 {
     async fn resolve_field(&self, ctx: &Context<'_>) -> Result<serde_json::Value> {
         if ctx.name.as_str() == "node" {
@@ -115,10 +121,11 @@ where
 }
 
 #[async_trait::async_trait]
-impl<'a, T, E> OutputValueType for Edge<'a, T, E>
+impl<'a, T, E, N> OutputValueType for Edge<'a, T, E, N>
 where
     T: OutputValueType + Send + Sync + 'a,
     E: ObjectType + Sync + Send + 'a,
+    N: ConnectionNameType,
 {
     async fn resolve(&self, ctx: &ContextSelectionSet<'_>, _pos: Pos) -> Result<serde_json::Value> {
         do_resolve(ctx, self).await
@@ -0,0 +1,152 @@
+use crate::types::connection::cursor::CursorType;
+use crate::{Connection, FieldResult, ObjectType, QueryOperation};
+
+/// The direction in which a keyset query reads the rows
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum KeysetDirection {
+    /// Read the rows in ascending key order, starting after `after_key`
+    Forward,
+
+    /// Read the rows in descending key order, starting before `before_key`
+    Backward,
+}
+
+/// A normalized keyset pagination plan
+///
+/// It turns any `QueryOperation` into the same four values, so a data source backed by a table
+/// only has to implement a single query:
+///
+/// ```sql
+/// SELECT * FROM t
+/// WHERE key > :after_key AND key < :before_key
+/// ORDER BY key ASC -- DESC if the direction is backward
+/// LIMIT :fetch_limit
+/// ```
+///
+/// The query fetches `limit + 1` rows, the extra row tells `KeysetQuery::into_connection` whether
+/// there is another page.
+///
+/// # Examples
+///
+/// ```rust
+/// use async_graphql::*;
+///
+/// struct Numbers;
+///
+/// #[DataSource]
+/// impl DataSource for Numbers {
+///     type CursorType = i32;
+///     type Element = i32;
+///     type EdgeFieldsObj = EmptyEdgeFields;
+///
+///     async fn query_operation(&self, ctx: &Context<'_>, operation: &QueryOperation<i32>) -> FieldResult<Connection<i32, EmptyEdgeFields, i32>> {
+///         let query = KeysetQuery::new(operation)?;
+///
+///         // Simulate `SELECT n FROM numbers WHERE ... ORDER BY n LIMIT ...`
+///         let mut rows: Vec<i32> = (0..100)
+///             .filter(|n| query.after_key.map(|key| *n > key).unwrap_or(true))
+///             .filter(|n| query.before_key.map(|key| *n < key).unwrap_or(true))
+///             .collect();
+///         if query.direction == KeysetDirection::Backward {
+///             rows.reverse();
+///         }
+///         if let Some(limit) = query.fetch_limit() {
+///             rows.truncate(limit);
+///         }
+///
+///         Ok(query.into_connection(rows.into_iter().map(|n| (n, EmptyEdgeFields, n)).collect()))
+///     }
+/// }
+///
+/// struct QueryRoot;
+///
+/// #[Object]
+/// impl QueryRoot {
+///     async fn numbers(&self, ctx: &Context<'_>,
+///         after: Option<Cursor>,
+///         before: Option<Cursor>,
+///         first: Option<i32>,
+///         last: Option<i32>
+///     ) -> FieldResult<Connection<i32, EmptyEdgeFields, i32>> {
+///         Numbers.query(ctx, after, before, first, last).await
+///     }
+/// }
+///
+/// async_std::task::block_on(async move {
+///     let schema = Schema::new(QueryRoot, EmptyMutation, EmptySubscription);
+///     let res = schema.execute(r#"{ numbers(last: 2, before: "10") { edges { node } pageInfo { hasPreviousPage hasNextPage } } }"#).await.unwrap().data;
+///     assert_eq!(res, serde_json::json!({
+///         "numbers": {
+///             "edges": [{ "node": 8 }, { "node": 9 }],
+///             "pageInfo": { "hasPreviousPage": true, "hasNextPage": true },
+///         }
+///     }));
+/// });
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct KeysetQuery<C> {
+    /// Only return the rows with a key greater than this key
+    pub after_key: Option<C>,
+
+    /// Only return the rows with a key less than this key
+    pub before_key: Option<C>,
+
+    /// The maximum number of rows of the page, `None` means no limit
+    pub limit: Option<usize>,
+
+    /// The direction in which the rows are read
+    pub direction: KeysetDirection,
+}
+
+impl<C: CursorType + Clone> KeysetQuery<C> {
+    /// Create a plan from the query operation, returns an error if the operation is invalid.
+    pub fn new(operation: &QueryOperation<C>) -> FieldResult<Self> {
+        let (after_key, before_key, first, last) = operation
+            .split()
+            .ok_or("The \"first\" and \"last\" parameters cannot exist at the same time.")?;
+        let (limit, direction) = match last {
+            Some(last) => (Some(last), KeysetDirection::Backward),
+            None => (first, KeysetDirection::Forward),
+        };
+
+        Ok(Self {
+            after_key: after_key.cloned(),
+            before_key: before_key.cloned(),
+            limit,
+            direction,
+        })
+    }
+
+    /// The number of rows to fetch, one more than the limit to find out whether there is another page.
+    pub fn fetch_limit(&self) -> Option<usize> {
+        self.limit.map(|limit| limit.saturating_add(1))
+    }
+
+    /// Create the connection from the fetched rows.
+    ///
+    /// The rows must be in the order of the direction, and at most `fetch_limit` rows are expected.
+    /// The extra row is removed, and the rows of a backward query are reversed, so the edges are
+    /// always in ascending key order.
+    pub fn into_connection<T, E: ObjectType + Sync + Send>(
+        self,
+        mut rows: Vec<(C, E, T)>,
+    ) -> Connection<T, E, C> {
+        let has_more = match self.limit {
+            Some(limit) if rows.len() > limit => {
+                rows.truncate(limit);
+                true
+            }
+            _ => false,
+        };
+
+        match self.direction {
+            KeysetDirection::Forward => {
+                Connection::new(None, self.after_key.is_some(), has_more, rows)
+            }
+            KeysetDirection::Backward => {
+                rows.reverse();
+                Connection::new(None, has_more, self.before_key.is_some(), rows)
+            }
+        }
+    }
+}
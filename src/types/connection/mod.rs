@@ -1,35 +1,43 @@
 mod connection_type;
 mod cursor;
 mod edge;
+mod keyset;
+mod name;
 mod page_info;
 mod slice;
+mod stream;
 
 use crate::{Context, FieldResult, ObjectType};
 
 pub use connection_type::Connection;
-pub use cursor::Cursor;
+pub use cursor::{Cursor, CursorType, TupleCursorError};
+pub use keyset::{KeysetDirection, KeysetQuery};
+pub use name::{ConnectionNameType, DefaultConnectionName};
 pub use page_info::PageInfo;
+pub use stream::{IterDataSource, StreamDataSource};
 
 /// Connection query operation
-pub enum QueryOperation {
+///
+/// The cursors are decoded into `C`, the cursor type of the `DataSource`.
+pub enum QueryOperation<C = Cursor> {
     /// Return all results
     None,
     /// Return all results after the cursor
     After {
         /// After this cursor
-        after: Cursor,
+        after: C,
     },
     /// Return all results before the cursor
     Before {
         /// Before this cursor
-        before: Cursor,
+        before: C,
     },
     /// Return all results between the cursors
     Between {
         /// After this cursor
-        after: Cursor,
+        after: C,
         /// But before this cursor
-        before: Cursor,
+        before: C,
     },
     /// Return the amount of results specified by `limit`, starting from the beginning
     First {
@@ -41,23 +49,23 @@ pub enum QueryOperation {
         /// The maximum amount of results to return
         limit: usize,
         /// After this cursor
-        after: Cursor,
+        after: C,
     },
     /// Return the amount of results specified by `limit`, starting from the beginning but ending before the cursor
     FirstBefore {
         /// The maximum amount of results to return
         limit: usize,
         /// Before this cursor
-        before: Cursor,
+        before: C,
     },
     /// Return the amount of results specified by `limit`, but between the cursors. Limit includes beginning results.
     FirstBetween {
         /// The maximum amount of results to return
         limit: usize,
         /// After this cursor
-        after: Cursor,
+        after: C,
         /// But before this cursor
-        before: Cursor,
+        before: C,
     },
     /// Return the amount of results specified by `limit`, but before the end
     Last {
@@ -69,36 +77,73 @@ pub enum QueryOperation {
         /// The maximum amount of results to return
         limit: usize,
         /// After this cursor
-        after: Cursor,
+        after: C,
     },
     /// Return the amount of results specified by `limit`, but before the cursor
     LastBefore {
         /// The maximum amount of results to return
         limit: usize,
         /// Before this cursor
-        before: Cursor,
+        before: C,
     },
     /// Return the amount of results specified by `limit`, but between the cursors. Limit includes ending results.
     LastBetween {
         /// The maximum amount of results to return
         limit: usize,
         /// After this cursor
-        after: Cursor,
+        after: C,
         /// But before this cursor
-        before: Cursor,
+        before: C,
     },
     /// An invalid query was made. For example: sending `first` and `last` in the same query
     Invalid,
 }
 
-/// Empty edge extension object
+// The `after`, `before`, `first` and `last` parameters of a query operation
+type OperationParts<'a, C> = (Option<&'a C>, Option<&'a C>, Option<usize>, Option<usize>);
+
+impl<C> QueryOperation<C> {
+    /// Returns the `after`, `before`, `first` and `last` parameters, or `None` if the operation is invalid.
+    pub(crate) fn split(&self) -> Option<OperationParts<'_, C>> {
+        Some(match self {
+            QueryOperation::None => (None, None, None, None),
+            QueryOperation::After { after } => (Some(after), None, None, None),
+            QueryOperation::Before { before } => (None, Some(before), None, None),
+            QueryOperation::Between { after, before } => (Some(after), Some(before), None, None),
+            QueryOperation::First { limit } => (None, None, Some(*limit), None),
+            QueryOperation::FirstAfter { after, limit } => (Some(after), None, Some(*limit), None),
+            QueryOperation::FirstBefore { before, limit } => {
+                (None, Some(before), Some(*limit), None)
+            }
+            QueryOperation::FirstBetween {
+                after,
+                before,
+                limit,
+            } => (Some(after), Some(before), Some(*limit), None),
+            QueryOperation::Last { limit } => (None, None, None, Some(*limit)),
+            QueryOperation::LastAfter { after, limit } => (Some(after), None, None, Some(*limit)),
+            QueryOperation::LastBefore { before, limit } => {
+                (None, Some(before), None, Some(*limit))
+            }
+            QueryOperation::LastBetween {
+                after,
+                before,
+                limit,
+            } => (Some(after), Some(before), None, Some(*limit)),
+            QueryOperation::Invalid => return None,
+        })
+    }
+}
+
+/// Empty extension object for edges and connections
 #[async_graphql_derive::SimpleObject(internal)]
+#[derive(Default)]
 pub struct EmptyEdgeFields;
 
 // Temporary struct for to store values for pattern matching
-struct Pagination {
-    after: Option<Cursor>,
-    before: Option<Cursor>,
+struct Pagination<C> {
+    after: Option<C>,
+    before: Option<C>,
     first: Option<i32>,
     last: Option<i32>,
 }
@@ -114,7 +159,6 @@ struct Pagination {
 ///
 /// ```rust
 /// use async_graphql::*;
-/// use byteorder::{ReadBytesExt, BE};
 ///
 /// struct QueryRoot;
 ///
@@ -127,10 +171,11 @@ struct Pagination {
 ///
 /// #[DataSource]
 /// impl DataSource for Numbers {
+///     type CursorType = i32;
 ///     type Element = i32;
 ///     type EdgeFieldsObj = DiffFields;
 ///
-///     async fn query_operation(&self, ctx: &Context<'_>, operation: &QueryOperation) -> FieldResult<Connection<Self::Element, Self::EdgeFieldsObj>> {
+///     async fn query_operation(&self, ctx: &Context<'_>, operation: &QueryOperation<i32>) -> FieldResult<Connection<Self::Element, Self::EdgeFieldsObj, i32>> {
 ///         let (start, end) = match operation {
 ///             QueryOperation::First {limit} => {
 ///                 let start = 0;
@@ -143,19 +188,12 @@ struct Pagination {
 ///                 (start, end)
 ///             }
 ///             QueryOperation::FirstAfter {after, limit} => {
-///                 let start = base64::decode(after.to_string())
-///                     .ok()
-///                     .and_then(|data| data.as_slice().read_i32::<BE>().ok())
-///                     .map(|idx| idx + 1)
-///                     .unwrap_or(0);
+///                 let start = *after + 1;
 ///                 let end = start + *limit as i32;
 ///                 (start, end)
 ///             }
 ///             QueryOperation::LastBefore {before, limit} => {
-///                 let end = base64::decode(before.to_string())
-///                     .ok()
-///                     .and_then(|data| data.as_slice().read_i32::<BE>().ok())
-///                     .unwrap_or(0);
+///                 let end = *before;
 ///                 let start = end - *limit as i32;
 ///                 (start, end)
 ///             }
@@ -163,7 +201,7 @@ struct Pagination {
 ///             _ => (0, 10)
 ///         };
 ///
-///         let nodes = (start..end).into_iter().map(|n| (base64::encode(n.to_be_bytes()).into(), DiffFields {diff: n - 1000}, n)).collect();
+///         let nodes = (start..end).into_iter().map(|n| (n, DiffFields {diff: n - 1000}, n)).collect();
 ///         Ok(Connection::new(None, true, true, nodes))
 ///     }
 /// }
@@ -175,7 +213,7 @@ struct Pagination {
 ///         before: Option<Cursor>,
 ///         first: Option<i32>,
 ///         last: Option<i32>
-///     ) -> FieldResult<Connection<i32, DiffFields>> {
+///     ) -> FieldResult<Connection<i32, DiffFields, i32>> {
 ///         Numbers.query(ctx, after, before, first, last).await
 ///     }
 /// }
@@ -201,10 +239,26 @@ struct Pagination {
 ///             ]
 ///         },
 ///     }));
+///
+///     assert_eq!(schema.execute(r#"{ numbers(first: 2, after: "1") { edges { node cursor } } }"#).await.unwrap().data, serde_json::json!({
+///         "numbers": {
+///             "edges": [
+///                 {"node": 2, "cursor": "2"},
+///                 {"node": 3, "cursor": "3"}
+///             ]
+///         },
+///     }));
+///
+///     assert!(schema.execute(r#"{ numbers(first: 2, after: "a") { edges { node } } }"#).await.is_err());
 /// }
 /// ```
 #[async_trait::async_trait]
 pub trait DataSource: Sync + Send {
+    /// Cursor type
+    ///
+    /// The `#[DataSource]` macro uses the opaque `Cursor` if it is not specified.
+    type CursorType: CursorType + Send + Sync;
+
     /// Record type
     type Element;
 
@@ -221,12 +275,12 @@ pub trait DataSource: Sync + Send {
         before: Option<Cursor>,
         first: Option<i32>,
         last: Option<i32>,
-    ) -> FieldResult<Connection<Self::Element, Self::EdgeFieldsObj>> {
+    ) -> FieldResult<Connection<Self::Element, Self::EdgeFieldsObj, Self::CursorType>> {
         let pagination = Pagination {
+            after: after.as_ref().map(cursor::decode_cursor).transpose()?,
+            before: before.as_ref().map(cursor::decode_cursor).transpose()?,
             first,
             last,
-            before,
-            after,
         };
 
         let operation = match pagination {
@@ -342,6 +396,6 @@ pub trait DataSource: Sync + Send {
     async fn query_operation(
         &self,
         ctx: &Context<'_>,
-        operation: &QueryOperation,
-    ) -> FieldResult<Connection<Self::Element, Self::EdgeFieldsObj>>;
+        operation: &QueryOperation<Self::CursorType>,
+    ) -> FieldResult<Connection<Self::Element, Self::EdgeFieldsObj, Self::CursorType>>;
 }
@@ -0,0 +1,65 @@
+use crate::Type;
+
+/// Names of the connection and edge types
+///
+/// By default, the types of a connection of `T` are named `TConnection` and `TEdge`. Implement this
+/// trait and use it as the `N` parameter of `Connection` to name them differently.
+///
+/// # Examples
+///
+/// ```rust
+/// use async_graphql::*;
+///
+/// struct MemberNames;
+///
+/// impl ConnectionNameType for MemberNames {
+///     fn connection_type_name<T: Type>() -> String {
+///         "MemberConnection".to_string()
+///     }
+///
+///     fn edge_type_name<T: Type>() -> String {
+///         "MemberEdge".to_string()
+///     }
+/// }
+///
+/// struct QueryRoot;
+///
+/// #[Object]
+/// impl QueryRoot {
+///     async fn members(&self, ctx: &Context<'_>) -> FieldResult<Connection<String, EmptyEdgeFields, Cursor, EmptyEdgeFields, MemberNames>> {
+///         let members = ["alice".to_string(), "bob".to_string()];
+///         let connection = members.as_ref().query(ctx, None, None, None, None).await?;
+///         Ok(connection.map(Clone::clone).with_name())
+///     }
+/// }
+///
+/// async_std::task::block_on(async move {
+///     let schema = Schema::new(QueryRoot, EmptyMutation, EmptySubscription);
+///     let res = schema.execute("{ members { __typename edges { __typename node } } }").await.unwrap().data;
+///     assert_eq!(res, serde_json::json!({
+///         "members": {
+///             "__typename": "MemberConnection",
+///             "edges": [
+///                 { "__typename": "MemberEdge", "node": "alice" },
+///                 { "__typename": "MemberEdge", "node": "bob" },
+///             ],
+///         }
+///     }));
+/// });
+/// ```
+pub trait ConnectionNameType: Send + Sync {
+    /// Returns the name of the connection type of `T`.
+    fn connection_type_name<T: Type>() -> String {
+        format!("{}Connection", T::type_name())
+    }
+
+    /// Returns the name of the edge type of `T`.
+    fn edge_type_name<T: Type>() -> String {
+        format!("{}Edge", T::type_name())
+    }
+}
+
+/// The default names of the connection and edge types, `TConnection` and `TEdge`
+pub struct DefaultConnectionName;
+
+impl ConnectionNameType for DefaultConnectionName {}
@@ -1,9 +1,39 @@
+use crate::types::connection::cursor::{decode_cursor, CursorType};
 use crate::types::connection::{EmptyEdgeFields, QueryOperation};
-use crate::{Connection, Context, DataSource, FieldResult};
-use byteorder::{ReadBytesExt, BE};
+use crate::{Connection, Context, Cursor, DataSource, FieldResult};
+use std::convert::TryInto;
+
+/// The index of an element of a slice.
+///
+/// The cursor keeps the format of the previous versions, the base64 encoded big-endian `u32`, but
+/// it is the index in the slice, while the previous versions used the index in the page.
+struct SliceIndex(usize);
+
+impl CursorType for SliceIndex {
+    type Error = &'static str;
+
+    fn decode_cursor(s: &str) -> std::result::Result<Self, Self::Error> {
+        let data = base64::decode(s).map_err(|_| "Invalid base64.")?;
+        let bytes = data.as_slice().try_into().map_err(|_| "Invalid index.")?;
+        Ok(SliceIndex(u32::from_be_bytes(bytes) as usize))
+    }
+
+    fn encode_cursor(&self) -> String {
+        base64::encode((self.0 as u32).to_be_bytes())
+    }
+}
+
+fn decode_index(cursor: &Cursor) -> FieldResult<usize> {
+    decode_cursor::<SliceIndex>(cursor).map(|index| index.0)
+}
+
+fn encode_index(idx: usize) -> Cursor {
+    SliceIndex(idx).encode_cursor().into()
+}
 
 #[async_trait::async_trait]
 impl<'a, T: Sync> DataSource for &'a [T] {
+    type CursorType = Cursor;
     type Element = &'a T;
     type EdgeFieldsObj = EmptyEdgeFields;
 
@@ -12,145 +42,30 @@ impl<'a, T: Sync> DataSource for &'a [T] {
         _ctx: &Context<'_>,
         operation: &QueryOperation,
     ) -> FieldResult<Connection<Self::Element, Self::EdgeFieldsObj>> {
-        let (start, end) = match operation {
-            QueryOperation::None => {
-                let start = 0;
-                let end = self.len();
-                (start, end)
-            }
-            QueryOperation::After { after } => {
-                let start = base64::decode(after.to_string())
-                    .ok()
-                    .and_then(|data| data.as_slice().read_u32::<BE>().ok())
-                    .map(|idx| (idx + 1) as usize)
-                    .unwrap_or(0);
-                let end = self.len();
-                (start, end)
-            }
-            QueryOperation::Before { before } => {
-                let end = base64::decode(before.to_string())
-                    .ok()
-                    .and_then(|data| data.as_slice().read_u32::<BE>().ok())
-                    .map(|idx| idx as usize)
-                    .unwrap_or_else(|| self.len());
-                let start = 0;
-                (start, end)
-            }
-            QueryOperation::Between { after, before } => {
-                let start = base64::decode(after.to_string())
-                    .ok()
-                    .and_then(|data| data.as_slice().read_u32::<BE>().ok())
-                    .map(|idx| (idx + 1) as usize)
-                    .unwrap_or(0);
-                let end = base64::decode(before.to_string())
-                    .ok()
-                    .and_then(|data| data.as_slice().read_u32::<BE>().ok())
-                    .map(|idx| idx as usize)
-                    .unwrap_or_else(|| self.len());
-                (start, end)
-            }
-            QueryOperation::First { limit } => {
-                let start = 0;
-                let end = (start + *limit).min(self.len());
-                (start, end)
-            }
-            QueryOperation::FirstAfter { after, limit } => {
-                let start = base64::decode(after.to_string())
-                    .ok()
-                    .and_then(|data| data.as_slice().read_u32::<BE>().ok())
-                    .map(|idx| (idx + 1) as usize)
-                    .unwrap_or(0);
-                let end = (start + *limit).min(self.len());
-                (start, end)
-            }
-            QueryOperation::FirstBefore { before, limit } => {
-                let end_cursor = base64::decode(before.to_string())
-                    .ok()
-                    .and_then(|data| data.as_slice().read_u32::<BE>().ok())
-                    .map(|idx| idx as usize)
-                    .unwrap_or_else(|| self.len());
-                let start = (end_cursor - *limit).max(0);
-                let end = (start + *limit).min(end_cursor);
-                (start, end)
-            }
-            QueryOperation::FirstBetween {
-                after,
-                before,
-                limit,
-            } => {
-                let start = base64::decode(after.to_string())
-                    .ok()
-                    .and_then(|data| data.as_slice().read_u32::<BE>().ok())
-                    .map(|idx| (idx + 1) as usize)
-                    .unwrap_or(0);
-                let end_cursor = base64::decode(before.to_string())
-                    .ok()
-                    .and_then(|data| data.as_slice().read_u32::<BE>().ok())
-                    .map(|idx| idx as usize)
-                    .unwrap_or_else(|| self.len());
-                let end = (start + *limit).min(end_cursor);
-                (start, end)
-            }
-            QueryOperation::Last { limit } => {
-                let end = self.len();
-                let start = (end - *limit).max(0);
-                (start, end)
-            }
-            QueryOperation::LastAfter { after, limit } => {
-                let end = self.len();
-                let start_cursor = base64::decode(after.to_string())
-                    .ok()
-                    .and_then(|data| data.as_slice().read_u32::<BE>().ok())
-                    .map(|idx| (idx + 1) as usize)
-                    .unwrap_or(0);
-                let start = (end - *limit).max(start_cursor);
-                (start, end)
-            }
-            QueryOperation::LastBefore { before, limit } => {
-                let end = base64::decode(before.to_string())
-                    .ok()
-                    .and_then(|data| data.as_slice().read_u32::<BE>().ok())
-                    .map(|idx| idx as usize)
-                    .unwrap_or_else(|| self.len());
-                let start = (end - *limit).max(0);
-                (start, end)
-            }
-            QueryOperation::LastBetween {
-                after,
-                before,
-                limit,
-            } => {
-                let start_cursor = base64::decode(after.to_string())
-                    .ok()
-                    .and_then(|data| data.as_slice().read_u32::<BE>().ok())
-                    .map(|idx| (idx + 1) as usize)
-                    .unwrap_or(0);
-                let end = base64::decode(before.to_string())
-                    .ok()
-                    .and_then(|data| data.as_slice().read_u32::<BE>().ok())
-                    .map(|idx| idx as usize)
-                    .unwrap_or_else(|| self.len());
-                let start = (end - *limit).max(start_cursor);
-                (start, end)
-            }
-            QueryOperation::Invalid => {
-                let start = 0;
-                let end = 0;
-                (start, end)
-            }
+        let (after, before, first, last) = match operation.split() {
+            Some(parts) => parts,
+            None => return Ok(Connection::new(None, false, false, Vec::new())),
         };
+        let after = after.map(decode_index).transpose()?;
+        let before = before.map(decode_index).transpose()?;
 
-        let mut nodes = Vec::with_capacity(end - start);
-        if nodes.capacity() != 0 {
-            for (idx, item) in self[start..end].iter().enumerate() {
-                nodes.push((
-                    base64::encode((idx as u32).to_be_bytes()).into(),
-                    EmptyEdgeFields,
-                    item,
-                ));
-            }
+        let mut start = after
+            .map(|after| after.saturating_add(1))
+            .unwrap_or(0)
+            .min(self.len());
+        let mut end = before.unwrap_or(self.len()).min(self.len()).max(start);
+        if let Some(first) = first {
+            end = start.saturating_add(first).min(end);
+        }
+        if let Some(last) = last {
+            start = end.saturating_sub(last).max(start);
         }
 
+        let nodes = self[start..end]
+            .iter()
+            .enumerate()
+            .map(|(idx, item)| (encode_index(start + idx), EmptyEdgeFields, item))
+            .collect();
         Ok(Connection::new(None, start > 0, end < self.len(), nodes))
     }
 }
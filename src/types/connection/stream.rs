@@ -0,0 +1,228 @@
+use crate::types::connection::{EmptyEdgeFields, QueryOperation};
+use crate::{Connection, Context, DataSource, FieldResult};
+use futures::future::BoxFuture;
+use futures::{Future, FutureExt, Stream, StreamExt};
+use std::collections::VecDeque;
+
+type TotalCountFn = Box<dyn Fn() -> BoxFuture<'static, FieldResult<usize>> + Send + Sync>;
+
+/// A data source that pages a `Stream` lazily
+///
+/// The stream is created for every query with the factory, the items before the page are skipped
+/// and the stream is not polled after the page, except for one more item that tells whether there
+/// is a next page. Queries with `last` and no `before` have to read the whole stream, but only keep
+/// `last` items in memory.
+///
+/// The cursors are the positions of the items in the stream.
+///
+/// # Examples
+///
+/// ```rust
+/// use async_graphql::*;
+///
+/// struct QueryRoot;
+///
+/// #[Object]
+/// impl QueryRoot {
+///     async fn numbers(&self, ctx: &Context<'_>,
+///         after: Option<Cursor>,
+///         before: Option<Cursor>,
+///         first: Option<i32>,
+///         last: Option<i32>
+///     ) -> FieldResult<Connection<i32, EmptyEdgeFields, usize>> {
+///         StreamDataSource::new(|| futures::stream::iter(0..1000))
+///             .total_count(|| async { Ok(1000) })
+///             .query(ctx, after, before, first, last)
+///             .await
+///     }
+/// }
+///
+/// async_std::task::block_on(async move {
+///     let schema = Schema::new(QueryRoot, EmptyMutation, EmptySubscription);
+///     let res = schema.execute(r#"{ numbers(first: 2, after: "9") { totalCount edges { node } } }"#).await.unwrap().data;
+///     assert_eq!(res, serde_json::json!({
+///         "numbers": {
+///             "totalCount": 1000,
+///             "edges": [{ "node": 10 }, { "node": 11 }],
+///         }
+///     }));
+/// });
+/// ```
+pub struct StreamDataSource<F> {
+    factory: F,
+    total_count: Option<TotalCountFn>,
+}
+
+impl<F> StreamDataSource<F> {
+    /// Create a data source with a function that creates the stream.
+    pub fn new(factory: F) -> Self {
+        Self {
+            factory,
+            total_count: None,
+        }
+    }
+
+    /// Set a function that counts all items, it is only called if the client selects `totalCount`.
+    pub fn total_count<T, R>(mut self, f: T) -> Self
+    where
+        T: Fn() -> R + Send + Sync + 'static,
+        R: Future<Output = FieldResult<usize>> + Send + 'static,
+    {
+        self.total_count = Some(Box::new(move || f().boxed()));
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl<F, S> DataSource for StreamDataSource<F>
+where
+    F: Fn() -> S + Send + Sync,
+    S: Stream + Send,
+    S::Item: Send,
+{
+    type CursorType = usize;
+    type Element = S::Item;
+    type EdgeFieldsObj = EmptyEdgeFields;
+
+    async fn query_operation(
+        &self,
+        ctx: &Context<'_>,
+        operation: &QueryOperation<usize>,
+    ) -> FieldResult<Connection<Self::Element, Self::EdgeFieldsObj, usize>> {
+        let mut connection = query_stream((self.factory)(), operation).await;
+        if let Some(total_count) = &self.total_count {
+            if ctx.look_ahead().is_selected("totalCount") {
+                connection.total_count = Some(total_count().await?);
+            }
+        }
+        Ok(connection)
+    }
+}
+
+/// A data source that pages an `Iterator` lazily
+///
+/// It works like `StreamDataSource`, the iterator is created for every query with the factory.
+///
+/// # Examples
+///
+/// ```rust
+/// use async_graphql::*;
+///
+/// struct QueryRoot;
+///
+/// #[Object]
+/// impl QueryRoot {
+///     async fn numbers(&self, ctx: &Context<'_>,
+///         after: Option<Cursor>,
+///         before: Option<Cursor>,
+///         first: Option<i32>,
+///         last: Option<i32>
+///     ) -> FieldResult<Connection<i32, EmptyEdgeFields, usize>> {
+///         IterDataSource::new(|| 0..1000)
+///             .query(ctx, after, before, first, last)
+///             .await
+///     }
+/// }
+///
+/// async_std::task::block_on(async move {
+///     let schema = Schema::new(QueryRoot, EmptyMutation, EmptySubscription);
+///     let res = schema.execute(r#"{ numbers(last: 2) { edges { node } } }"#).await.unwrap().data;
+///     assert_eq!(res, serde_json::json!({
+///         "numbers": {
+///             "edges": [{ "node": 998 }, { "node": 999 }],
+///         }
+///     }));
+/// });
+/// ```
+pub struct IterDataSource<F> {
+    factory: F,
+    total_count: Option<TotalCountFn>,
+}
+
+impl<F> IterDataSource<F> {
+    /// Create a data source with a function that creates the iterator.
+    pub fn new(factory: F) -> Self {
+        Self {
+            factory,
+            total_count: None,
+        }
+    }
+
+    /// Set a function that counts all items, it is only called if the client selects `totalCount`.
+    pub fn total_count<T, R>(mut self, f: T) -> Self
+    where
+        T: Fn() -> R + Send + Sync + 'static,
+        R: Future<Output = FieldResult<usize>> + Send + 'static,
+    {
+        self.total_count = Some(Box::new(move || f().boxed()));
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl<F, I> DataSource for IterDataSource<F>
+where
+    F: Fn() -> I + Send + Sync,
+    I: IntoIterator,
+    I::IntoIter: Send,
+    I::Item: Send,
+{
+    type CursorType = usize;
+    type Element = I::Item;
+    type EdgeFieldsObj = EmptyEdgeFields;
+
+    async fn query_operation(
+        &self,
+        ctx: &Context<'_>,
+        operation: &QueryOperation<usize>,
+    ) -> FieldResult<Connection<Self::Element, Self::EdgeFieldsObj, usize>> {
+        let stream = futures::stream::iter((self.factory)());
+        let mut connection = query_stream(stream, operation).await;
+        if let Some(total_count) = &self.total_count {
+            if ctx.look_ahead().is_selected("totalCount") {
+                connection.total_count = Some(total_count().await?);
+            }
+        }
+        Ok(connection)
+    }
+}
+
+async fn query_stream<S: Stream>(
+    stream: S,
+    operation: &QueryOperation<usize>,
+) -> Connection<S::Item, EmptyEdgeFields, usize> {
+    let (after, before, first, last) = match operation.split() {
+        Some(parts) => parts,
+        None => return Connection::new(None, false, false, Vec::new()),
+    };
+
+    let start = after.map(|after| after.saturating_add(1)).unwrap_or(0);
+    let end = match (before, first) {
+        (Some(before), Some(first)) => Some((*before).min(start.saturating_add(first))),
+        (Some(before), None) => Some(*before),
+        (None, Some(first)) => Some(start.saturating_add(first)),
+        (None, None) => None,
+    };
+
+    let stream = stream.skip(start);
+    futures::pin_mut!(stream);
+
+    let mut nodes = VecDeque::new();
+    let mut pos = start;
+    let mut has_previous_page = start > 0;
+    let mut has_next_page = false;
+    while let Some(item) = stream.next().await {
+        if end.map(|end| pos >= end).unwrap_or_default() {
+            has_next_page = true;
+            break;
+        }
+        nodes.push_back((pos, EmptyEdgeFields, item));
+        if last.map(|last| nodes.len() > last).unwrap_or_default() {
+            nodes.pop_front();
+            has_previous_page = true;
+        }
+        pos += 1;
+    }
+
+    Connection::new(None, has_previous_page, has_next_page, nodes.into())
+}
@@ -1,7 +1,7 @@
 use crate::context::Environment;
 use crate::{
-    registry, Context, ContextSelectionSet, Error, ObjectType, OutputValueType, QueryError, Result,
-    Schema, SubscriptionType, Type,
+    registry, Context, ContextSelectionSet, Error, ObjectType, OutputValueType, QueryError,
+    QueryResponse, Result, Schema, SubscriptionType, Type,
 };
 use futures::Stream;
 use graphql_parser::Pos;
@@ -42,7 +42,7 @@ impl SubscriptionType for EmptySubscription {
         _ctx: &Context<'_>,
         _schema: &Schema<Query, Mutation, Self>,
         _environment: Arc<Environment>,
-    ) -> Result<Pin<Box<dyn Stream<Item = Result<serde_json::Value>> + Send>>>
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<QueryResponse>> + Send>>>
     where
         Query: ObjectType + Send + Sync + 'static,
         Mutation: ObjectType + Send + Sync + 'static,
@@ -3,13 +3,21 @@ mod empty_mutation;
 mod empty_subscription;
 mod r#enum;
 mod list;
+mod node;
 mod optional;
 mod query_root;
 mod upload;
 
-pub use connection::{Connection, Cursor, DataSource, EmptyEdgeFields, PageInfo, QueryOperation};
+pub use connection::{
+    Connection, ConnectionNameType, Cursor, CursorType, DataSource, DefaultConnectionName,
+    EmptyEdgeFields, IterDataSource, KeysetDirection, KeysetQuery, PageInfo, QueryOperation,
+    StreamDataSource, TupleCursorError,
+};
 pub use empty_mutation::EmptyMutation;
 pub use empty_subscription::EmptySubscription;
+pub(crate) use node::decode_global_id;
+pub use node::GlobalId;
 pub use query_root::QueryRoot;
 pub use r#enum::{EnumItem, EnumType};
-pub use upload::Upload;
+pub(crate) use upload::upload_value;
+pub use upload::{Upload, UploadValue};
@@ -0,0 +1,130 @@
+use crate::{
+    registry, ContextSelectionSet, InputValueType, OutputValueType, Result, Type, Value, ID,
+};
+use graphql_parser::Pos;
+use std::borrow::Cow;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::marker::PhantomData;
+
+/// Typed global object identifier for the Relay `Node` interface
+///
+/// The output is the `ID` of the object prefixed with the name of the type `T` and encoded with
+/// base64, so it is unique among all types. As an input, it only accepts global ids of the type `T`.
+///
+/// # Examples
+///
+/// ```rust
+/// use async_graphql::*;
+///
+/// #[SimpleObject]
+/// struct User {
+///     id: GlobalId<User>,
+///     name: String,
+/// }
+///
+/// struct QueryRoot;
+///
+/// #[Object]
+/// impl QueryRoot {
+///     #[node]
+///     async fn find_user(&self, id: ID) -> Option<User> {
+///         Some(User {
+///             id: GlobalId::new(id),
+///             name: "jack".to_string(),
+///         })
+///     }
+/// }
+///
+/// async_std::task::block_on(async move {
+///     let schema = Schema::new(QueryRoot, EmptyMutation, EmptySubscription);
+///     let id = User::global_id("1".into());
+///     let query = format!(r#"{{ node(id: "{}") {{ id ... on User {{ name }} }} }}"#, id.as_str());
+///     let res = schema.execute(&query).await.unwrap().data;
+///     assert_eq!(res, serde_json::json!({
+///         "node": { "id": id.as_str(), "name": "jack" },
+///     }));
+/// });
+/// ```
+pub struct GlobalId<T> {
+    id: ID,
+    _mark: PhantomData<T>,
+}
+
+impl<T> GlobalId<T> {
+    /// Create a global id from the id of the object.
+    pub fn new(id: impl Into<ID>) -> Self {
+        Self {
+            id: id.into(),
+            _mark: PhantomData,
+        }
+    }
+
+    /// Returns the id of the object.
+    pub fn id(&self) -> &ID {
+        &self.id
+    }
+
+    /// Consumes the global id and returns the id of the object.
+    pub fn into_inner(self) -> ID {
+        self.id
+    }
+}
+
+impl<T> Clone for GlobalId<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.id.clone())
+    }
+}
+
+impl<T> PartialEq for GlobalId<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for GlobalId<T> {}
+
+impl<T> Debug for GlobalId<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("GlobalId").field(&self.id).finish()
+    }
+}
+
+impl<T> Display for GlobalId<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.id, f)
+    }
+}
+
+impl<T: Type> Type for GlobalId<T> {
+    fn type_name() -> Cow<'static, str> {
+        ID::type_name()
+    }
+
+    fn create_type_info(registry: &mut registry::Registry) -> String {
+        ID::create_type_info(registry)
+    }
+}
+
+impl<T: Type> InputValueType for GlobalId<T> {
+    fn parse(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(s) => T::parse_global_id(s.clone().into()).map(Self::new),
+            _ => None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: Type + Send + Sync> OutputValueType for GlobalId<T> {
+    async fn resolve(&self, _: &ContextSelectionSet<'_>, _pos: Pos) -> Result<serde_json::Value> {
+        Ok(T::global_id(self.id.clone()).to_string().into())
+    }
+}
+
+/// Decode a global id to the type name and the id of the object.
+pub(crate) fn decode_global_id(id: &str) -> Option<(String, ID)> {
+    let s = String::from_utf8(base64::decode(id).ok()?).ok()?;
+    let idx = s.find(':')?;
+    Some((s[..idx].to_string(), s[idx + 1..].to_string().into()))
+}
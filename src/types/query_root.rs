@@ -1,8 +1,9 @@
 use crate::model::{__Schema, __Type};
 use crate::scalars::Any;
+use crate::types::decode_global_id;
 use crate::{
     do_resolve, registry, Context, ContextSelectionSet, Error, ObjectType, OutputValueType,
-    QueryError, Result, Type, Value,
+    QueryError, Result, Type, Value, ID,
 };
 use async_graphql_derive::SimpleObject;
 use graphql_parser::Pos;
@@ -78,6 +79,19 @@ impl<T: Type> Type for QueryRoot<T> {
     }
 }
 
+impl<T: ObjectType + Send + Sync> QueryRoot<T> {
+    async fn find_node(&self, ctx: &Context<'_>, id: ID) -> Result<serde_json::Value> {
+        match decode_global_id(&id) {
+            Some((type_name, id)) if ctx.registry.node_types.contains(&type_name) => {
+                self.inner
+                    .find_node(ctx, ctx.position, &type_name, id)
+                    .await
+            }
+            _ => Ok(serde_json::Value::Null),
+        }
+    }
+}
+
 #[async_trait::async_trait]
 impl<T: ObjectType + Send + Sync> ObjectType for QueryRoot<T> {
     async fn resolve_field(&self, ctx: &Context<'_>) -> Result<serde_json::Value> {
@@ -122,6 +136,21 @@ impl<T: ObjectType + Send + Sync> ObjectType for QueryRoot<T> {
                 res.push(self.inner.find_entity(ctx, ctx.position, &item.0).await?);
             }
             return Ok(res.into());
+        } else if ctx.name.as_str() == "node" && !ctx.registry.node_types.is_empty() {
+            let id: ID = ctx.param_value("id", ctx.position, || Value::Null)?;
+            return self.find_node(ctx, id).await;
+        } else if ctx.name.as_str() == "nodes" && !ctx.registry.node_types.is_empty() {
+            let ids: Vec<ID> = ctx.param_value("ids", ctx.position, || Value::Null)?;
+            let mut res = Vec::new();
+            for id in ids {
+                // like Relay, a node that fails to load is `null` instead of failing the list
+                res.push(
+                    self.find_node(ctx, id)
+                        .await
+                        .unwrap_or(serde_json::Value::Null),
+                );
+            }
+            return Ok(res.into());
         } else if ctx.name.as_str() == "_service" {
             let ctx_obj = ctx.with_selection_set(&ctx.selection_set);
             return OutputValueType::resolve(
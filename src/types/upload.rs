@@ -1,6 +1,35 @@
-use crate::{registry, InputValueType, Type, Value};
+use crate::http::{UploadContent, UploadReader};
+use crate::{registry, Context, InputValueType, Type, Value};
 use std::borrow::Cow;
-use std::path::PathBuf;
+use std::io::{Error, ErrorKind, Result};
+use std::sync::Arc;
+
+/// The variable at `var_path` is set to a reference to itself, which JSON variables can't contain. `Upload::value`
+/// finds the file by the path in the uploads of the query, so only the files set with `QueryBuilder::set_upload` can
+/// be read.
+pub(crate) fn upload_value(var_path: &str) -> Value {
+    Value::Variable(var_path.to_string())
+}
+
+/// An uploaded file of the request
+pub struct UploadValue {
+    /// Filename
+    pub filename: String,
+
+    /// Content type, such as `application/json`, `image/jpg` ...
+    pub content_type: Option<String>,
+
+    pub(crate) content: Arc<dyn UploadContent>,
+}
+
+impl UploadValue {
+    /// Open a reader of the content of the file.
+    ///
+    /// The content is kept by the `UploadStorage` of the request, it can be read more than once.
+    pub fn reader(&self) -> Result<UploadReader> {
+        self.content.reader()
+    }
+}
 
 /// Uploaded file
 ///
@@ -11,20 +40,26 @@ use std::path::PathBuf;
 /// Enable this feature by accepting an argument of type `Upload` (single file) or
 /// `Vec<Upload>` (multiple files) in your mutation like in the example blow.
 ///
+/// `Upload` is a handle of a file of the multipart request, the file is read with `Upload::value`. The handles are
+/// created by the multipart parser only, a client can't send one as a JSON variable.
 ///
 /// # Example
 /// *[Full Example](<https://github.com/async-graphql/examples/blob/master/models/files/src/lib.rs>)*
 ///
 /// ```
-/// use async_graphql::Upload;
+/// use async_graphql::*;
+/// use futures::AsyncReadExt;
 ///
 /// struct MutationRoot;
 ///
-/// #[async_graphql::Object]
+/// #[Object]
 /// impl MutationRoot {
-///     async fn upload(&self, file: Upload) -> bool {
-///         println!("upload: filename={}", file.filename);
-///         true
+///     async fn upload(&self, ctx: &Context<'_>, file: Upload) -> FieldResult<i32> {
+///         let file = file.value(ctx)?;
+///         let mut content = Vec::new();
+///         file.reader()?.read_to_end(&mut content).await?;
+///         println!("upload: filename={} size={}", file.filename, content.len());
+///         Ok(content.len() as i32)
 ///     }
 /// }
 ///
@@ -41,18 +76,20 @@ use std::path::PathBuf;
 /// --form 'map={ "0": ["variables.file"] }' \
 /// --form '0=@myFile.txt'
 /// ```
-pub struct Upload {
-    /// Filename
-    pub filename: String,
+pub struct Upload(String);
 
-    /// Content type, such as `application/json`, `image/jpg` ...
-    pub content_type: Option<String>,
-
-    /// Temporary file path
-    pub path: PathBuf,
+impl Upload {
+    /// Returns the uploaded file.
+    pub fn value<'a>(&self, ctx: &Context<'a>) -> Result<&'a UploadValue> {
+        ctx.uploads
+            .iter()
+            .find(|(var_path, _)| *var_path == self.0)
+            .map(|(_, value)| value)
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "The file isn't uploaded"))
+    }
 }
 
-impl<'a> Type for Upload {
+impl Type for Upload {
     fn type_name() -> Cow<'static, str> {
         Cow::Borrowed("Upload")
     }
@@ -61,40 +98,16 @@ impl<'a> Type for Upload {
         registry.create_type::<Self, _>(|_| registry::Type::Scalar {
             name: Self::type_name().to_string(),
             description: None,
-            is_valid: |value| match value {
-                Value::String(s) => s.starts_with("file:"),
-                _ => false,
-            },
+            is_valid: |value| matches!(value, Value::Variable(_)),
         })
     }
 }
 
-impl<'a> InputValueType for Upload {
+impl InputValueType for Upload {
     fn parse(value: &Value) -> Option<Self> {
-        if let Value::String(s) = value {
-            if s.starts_with("file:") {
-                let s = &s[5..];
-                if let Some(idx) = s.find('|') {
-                    let name_and_type = &s[..idx];
-                    let path = &s[idx + 1..];
-                    if let Some(type_idx) = name_and_type.find(':') {
-                        let name = &name_and_type[..type_idx];
-                        let mime_type = &name_and_type[type_idx + 1..];
-                        return Some(Self {
-                            filename: name.to_string(),
-                            content_type: Some(mime_type.to_string()),
-                            path: PathBuf::from(path),
-                        });
-                    } else {
-                        return Some(Self {
-                            filename: name_and_type.to_string(),
-                            content_type: None,
-                            path: PathBuf::from(path),
-                        });
-                    }
-                }
-            }
+        match value {
+            Value::Variable(var_path) => Some(Upload(var_path.clone())),
+            _ => None,
         }
-        None
     }
 }
@@ -0,0 +1,158 @@
+use async_graphql::*;
+use futures::{Stream, StreamExt};
+
+#[async_std::test]
+pub async fn test_memory_broker_topics() {
+    let broker = MemoryBroker::<i32>::new(16);
+    let mut order1 = broker.subscribe("order:1").await.unwrap();
+    let mut orders = broker.subscribe("order:*").await.unwrap();
+
+    broker.publish("order:1", 1).await.unwrap();
+    broker.publish("order:2", 2).await.unwrap();
+    broker.publish("user:1", 3).await.unwrap();
+
+    assert_eq!(order1.next().await.unwrap().unwrap(), 1);
+    assert_eq!(orders.next().await.unwrap().unwrap(), 1);
+    assert_eq!(orders.next().await.unwrap().unwrap(), 2);
+
+    drop(order1);
+    broker.publish("order:1", 4).await.unwrap();
+    assert_eq!(orders.next().await.unwrap().unwrap(), 4);
+}
+
+#[async_std::test]
+pub async fn test_memory_broker_overflow() {
+    let broker = MemoryBroker::<i32>::new(2);
+    let mut stream = broker.subscribe("a").await.unwrap();
+    for i in 0..4 {
+        broker.publish("a", i).await.unwrap();
+    }
+    assert_eq!(stream.next().await.unwrap().unwrap(), 2);
+    assert_eq!(stream.next().await.unwrap().unwrap(), 3);
+
+    let broker = MemoryBroker::<i32>::new(2).overflow_policy(OverflowPolicy::Terminate);
+    let mut stream = broker.subscribe("a").await.unwrap();
+    for i in 0..5 {
+        broker.publish("a", i).await.unwrap();
+    }
+    assert_eq!(stream.next().await.unwrap().unwrap(), 0);
+    assert_eq!(stream.next().await.unwrap().unwrap(), 1);
+    assert_eq!(
+        stream.next().await.unwrap().unwrap_err().0,
+        "The subscriber lagged behind and missed 3 messages"
+    );
+    broker.publish("a", 5).await.unwrap();
+    assert!(stream.next().await.is_none());
+}
+
+#[async_std::test]
+pub async fn test_broker_subscription() {
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {}
+
+    struct SubscriptionRoot;
+
+    #[Subscription]
+    impl SubscriptionRoot {
+        async fn order(
+            &self,
+            ctx: &Context<'_>,
+            id: i32,
+        ) -> FieldResult<impl Stream<Item = FieldResult<i32>>> {
+            ctx.data::<MemoryBroker<i32>>()
+                .subscribe(&format!("order:{}", id))
+                .await
+        }
+    }
+
+    let broker = MemoryBroker::<i32>::new(16);
+    let schema = Schema::build(QueryRoot, EmptyMutation, SubscriptionRoot)
+        .data(broker.clone())
+        .finish();
+
+    let mut stream = schema
+        .create_subscription_stream(
+            "subscription { order(id: 1) }",
+            None,
+            Default::default(),
+            None,
+        )
+        .await
+        .unwrap()
+        .map(|res| res.map(|resp| resp.data));
+
+    broker.publish("order:2", 20).await.unwrap();
+    broker.publish("order:1", 10).await.unwrap();
+    assert_eq!(
+        stream.next().await,
+        Some(Ok(serde_json::json!({ "order": 10 })))
+    );
+}
+
+#[async_std::test]
+pub async fn test_broker_terminated_subscription() {
+    use futures::{FutureExt, SinkExt};
+
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {}
+
+    struct SubscriptionRoot;
+
+    #[Subscription]
+    impl SubscriptionRoot {
+        async fn values(
+            &self,
+            ctx: &Context<'_>,
+        ) -> FieldResult<impl Stream<Item = FieldResult<i32>>> {
+            ctx.data::<MemoryBroker<i32>>().subscribe("values").await
+        }
+    }
+
+    let broker = MemoryBroker::<i32>::new(2).overflow_policy(OverflowPolicy::Terminate);
+    let schema = Schema::build(QueryRoot, EmptyMutation, SubscriptionRoot)
+        .data(broker.clone())
+        .finish();
+    let (mut sink, mut stream) = schema.subscription_connection(WebSocketTransport::default());
+
+    for msg in &[
+        serde_json::json!({ "type": "connection_init" }),
+        serde_json::json!({
+            "type": "start",
+            "id": "1",
+            "payload": { "query": "subscription { values }" },
+        }),
+    ] {
+        sink.send(serde_json::to_vec(msg).unwrap().into())
+            .await
+            .unwrap();
+    }
+    assert_eq!(
+        serde_json::json!({ "type": "connection_ack" }),
+        serde_json::from_slice::<serde_json::Value>(&stream.next().await.unwrap()).unwrap()
+    );
+    assert!(stream.next().now_or_never().is_none());
+
+    // the lag ends the subscription with an error after the buffered messages
+    for i in 0..5 {
+        broker.publish("values", i).await.unwrap();
+    }
+    for i in 0..2 {
+        let msg: serde_json::Value = serde_json::from_slice(&stream.next().await.unwrap()).unwrap();
+        assert_eq!(msg["type"], "data");
+        assert_eq!(msg["payload"]["data"]["values"], i);
+    }
+    let msg: serde_json::Value = serde_json::from_slice(&stream.next().await.unwrap()).unwrap();
+    assert_eq!(msg["type"], "error");
+    assert_eq!(msg["id"], "1");
+    assert_eq!(
+        msg["payload"][0]["message"],
+        "The subscriber lagged behind and missed 3 messages"
+    );
+
+    broker.publish("values", 5).await.unwrap();
+    assert!(stream.next().now_or_never().is_none());
+}
@@ -0,0 +1,600 @@
+use async_graphql::*;
+use chrono::{DateTime, TimeZone, Utc};
+
+struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    async fn numbers(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<Cursor>,
+        before: Option<Cursor>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> FieldResult<Connection<&i32>> {
+        const NUMBERS: &[i32] = &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+        NUMBERS.query(ctx, after, before, first, last).await
+    }
+}
+
+async fn query_numbers<Q: ObjectType + Send + Sync + 'static>(
+    schema: &Schema<Q, EmptyMutation, EmptySubscription>,
+    args: &str,
+) -> serde_json::Value {
+    let query = format!(
+        "{{ numbers({}) {{ edges {{ node cursor }} pageInfo {{ hasPreviousPage hasNextPage }} }} }}",
+        args
+    );
+    schema.execute(&query).await.unwrap().data
+}
+
+fn page(
+    nodes: std::ops::Range<i32>,
+    has_previous_page: bool,
+    has_next_page: bool,
+) -> serde_json::Value {
+    page_with_cursors(nodes, has_previous_page, has_next_page, |n| n.to_string())
+}
+
+/// The cursors of the slices are the base64 encoded big-endian `u32` indexes.
+fn slice_cursor(n: i32) -> String {
+    base64::encode((n as u32).to_be_bytes())
+}
+
+fn page_with_cursors(
+    nodes: std::ops::Range<i32>,
+    has_previous_page: bool,
+    has_next_page: bool,
+    cursor: fn(i32) -> String,
+) -> serde_json::Value {
+    serde_json::json!({
+        "numbers": {
+            "edges": nodes.map(|n| serde_json::json!({ "node": n, "cursor": cursor(n) })).collect::<Vec<_>>(),
+            "pageInfo": {
+                "hasPreviousPage": has_previous_page,
+                "hasNextPage": has_next_page,
+            },
+        }
+    })
+}
+
+#[async_std::test]
+pub async fn test_slice_datasource() {
+    let schema = Schema::new(QueryRoot, EmptyMutation, EmptySubscription);
+
+    // the cursors keep the format of the previous versions
+    assert_eq!(
+        query_numbers(&schema, r#"first: 1, after: "AAAAAg==""#).await,
+        page_with_cursors(3..4, true, true, slice_cursor)
+    );
+
+    assert_eq!(
+        query_numbers(&schema, "first: 3").await,
+        page_with_cursors(0..3, false, true, slice_cursor)
+    );
+    assert_eq!(
+        query_numbers(
+            &schema,
+            &format!(r#"first: 3, after: "{}""#, slice_cursor(2))
+        )
+        .await,
+        page_with_cursors(3..6, true, true, slice_cursor)
+    );
+    assert_eq!(
+        query_numbers(
+            &schema,
+            &format!(r#"first: 3, after: "{}""#, slice_cursor(8))
+        )
+        .await,
+        page_with_cursors(9..10, true, false, slice_cursor)
+    );
+    assert_eq!(
+        query_numbers(&schema, "last: 3").await,
+        page_with_cursors(7..10, true, false, slice_cursor)
+    );
+    assert_eq!(
+        query_numbers(
+            &schema,
+            &format!(r#"last: 3, before: "{}""#, slice_cursor(2))
+        )
+        .await,
+        page_with_cursors(0..2, false, true, slice_cursor)
+    );
+    assert_eq!(
+        query_numbers(
+            &schema,
+            &format!(r#"first: 5, before: "{}""#, slice_cursor(2))
+        )
+        .await,
+        page_with_cursors(0..2, false, true, slice_cursor)
+    );
+    assert_eq!(
+        query_numbers(
+            &schema,
+            &format!(r#"last: 20, after: "{}""#, slice_cursor(5))
+        )
+        .await,
+        page_with_cursors(6..10, true, false, slice_cursor)
+    );
+    assert_eq!(
+        query_numbers(
+            &schema,
+            &format!(
+                r#"after: "{}", before: "{}""#,
+                slice_cursor(3),
+                slice_cursor(6)
+            )
+        )
+        .await,
+        page_with_cursors(4..6, true, true, slice_cursor)
+    );
+    assert_eq!(
+        query_numbers(&schema, &format!(r#"after: "{}""#, slice_cursor(30))).await,
+        page_with_cursors(0..0, true, false, slice_cursor)
+    );
+}
+
+#[async_std::test]
+pub async fn test_invalid_cursor() {
+    let schema = Schema::new(QueryRoot, EmptyMutation, EmptySubscription);
+    let err = schema
+        .execute(r#"{ numbers(first: 2, after: "abc") { edges { node } } }"#)
+        .await
+        .unwrap_err();
+    match err {
+        Error::Query {
+            err: QueryError::FieldError { err, .. },
+            ..
+        } => assert!(err.starts_with("Invalid cursor \"abc\"")),
+        _ => panic!("unexpected error"),
+    }
+}
+
+#[async_std::test]
+pub async fn test_opaque_cursor() {
+    struct Letters;
+
+    #[DataSource]
+    impl DataSource for Letters {
+        type Element = String;
+        type EdgeFieldsObj = EmptyEdgeFields;
+
+        async fn query_operation(
+            &self,
+            _ctx: &Context<'_>,
+            operation: &QueryOperation,
+        ) -> FieldResult<Connection<String, EmptyEdgeFields>> {
+            let start = match operation {
+                QueryOperation::After { after } => after.as_bytes()[0] + 1,
+                _ => b'a',
+            };
+            let nodes = (start..=b'c')
+                .map(|c| {
+                    let s = (c as char).to_string();
+                    (s.clone().into(), EmptyEdgeFields, s)
+                })
+                .collect();
+            Ok(Connection::new(None, false, false, nodes))
+        }
+    }
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        async fn letters(
+            &self,
+            ctx: &Context<'_>,
+            after: Option<Cursor>,
+        ) -> FieldResult<Connection<String, EmptyEdgeFields>> {
+            Letters.query(ctx, after, None, None, None).await
+        }
+    }
+
+    let schema = Schema::new(Query, EmptyMutation, EmptySubscription);
+    assert_eq!(
+        schema
+            .execute(r#"{ letters(after: "a") { edges { node cursor } } }"#)
+            .await
+            .unwrap()
+            .data,
+        serde_json::json!({
+            "letters": {
+                "edges": [
+                    { "node": "b", "cursor": "b" },
+                    { "node": "c", "cursor": "c" },
+                ]
+            }
+        })
+    );
+}
+
+#[test]
+pub fn test_cursor_types() {
+    assert_eq!(10usize.encode_cursor(), "10");
+    assert_eq!(usize::decode_cursor("10").unwrap(), 10);
+    assert!(usize::decode_cursor("-1").is_err());
+    assert_eq!(i64::decode_cursor("-1").unwrap(), -1);
+
+    let time = Utc.ymd(2020, 4, 1).and_hms(10, 20, 30);
+    assert_eq!(
+        DateTime::<Utc>::decode_cursor(&time.encode_cursor()).unwrap(),
+        time
+    );
+
+    let key = (time, 10i64, "a:b".to_string());
+    assert_eq!(
+        <(DateTime<Utc>, i64, String)>::decode_cursor(&key.encode_cursor()).unwrap(),
+        key
+    );
+    assert!(<(i64, i64)>::decode_cursor("MQ==").is_err());
+    assert!(<(i64, i64)>::decode_cursor("MQ==:YQ==").is_err());
+    assert!(<(i64, i64)>::decode_cursor("MQ==:Mg==:Mw==").is_err());
+    assert_eq!(<(i64, i64)>::decode_cursor("MQ==:Mg==").unwrap(), (1, 2));
+}
+
+#[test]
+pub fn test_keyset_query() {
+    let query = KeysetQuery::new(&QueryOperation::FirstAfter {
+        limit: 2,
+        after: 3i64,
+    })
+    .unwrap();
+    assert_eq!(
+        query,
+        KeysetQuery {
+            after_key: Some(3),
+            before_key: None,
+            limit: Some(2),
+            direction: KeysetDirection::Forward,
+        }
+    );
+    assert_eq!(query.fetch_limit(), Some(3));
+
+    let conn = query.into_connection(vec![
+        (4, EmptyEdgeFields, 4),
+        (5, EmptyEdgeFields, 5),
+        (6, EmptyEdgeFields, 6),
+    ]);
+    assert_eq!(
+        conn.nodes.iter().map(|(_, _, n)| *n).collect::<Vec<_>>(),
+        vec![4, 5]
+    );
+    assert!(conn.page_info.has_previous_page);
+    assert!(conn.page_info.has_next_page);
+    assert_eq!(
+        conn.page_info.end_cursor.as_deref().map(String::as_str),
+        Some("5")
+    );
+
+    let query = KeysetQuery::new(&QueryOperation::Last { limit: 2 }).unwrap();
+    assert_eq!(query.direction, KeysetDirection::Backward);
+    let conn = query.into_connection(vec![(1i64, EmptyEdgeFields, 1), (0, EmptyEdgeFields, 0)]);
+    assert_eq!(
+        conn.nodes.iter().map(|(_, _, n)| *n).collect::<Vec<_>>(),
+        vec![0, 1]
+    );
+    assert!(!conn.page_info.has_previous_page);
+    assert!(!conn.page_info.has_next_page);
+
+    let query = KeysetQuery::new(&QueryOperation::LastBefore {
+        limit: 1,
+        before: 5i64,
+    })
+    .unwrap();
+    let conn = query.into_connection(vec![(4, EmptyEdgeFields, 4), (3, EmptyEdgeFields, 3)]);
+    assert_eq!(
+        conn.nodes.iter().map(|(_, _, n)| *n).collect::<Vec<_>>(),
+        vec![4]
+    );
+    assert!(conn.page_info.has_previous_page);
+    assert!(conn.page_info.has_next_page);
+
+    assert!(KeysetQuery::<i64>::new(&QueryOperation::Invalid).is_err());
+}
+
+#[async_std::test]
+pub async fn test_stream_datasource() {
+    use futures::StreamExt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    struct Query {
+        polled: Arc<AtomicUsize>,
+        counted: Arc<AtomicUsize>,
+    }
+
+    #[Object]
+    impl Query {
+        async fn numbers(
+            &self,
+            ctx: &Context<'_>,
+            after: Option<Cursor>,
+            before: Option<Cursor>,
+            first: Option<i32>,
+            last: Option<i32>,
+        ) -> FieldResult<Connection<i32, EmptyEdgeFields, usize>> {
+            let polled = self.polled.clone();
+            let counted = self.counted.clone();
+            StreamDataSource::new(move || {
+                let polled = polled.clone();
+                futures::stream::iter(0..10).inspect(move |_| {
+                    polled.fetch_add(1, Ordering::SeqCst);
+                })
+            })
+            .total_count(move || {
+                counted.fetch_add(1, Ordering::SeqCst);
+                async { Ok(10) }
+            })
+            .query(ctx, after, before, first, last)
+            .await
+        }
+
+        async fn letters(
+            &self,
+            ctx: &Context<'_>,
+            after: Option<Cursor>,
+            before: Option<Cursor>,
+            first: Option<i32>,
+            last: Option<i32>,
+        ) -> FieldResult<Connection<String, EmptyEdgeFields, usize>> {
+            IterDataSource::new(|| "abcdef".chars().map(|c| c.to_string()))
+                .query(ctx, after, before, first, last)
+                .await
+        }
+    }
+
+    let polled = Arc::new(AtomicUsize::new(0));
+    let counted = Arc::new(AtomicUsize::new(0));
+    let schema = Schema::new(
+        Query {
+            polled: polled.clone(),
+            counted: counted.clone(),
+        },
+        EmptyMutation,
+        EmptySubscription,
+    );
+
+    assert_eq!(
+        query_numbers(&schema, r#"first: 3, after: "2""#).await,
+        page(3..6, true, true)
+    );
+    assert_eq!(polled.load(Ordering::SeqCst), 7);
+    assert_eq!(counted.load(Ordering::SeqCst), 0);
+
+    assert_eq!(
+        query_numbers(&schema, "last: 3").await,
+        page(7..10, true, false)
+    );
+    assert_eq!(
+        query_numbers(&schema, r#"last: 3, before: "2""#).await,
+        page(0..2, false, true)
+    );
+    assert_eq!(
+        query_numbers(&schema, r#"after: "3", before: "6""#).await,
+        page(4..6, true, true)
+    );
+    assert_eq!(
+        query_numbers(&schema, r#"first: 3, after: "8""#).await,
+        page(9..10, true, false)
+    );
+
+    assert_eq!(
+        schema
+            .execute("{ numbers(first: 1) { totalCount } }")
+            .await
+            .unwrap()
+            .data,
+        serde_json::json!({ "numbers": { "totalCount": 10 } })
+    );
+    assert_eq!(counted.load(Ordering::SeqCst), 1);
+
+    assert_eq!(
+        schema
+            .execute(r#"{ letters(first: 2, after: "1") { edges { node cursor } } }"#)
+            .await
+            .unwrap()
+            .data,
+        serde_json::json!({
+            "letters": {
+                "edges": [
+                    { "node": "c", "cursor": "2" },
+                    { "node": "d", "cursor": "3" },
+                ]
+            }
+        })
+    );
+}
+
+#[async_std::test]
+pub async fn test_connection_names_and_fields() {
+    struct MemberNames;
+
+    impl ConnectionNameType for MemberNames {
+        fn connection_type_name<T: Type>() -> String {
+            "MemberConnection".to_string()
+        }
+
+        fn edge_type_name<T: Type>() -> String {
+            "MemberEdge".to_string()
+        }
+    }
+
+    #[SimpleObject]
+    #[derive(Default)]
+    struct MemberFields {
+        group: String,
+    }
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        async fn members(
+            &self,
+            ctx: &Context<'_>,
+            first: Option<i32>,
+        ) -> FieldResult<Connection<i32, EmptyEdgeFields, Cursor, MemberFields, MemberNames>>
+        {
+            const MEMBERS: &[i32] = &[1, 2, 3];
+            let connection = MEMBERS.query(ctx, None, None, first, None).await?;
+            Ok(connection
+                .map(|n| *n)
+                .with_name()
+                .with_additional_fields(MemberFields {
+                    group: "admins".to_string(),
+                }))
+        }
+    }
+
+    let schema = Schema::new(Query, EmptyMutation, EmptySubscription);
+    assert_eq!(
+        schema
+            .execute("{ members(first: 2) { __typename group nodes edges { __typename node } } }")
+            .await
+            .unwrap()
+            .data,
+        serde_json::json!({
+            "members": {
+                "__typename": "MemberConnection",
+                "group": "admins",
+                "nodes": [1, 2],
+                "edges": [
+                    { "__typename": "MemberEdge", "node": 1 },
+                    { "__typename": "MemberEdge", "node": 2 },
+                ],
+            }
+        })
+    );
+
+    let sdl = schema
+        .execute(r#"{ __type(name: "MemberConnection") { fields { name } } }"#)
+        .await
+        .unwrap()
+        .data;
+    let mut names = sdl["__type"]["fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|field| field["name"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(
+        names,
+        vec!["edges", "group", "ints", "nodes", "pageInfo", "totalCount"]
+    );
+}
+
+#[async_std::test]
+pub async fn test_connection_additional_fields_type() {
+    #[SimpleObject]
+    #[derive(Default, Clone)]
+    struct Stats {
+        count: i32,
+    }
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        async fn stats(&self) -> Stats {
+            Stats { count: 3 }
+        }
+
+        async fn numbers(
+            &self,
+            ctx: &Context<'_>,
+        ) -> FieldResult<Connection<i32, EmptyEdgeFields, Cursor, Stats>> {
+            const NUMBERS: &[i32] = &[1, 2, 3];
+            let connection = NUMBERS.query(ctx, None, None, None, None).await?;
+            Ok(connection
+                .map(|n| *n)
+                .with_additional_fields(Stats { count: 3 }))
+        }
+    }
+
+    // the type of the additional fields is still a type of the schema
+    let schema = Schema::new(Query, EmptyMutation, EmptySubscription);
+    assert_eq!(
+        schema
+            .execute(r#"{ stats { count } numbers { count } __type(name: "Stats") { name } }"#)
+            .await
+            .unwrap()
+            .data,
+        serde_json::json!({
+            "stats": { "count": 3 },
+            "numbers": { "count": 3 },
+            "__type": { "name": "Stats" },
+        })
+    );
+}
+
+#[async_std::test]
+pub async fn test_connection_additional_fields_recursive() {
+    #[derive(Default)]
+    struct More;
+
+    #[Object]
+    impl More {
+        async fn more(
+            &self,
+            ctx: &Context<'_>,
+        ) -> FieldResult<Connection<i32, EmptyEdgeFields, Cursor, More>> {
+            const NUMBERS: &[i32] = &[1, 2];
+            let connection = NUMBERS.query(ctx, None, None, None, None).await?;
+            Ok(connection.map(|n| *n).with_additional_fields(More))
+        }
+    }
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        async fn numbers(
+            &self,
+            ctx: &Context<'_>,
+        ) -> FieldResult<Connection<i32, EmptyEdgeFields, Cursor, More>> {
+            More.more(ctx).await
+        }
+    }
+
+    let schema = Schema::new(Query, EmptyMutation, EmptySubscription);
+    assert_eq!(
+        schema
+            .execute("{ numbers { nodes more { nodes } } }")
+            .await
+            .unwrap()
+            .data,
+        serde_json::json!({
+            "numbers": { "nodes": [1, 2], "more": { "nodes": [1, 2] } },
+        })
+    );
+}
+
+#[test]
+pub fn test_connection_additional_fields_conflict() {
+    #[SimpleObject]
+    #[derive(Default)]
+    struct Fields {
+        total_count: i32,
+    }
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        async fn numbers(&self) -> Connection<i32, EmptyEdgeFields, usize, Fields> {
+            Connection::new(None, false, false, Vec::new())
+        }
+    }
+
+    let err = Schema::build(Query, EmptyMutation, EmptySubscription)
+        .try_finish()
+        .err()
+        .unwrap();
+    assert_eq!(
+        err,
+        SchemaError("The additional field \"totalCount\" of \"Fields\" conflicts with a field of the connection \"IntConnection\".".to_string())
+    );
+}
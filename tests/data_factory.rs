@@ -0,0 +1,222 @@
+use async_graphql::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+struct Token(String);
+
+struct CurrentUser(String);
+
+struct Permissions(Vec<String>);
+
+struct CurrentUserFactory(Arc<AtomicUsize>);
+
+#[async_trait::async_trait]
+impl DataFactory for CurrentUserFactory {
+    type Output = CurrentUser;
+
+    async fn create(&self, ctx: &Context<'_>) -> FieldResult<CurrentUser> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        let token = ctx.data_opt::<Token>().ok_or("Unauthorized")?;
+        Ok(CurrentUser(format!("user:{}", token.0)))
+    }
+}
+
+struct PermissionsFactory;
+
+#[async_trait::async_trait]
+impl DataFactory for PermissionsFactory {
+    type Output = Permissions;
+
+    async fn create(&self, ctx: &Context<'_>) -> FieldResult<Permissions> {
+        let user = ctx.data_async::<CurrentUser>().await?;
+        Ok(Permissions(vec![format!("{}:read", user.0)]))
+    }
+}
+
+struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    async fn user(&self, ctx: &Context<'_>) -> FieldResult<String> {
+        Ok(ctx.data_async::<CurrentUser>().await?.0.clone())
+    }
+
+    async fn permissions(&self, ctx: &Context<'_>) -> FieldResult<Vec<String>> {
+        Ok(ctx.data_async::<Permissions>().await?.0.clone())
+    }
+
+    async fn cached_user(&self, ctx: &Context<'_>) -> Option<String> {
+        ctx.data_opt::<CurrentUser>().map(|user| user.0.clone())
+    }
+
+    async fn missing(&self, ctx: &Context<'_>) -> FieldResult<i32> {
+        Ok(*ctx.data_async::<i32>().await?)
+    }
+}
+
+#[async_std::test]
+pub async fn test_data_factory() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let schema = Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data_factory(CurrentUserFactory(count.clone()))
+        .data_factory(PermissionsFactory)
+        .finish();
+
+    let resp = QueryBuilder::new("{ a: user b: user permissions cachedUser }")
+        .data(Token("abc".to_string()))
+        .execute(&schema)
+        .await
+        .unwrap();
+    assert_eq!(
+        resp.data,
+        serde_json::json!({
+            "a": "user:abc",
+            "b": "user:abc",
+            "permissions": ["user:abc:read"],
+            "cachedUser": "user:abc",
+        })
+    );
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+
+    let resp = QueryBuilder::new("{ user }")
+        .data(Token("def".to_string()))
+        .execute(&schema)
+        .await
+        .unwrap();
+    assert_eq!(resp.data, serde_json::json!({ "user": "user:def" }));
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+
+    let resp = schema.execute("{ cachedUser }").await.unwrap();
+    assert_eq!(resp.data, serde_json::json!({ "cachedUser": null }));
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+
+    assert!(schema.execute("{ user }").await.is_err());
+    assert!(schema.execute("{ missing }").await.is_err());
+}
+
+#[async_std::test]
+pub async fn test_data_factory_prefers_request_data() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let schema = Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data_factory(CurrentUserFactory(count.clone()))
+        .finish();
+
+    let resp = QueryBuilder::new("{ user }")
+        .data(CurrentUser("explicit".to_string()))
+        .execute(&schema)
+        .await
+        .unwrap();
+    assert_eq!(resp.data, serde_json::json!({ "user": "explicit" }));
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+}
+
+struct Ping(i32);
+
+struct Pong(i32);
+
+struct PingFactory;
+
+#[async_trait::async_trait]
+impl DataFactory for PingFactory {
+    type Output = Ping;
+
+    async fn create(&self, ctx: &Context<'_>) -> FieldResult<Ping> {
+        Ok(Ping(ctx.data_async::<Pong>().await?.0 + 1))
+    }
+}
+
+struct PongFactory;
+
+#[async_trait::async_trait]
+impl DataFactory for PongFactory {
+    type Output = Pong;
+
+    async fn create(&self, ctx: &Context<'_>) -> FieldResult<Pong> {
+        Ok(Pong(ctx.data_async::<Ping>().await?.0 + 1))
+    }
+}
+
+struct SelfFactory;
+
+#[async_trait::async_trait]
+impl DataFactory for SelfFactory {
+    type Output = i64;
+
+    async fn create(&self, ctx: &Context<'_>) -> FieldResult<i64> {
+        Ok(*ctx.data_async::<i64>().await? + 1)
+    }
+}
+
+struct CycleQueryRoot;
+
+#[Object]
+impl CycleQueryRoot {
+    async fn ping(&self, ctx: &Context<'_>) -> FieldResult<i32> {
+        Ok(ctx.data_async::<Ping>().await?.0)
+    }
+
+    async fn pong(&self, ctx: &Context<'_>) -> FieldResult<i32> {
+        Ok(ctx.data_async::<Pong>().await?.0)
+    }
+
+    async fn value(&self, ctx: &Context<'_>) -> FieldResult<i64> {
+        Ok(*ctx.data_async::<i64>().await?)
+    }
+
+    async fn user(&self, ctx: &Context<'_>) -> Option<String> {
+        ctx.data_async::<CurrentUser>()
+            .await
+            .ok()
+            .map(|user| user.0.clone())
+    }
+}
+
+#[async_std::test]
+pub async fn test_data_factory_cycle() {
+    let schema = Schema::build(CycleQueryRoot, EmptyMutation, EmptySubscription)
+        .data_factory(PingFactory)
+        .data_factory(PongFactory)
+        .data_factory(SelfFactory)
+        .finish();
+
+    for query in &["{ value }", "{ ping }", "{ ping pong }"] {
+        let res =
+            async_std::future::timeout(std::time::Duration::from_secs(5), schema.execute(query))
+                .await
+                .expect("the data factories are deadlocked");
+        assert!(res.is_err());
+    }
+}
+
+#[async_std::test]
+pub async fn test_data_factory_caches_errors() {
+    let count = Arc::new(AtomicUsize::new(0));
+    let schema = Schema::build(CycleQueryRoot, EmptyMutation, EmptySubscription)
+        .data_factory(CurrentUserFactory(count.clone()))
+        .finish();
+
+    let resp = schema.execute("{ a: user b: user }").await.unwrap();
+    assert_eq!(resp.data, serde_json::json!({ "a": null, "b": null }));
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+}
+
+#[async_std::test]
+#[should_panic(expected = "is created by a data factory, use `Context::data_async` to get it.")]
+pub async fn test_data_factory_data_panics() {
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {
+        async fn user(&self, ctx: &Context<'_>) -> String {
+            ctx.data::<CurrentUser>().0.clone()
+        }
+    }
+
+    let schema = Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data_factory(CurrentUserFactory(Default::default()))
+        .finish();
+    let _ = QueryBuilder::new("{ user }")
+        .data(Token("abc".to_string()))
+        .execute(&schema)
+        .await;
+}
@@ -154,6 +154,7 @@ pub async fn test_guard() {
             )
             .await
             .unwrap()
+            .map(|res| res.map(|resp| resp.data))
             .collect::<Vec<_>>()
             .await,
         vec![
@@ -0,0 +1,97 @@
+use async_graphql::http::RequestMetadata;
+use async_graphql::*;
+
+#[async_std::test]
+pub async fn test_http_headers() {
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {
+        async fn value(&self, ctx: &Context<'_>) -> FieldResult<i32> {
+            ctx.insert_http_header("x-custom", "1")?;
+            ctx.insert_http_header("x-custom", "2")?;
+            ctx.append_http_header("x-multi", "a")?;
+            ctx.append_http_header("x-multi", "b")?;
+            ctx.set_cookie("session", "abc")?;
+            Ok(10)
+        }
+
+        async fn invalid(&self, ctx: &Context<'_>) -> FieldResult<i32> {
+            ctx.insert_http_header("x-invalid", "a\nb")?;
+            Ok(10)
+        }
+
+        async fn cookie(&self, ctx: &Context<'_>, name: String, value: String) -> FieldResult<i32> {
+            ctx.set_cookie(&name, &value)?;
+            Ok(10)
+        }
+    }
+
+    let schema = Schema::new(QueryRoot, EmptyMutation, EmptySubscription);
+    let resp = schema.execute("{ value }").await.unwrap();
+    assert_eq!(resp.data, serde_json::json!({ "value": 10 }));
+    assert_eq!(
+        resp.http_headers
+            .get_all("x-custom")
+            .iter()
+            .collect::<Vec<_>>(),
+        vec!["2"]
+    );
+    assert_eq!(
+        resp.http_headers
+            .get_all("x-multi")
+            .iter()
+            .collect::<Vec<_>>(),
+        vec!["a", "b"]
+    );
+    assert_eq!(resp.http_headers.get("set-cookie").unwrap(), "session=abc");
+
+    assert!(schema.execute("{ invalid }").await.is_err());
+
+    let resp = schema
+        .execute(r#"{ cookie(name: "id", value: "\"a-b\"") }"#)
+        .await
+        .unwrap();
+    assert_eq!(resp.http_headers.get("set-cookie").unwrap(), "id=\"a-b\"");
+    for (name, value) in &[
+        ("", "abc"),
+        ("a b", "abc"),
+        ("a=b", "abc"),
+        ("id", "a;b"),
+        ("id", "a b"),
+        ("id", "a,b"),
+        ("id", "a\\\\b"),
+    ] {
+        let query = format!(r#"{{ cookie(name: "{}", value: "{}") }}"#, name, value);
+        assert!(schema.execute(&query).await.is_err());
+    }
+}
+
+#[async_std::test]
+pub async fn test_request_metadata() {
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {
+        async fn user_agent<'a>(&self, ctx: &'a Context<'_>) -> Option<&'a str> {
+            ctx.data_opt::<RequestMetadata>()
+                .and_then(|metadata| metadata.header("user-agent"))
+        }
+    }
+
+    let schema = Schema::new(QueryRoot, EmptyMutation, EmptySubscription);
+
+    let mut metadata = RequestMetadata::default();
+    metadata
+        .headers
+        .insert("user-agent", "test-agent".parse().unwrap());
+    let resp = QueryBuilder::new("{ userAgent }")
+        .data(metadata)
+        .execute(&schema)
+        .await
+        .unwrap();
+    assert_eq!(resp.data, serde_json::json!({ "userAgent": "test-agent" }));
+
+    let resp = schema.execute("{ userAgent }").await.unwrap();
+    assert_eq!(resp.data, serde_json::json!({ "userAgent": null }));
+}
@@ -0,0 +1,246 @@
+use async_graphql::*;
+
+#[async_std::test]
+pub async fn test_look_ahead_simple() {
+    #[SimpleObject]
+    #[derive(Clone)]
+    struct Detail {
+        c: i32,
+        d: i32,
+    }
+
+    #[SimpleObject]
+    struct MyObj {
+        a: i32,
+        b: i32,
+        detail: Detail,
+    }
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        async fn obj(&self, ctx: &Context<'_>, n: i32) -> MyObj {
+            if ctx.look_ahead().field("a").exists() {
+                // This is a query like `obj { a }`
+                assert_eq!(n, 1);
+            } else if ctx.look_ahead().field("detail").field("c").exists() {
+                // This is a query like `obj { detail { c } }`
+                assert_eq!(n, 2);
+            } else {
+                // This query doesn't have `a`
+                assert_eq!(n, 3);
+            }
+            MyObj {
+                a: 0,
+                b: 0,
+                detail: Detail { c: 0, d: 0 },
+            }
+        }
+    }
+
+    let schema = Schema::new(Query, EmptyMutation, EmptySubscription);
+
+    assert!(schema
+        .execute(
+            r#"{
+        obj(n: 1) {
+            a
+        }
+    }"#,
+        )
+        .await
+        .is_ok());
+
+    assert!(schema
+        .execute(
+            r#"{
+        obj(n: 1) {
+            k:a
+        }
+    }"#,
+        )
+        .await
+        .is_ok());
+
+    assert!(schema
+        .execute(
+            r#"{
+        obj(n: 2) {
+            detail {
+                c
+            }
+        }
+    }"#,
+        )
+        .await
+        .is_ok());
+
+    assert!(schema
+        .execute(
+            r#"{
+        obj(n: 3) {
+            b
+        }
+    }"#,
+        )
+        .await
+        .is_ok());
+
+    assert!(schema
+        .execute(
+            r#"{
+        obj(n: 1) {
+            ... {
+                a
+            }
+        }
+    }"#,
+        )
+        .await
+        .is_ok());
+
+    assert!(schema
+        .execute(
+            r#"{
+        obj(n: 2) {
+            ... {
+                detail {
+                    c
+                }
+            }
+        }
+    }"#,
+        )
+        .await
+        .is_ok());
+
+    assert!(schema
+        .execute(
+            r#"{
+        obj(n: 1) {
+            ... A
+        }
+    }
+
+    fragment A on MyObj {
+        a
+    }"#,
+        )
+        .await
+        .is_ok());
+
+    assert!(schema
+        .execute(
+            r#"{
+        obj(n: 2) {
+            ... A
+        }
+    }
+
+    fragment A on MyObj {
+        detail {
+            c
+        }
+    }"#,
+        )
+        .await
+        .is_ok());
+
+    assert!(schema
+        .execute(
+            r#"{
+        obj(n: 3) {
+            a @skip(if: true)
+            b
+        }
+    }"#,
+        )
+        .await
+        .is_ok());
+}
+
+#[async_std::test]
+pub async fn test_look_ahead_field_names_and_arguments() {
+    #[SimpleObject]
+    struct Item {
+        id: i32,
+        name: String,
+    }
+
+    struct Items;
+
+    #[Object]
+    impl Items {
+        async fn total(&self) -> i32 {
+            0
+        }
+
+        async fn list(&self, limit: i32, order: Option<String>) -> Vec<Item> {
+            let _ = (limit, order);
+            Vec::new()
+        }
+    }
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        async fn items(&self, ctx: &Context<'_>) -> FieldResult<Items> {
+            let look_ahead = ctx.look_ahead();
+            assert_eq!(look_ahead.field_names(), vec!["total", "list"]);
+            assert!(look_ahead.is_selected("list"));
+            assert!(!look_ahead.is_selected("other"));
+
+            let list = look_ahead.field("list");
+            assert_eq!(list.field_names(), vec!["id", "name"]);
+            assert_eq!(list.argument("limit"), Some(Value::Int(10.into())));
+            assert_eq!(
+                list.argument("order"),
+                Some(Value::String("name".to_string()))
+            );
+            assert_eq!(list.argument("unknown"), None);
+            assert_eq!(
+                list.arguments(),
+                vec![
+                    ("limit", Value::Int(10.into())),
+                    ("order", Value::String("name".to_string()))
+                ]
+            );
+            Ok(Items)
+        }
+    }
+
+    let schema = Schema::new(Query, EmptyMutation, EmptySubscription);
+    let query = r#"query ($limit: Int!, $order: String = "name") {
+        items {
+            total
+            list(limit: $limit, order: $order) {
+                id
+                ... F
+            }
+            list2: list(limit: $limit, order: $order) {
+                id
+            }
+        }
+    }
+
+    fragment F on Item {
+        name
+    }"#;
+    let resp = QueryBuilder::new(query)
+        .variables(Variables::parse_from_json(serde_json::json!({ "limit": 10 })).unwrap())
+        .execute(&schema)
+        .await
+        .unwrap();
+    assert_eq!(
+        resp.data,
+        serde_json::json!({
+            "items": {
+                "total": 0,
+                "list": [],
+                "list2": [],
+            }
+        })
+    );
+}
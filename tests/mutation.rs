@@ -76,3 +76,50 @@ pub async fn test_mutation_fragment() {
         })
     );
 }
+
+#[async_std::test]
+pub async fn test_mutation_disabled() {
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {
+        async fn value(&self) -> i32 {
+            10
+        }
+    }
+
+    struct MutationRoot;
+
+    #[Object]
+    impl MutationRoot {
+        async fn action(&self) -> bool {
+            true
+        }
+    }
+
+    let schema = Schema::new(QueryRoot, MutationRoot, EmptySubscription);
+
+    let builder = async_graphql::http::parse_query_string("query=%7B%20value%20%7D").unwrap();
+    assert_eq!(
+        builder.execute(&schema).await.unwrap().data,
+        serde_json::json!({ "value": 10 })
+    );
+
+    let builder =
+        async_graphql::http::parse_query_string("query=mutation%20%7B%20action%20%7D").unwrap();
+    assert_eq!(
+        builder.execute(&schema).await.unwrap_err(),
+        Error::Query {
+            pos: Pos::default(),
+            path: None,
+            err: QueryError::MutationNotAllowed,
+        }
+    );
+
+    let res = QueryBuilder::new("mutation { action }")
+        .execute(&schema)
+        .await
+        .unwrap()
+        .data;
+    assert_eq!(res, serde_json::json!({ "action": true }));
+}
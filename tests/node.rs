@@ -0,0 +1,189 @@
+use async_graphql::*;
+
+#[SimpleObject]
+struct User {
+    id: GlobalId<User>,
+    name: String,
+}
+
+#[SimpleObject]
+struct Post {
+    id: GlobalId<Post>,
+    title: String,
+}
+
+struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    #[node]
+    async fn find_user(&self, id: ID) -> Option<User> {
+        if id.as_str() == "1" {
+            Some(User {
+                id: GlobalId::new(id),
+                name: "jack".to_string(),
+            })
+        } else {
+            None
+        }
+    }
+
+    #[node]
+    async fn find_post(&self, _ctx: &Context<'_>, id: String) -> FieldResult<Post> {
+        if id == "error" {
+            return Err("failed to load the post".into());
+        }
+        Ok(Post {
+            id: GlobalId::new(id.clone()),
+            title: format!("post {}", id),
+        })
+    }
+
+    async fn user_name(&self, id: GlobalId<User>) -> String {
+        format!("user {}", id.id().as_str())
+    }
+}
+
+#[async_std::test]
+pub async fn test_node() {
+    let schema = Schema::new(QueryRoot, EmptyMutation, EmptySubscription);
+    let user_id = User::global_id("1".into());
+    let post_id = Post::global_id("2".into());
+
+    let query = format!(
+        r#"{{
+            node(id: "{}") {{
+                id
+                __typename
+                ... on User {{ name }}
+                ... on Post {{ title }}
+            }}
+        }}"#,
+        user_id.as_str()
+    );
+    assert_eq!(
+        schema.execute(&query).await.unwrap().data,
+        serde_json::json!({
+            "node": {
+                "id": user_id.as_str(),
+                "__typename": "User",
+                "name": "jack",
+            }
+        })
+    );
+
+    let query = format!(
+        r#"{{
+            nodes(ids: ["{}", "{}", "{}", "invalid", "{}"]) {{
+                ... on Node {{ id }}
+                ... on Post {{ title }}
+            }}
+        }}"#,
+        post_id.as_str(),
+        User::global_id("2".into()).as_str(),
+        ID::from("VW5rbm93bjox"),
+        Post::global_id("error".into()).as_str(),
+    );
+    assert_eq!(
+        schema.execute(&query).await.unwrap().data,
+        serde_json::json!({
+            "nodes": [
+                { "id": post_id.as_str(), "title": "post 2" },
+                null,
+                null,
+                null,
+                null,
+            ]
+        })
+    );
+}
+
+#[async_std::test]
+pub async fn test_global_id_input() {
+    let schema = Schema::new(QueryRoot, EmptyMutation, EmptySubscription);
+
+    let query = format!(
+        r#"{{ userName(id: "{}") }}"#,
+        User::global_id("1".into()).as_str()
+    );
+    assert_eq!(
+        schema.execute(&query).await.unwrap().data,
+        serde_json::json!({ "userName": "user 1" })
+    );
+
+    let query = format!(
+        r#"{{ userName(id: "{}") }}"#,
+        Post::global_id("1".into()).as_str()
+    );
+    assert!(schema.execute(&query).await.is_err());
+
+    assert_eq!(User::parse_global_id(Post::global_id("1".into())), None);
+    assert_eq!(
+        User::parse_global_id(User::global_id("1".into())),
+        Some(ID::from("1"))
+    );
+    assert_eq!(User::from_global_id("Post:1".into()), None);
+    assert_eq!(User::from_global_id("User:1".into()), Some(ID::from("1")));
+}
+
+#[async_std::test]
+pub async fn test_node_loader_outside_query_root() {
+    struct Users;
+
+    #[Object]
+    impl Users {
+        #[node]
+        async fn find_user(&self, id: ID) -> User {
+            User {
+                id: GlobalId::new(id),
+                name: "jack".to_string(),
+            }
+        }
+    }
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        async fn users(&self) -> Users {
+            Users
+        }
+    }
+
+    let err = Schema::build(Query, EmptyMutation, EmptySubscription)
+        .try_finish()
+        .err()
+        .unwrap();
+    assert_eq!(
+        err,
+        SchemaError(
+            r#"The node loader of "User" is defined on "Users", it must be defined on the query root "Query"."#
+                .to_string()
+        )
+    );
+}
+
+#[async_std::test]
+pub async fn test_node_interface_introspection() {
+    let schema = Schema::new(QueryRoot, EmptyMutation, EmptySubscription);
+    let query = r#"{
+        __type(name: "Node") {
+            kind
+            fields { name }
+            possibleTypes { name }
+        }
+    }"#;
+    let mut data = schema.execute(query).await.unwrap().data;
+    let possible_types = data["__type"]["possibleTypes"].as_array_mut().unwrap();
+    possible_types.sort_by_key(|ty| ty["name"].as_str().unwrap().to_string());
+    assert_eq!(
+        data,
+        serde_json::json!({
+            "__type": {
+                "kind": "INTERFACE",
+                "fields": [{ "name": "id" }],
+                "possibleTypes": [{ "name": "Post" }, { "name": "User" }],
+            }
+        })
+    );
+}
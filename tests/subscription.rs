@@ -39,7 +39,8 @@ pub async fn test_subscription() {
                 None,
             )
             .await
-            .unwrap();
+            .unwrap()
+            .map(|res| res.map(|resp| resp.data));
         for i in 10..20 {
             assert_eq!(
                 Some(Ok(serde_json::json!({ "values": i }))),
@@ -58,7 +59,8 @@ pub async fn test_subscription() {
                 None,
             )
             .await
-            .unwrap();
+            .unwrap()
+            .map(|res| res.map(|resp| resp.data));
         for i in 10..20 {
             assert_eq!(
                 Some(Ok(serde_json::json!({ "events": {"a": i, "b": i * 10} }))),
@@ -110,7 +112,8 @@ pub async fn test_simple_broker() {
             None,
         )
         .await
-        .unwrap();
+        .unwrap()
+        .map(|res| res.map(|resp| resp.data));
     let mut stream2 = schema
         .create_subscription_stream(
             "subscription { events2 { value } }",
@@ -119,7 +122,8 @@ pub async fn test_simple_broker() {
             None,
         )
         .await
-        .unwrap();
+        .unwrap()
+        .map(|res| res.map(|resp| resp.data));
 
     SimpleBroker::publish(Event1 { value: 10 });
     SimpleBroker::publish(Event2 { value: 88 });
@@ -190,7 +194,8 @@ pub async fn test_subscription_with_ctx_data() {
                 })),
             )
             .await
-            .unwrap();
+            .unwrap()
+            .map(|res| res.map(|resp| resp.data));
         assert_eq!(
             Some(Ok(serde_json::json!({ "values": 100 }))),
             stream.next().await
@@ -239,7 +244,8 @@ pub async fn test_subscription_with_token() {
                 })),
             )
             .await
-            .unwrap();
+            .unwrap()
+            .map(|res| res.map(|resp| resp.data));
         assert_eq!(
             Some(Ok(serde_json::json!({ "values": 100 }))),
             stream.next().await
@@ -304,7 +310,8 @@ pub async fn test_subscription_inline_fragment() {
             None,
         )
         .await
-        .unwrap();
+        .unwrap()
+        .map(|res| res.map(|resp| resp.data));
     for i in 10..20 {
         assert_eq!(
             Some(Ok(serde_json::json!({ "events": {"a": i, "b": i * 10} }))),
@@ -359,7 +366,8 @@ pub async fn test_subscription_fragment() {
             None,
         )
         .await
-        .unwrap();
+        .unwrap()
+        .map(|res| res.map(|resp| resp.data));
     for i in 10..20 {
         assert_eq!(
             Some(Ok(serde_json::json!({ "events": {"a": i, "b": i * 10} }))),
@@ -415,7 +423,8 @@ pub async fn test_subscription_fragment2() {
             None,
         )
         .await
-        .unwrap();
+        .unwrap()
+        .map(|res| res.map(|resp| resp.data));
     for i in 10..20 {
         assert_eq!(
             Some(Ok(serde_json::json!({ "events": {"a": i, "b": i * 10} }))),
@@ -465,7 +474,8 @@ pub async fn test_subscription_error() {
             None,
         )
         .await
-        .unwrap();
+        .unwrap()
+        .map(|res| res.map(|resp| resp.data));
     for i in 0i32..5 {
         assert_eq!(
             Some(Ok(serde_json::json!({ "events": { "value": i } }))),
@@ -514,7 +524,8 @@ pub async fn test_subscription_fieldresult() {
     let mut stream = schema
         .create_subscription_stream("subscription { values }", None, Default::default(), None)
         .await
-        .unwrap();
+        .unwrap()
+        .map(|res| res.map(|resp| resp.data));
     for i in 0i32..5 {
         assert_eq!(
             Some(Ok(serde_json::json!({ "values": i }))),
@@ -538,3 +549,140 @@ pub async fn test_subscription_fieldresult() {
 
     assert!(stream.next().await.is_none());
 }
+
+#[async_std::test]
+pub async fn test_subscription_extensions() {
+    use async_graphql::extensions::{Extension, ResolveInfo};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Counters {
+        parse: AtomicUsize,
+        validation: AtomicUsize,
+        execution_end: AtomicUsize,
+        end: AtomicUsize,
+    }
+
+    struct MyExtension {
+        counters: Arc<Counters>,
+        parsed: AtomicBool,
+        fields: Mutex<Vec<String>>,
+    }
+
+    impl Extension for MyExtension {
+        fn name(&self) -> Option<&'static str> {
+            Some("fields")
+        }
+
+        fn parse_start(&self, _query_source: &str) {
+            self.counters.parse.fetch_add(1, Ordering::SeqCst);
+            self.parsed.store(true, Ordering::SeqCst);
+        }
+
+        fn validation_start(&self) {
+            self.counters.validation.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn execution_end(&self) {
+            self.counters.execution_end.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn resolve_field_start(&self, info: &ResolveInfo<'_>) {
+            self.fields.lock().unwrap().push(info.path_node.to_string());
+        }
+
+        fn subscription_end(&self) {
+            self.counters.end.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn result(&self) -> Option<serde_json::Value> {
+            Some(serde_json::json!({
+                "parsed": self.parsed.load(Ordering::SeqCst),
+                "fields": *self.fields.lock().unwrap(),
+            }))
+        }
+    }
+
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {}
+
+    struct Event {
+        a: i32,
+    }
+
+    #[Object]
+    impl Event {
+        async fn a(&self) -> FieldResult<i32> {
+            if self.a == 2 {
+                return Err("Invalid event".into());
+            }
+            Ok(self.a)
+        }
+    }
+
+    struct SubscriptionRoot;
+
+    #[Subscription]
+    impl SubscriptionRoot {
+        async fn events(&self) -> impl Stream<Item = Event> {
+            futures::stream::iter((0..3).map(|a| Event { a }))
+        }
+    }
+
+    let counters = Arc::new(Counters::default());
+    let schema = Schema::build(QueryRoot, EmptyMutation, SubscriptionRoot)
+        .extension({
+            let counters = counters.clone();
+            move || MyExtension {
+                counters: counters.clone(),
+                parsed: Default::default(),
+                fields: Default::default(),
+            }
+        })
+        .finish();
+
+    let mut stream = schema
+        .create_subscription_stream(
+            "subscription { events { a } }",
+            None,
+            Default::default(),
+            None,
+        )
+        .await
+        .unwrap();
+    for a in 0..2 {
+        let resp = stream.next().await.unwrap().unwrap();
+        assert_eq!(resp.data, serde_json::json!({ "events": { "a": a } }));
+        assert_eq!(
+            resp.extensions,
+            Some(
+                serde_json::json!({ "fields": { "parsed": false, "fields": ["events.a"] } })
+                    .as_object()
+                    .unwrap()
+                    .clone()
+            )
+        );
+    }
+    // the extensions see the end of the execution of a failed event
+    assert!(stream.next().await.unwrap().is_err());
+    assert!(stream.next().await.is_none());
+
+    assert_eq!(counters.parse.load(Ordering::SeqCst), 1);
+    assert_eq!(counters.validation.load(Ordering::SeqCst), 1);
+    assert_eq!(counters.execution_end.load(Ordering::SeqCst), 3);
+    assert_eq!(counters.end.load(Ordering::SeqCst), 1);
+
+    // the subscriptions that fail to parse or validate end too
+    for query in &["subscription {", "subscription { unknown }"] {
+        assert!(schema
+            .create_subscription_stream(query, None, Default::default(), None)
+            .await
+            .is_err());
+    }
+    assert_eq!(counters.parse.load(Ordering::SeqCst), 3);
+    assert_eq!(counters.validation.load(Ordering::SeqCst), 2);
+    assert_eq!(counters.end.load(Ordering::SeqCst), 3);
+}
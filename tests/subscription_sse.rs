@@ -0,0 +1,91 @@
+use async_graphql::http::sse_stream;
+use async_graphql::*;
+use futures::{Stream, StreamExt};
+
+struct QueryRoot;
+
+#[Object]
+impl QueryRoot {}
+
+struct SubscriptionRoot;
+
+#[Subscription]
+impl SubscriptionRoot {
+    async fn values(&self, ctx: &Context<'_>, count: i32) -> impl Stream<Item = i32> {
+        let step = *ctx.data::<i32>();
+        futures::stream::iter((0..count).map(move |n| n * step))
+    }
+}
+
+async fn collect_events(builder: QueryBuilder) -> String {
+    let schema = Schema::new(QueryRoot, EmptyMutation, SubscriptionRoot);
+    sse_stream(schema, builder)
+        .map(|bytes| String::from_utf8(bytes.to_vec()).unwrap())
+        .collect::<Vec<_>>()
+        .await
+        .concat()
+}
+
+#[async_std::test]
+pub async fn test_sse_stream() {
+    assert_eq!(
+        collect_events(
+            QueryBuilder::new("subscription($count: Int!) { values(count: $count) }")
+                .variables(Variables::parse_from_json(serde_json::json!({ "count": 2 })).unwrap())
+                .data(10)
+        )
+        .await,
+        "event: next\ndata: {\"data\":{\"values\":0}}\n\n\
+         event: next\ndata: {\"data\":{\"values\":10}}\n\n\
+         event: complete\ndata:\n\n"
+    );
+}
+
+#[async_std::test]
+pub async fn test_sse_stream_error() {
+    let events = collect_events(QueryBuilder::new("subscription { values(count: 2) { a } }")).await;
+    let events = events.split("\n\n").collect::<Vec<_>>();
+    assert_eq!(events.len(), 3);
+    assert!(events[0].starts_with("event: next\ndata: {\"errors\":["));
+    assert_eq!(events[1], "event: complete\ndata:");
+    assert_eq!(events[2], "");
+}
+
+#[async_std::test]
+pub async fn test_handle_sse_event_source() {
+    use async_graphql::http::{handle, handle_sse, Body};
+    use futures::TryStreamExt;
+
+    let schema = Schema::new(QueryRoot, EmptyMutation, SubscriptionRoot);
+    let event_source_request = || {
+        ::http::Request::get("/?query=subscription%20%7B%20values(count%3A%202)%20%7D")
+            .header("accept", "text/event-stream")
+            .body(Body::default())
+            .unwrap()
+    };
+
+    // an `EventSource` can't set headers, only the Server-Sent Events route accepts its requests
+    let resp = handle(&schema, event_source_request()).await;
+    assert_eq!(resp.status(), ::http::StatusCode::BAD_REQUEST);
+
+    let resp = handle_sse(&schema, event_source_request(), |builder| builder.data(10)).await;
+    assert_eq!(resp.headers()["content-type"], "text/event-stream");
+    let body = resp
+        .into_body()
+        .map_ok(|bytes| bytes.to_vec())
+        .try_concat()
+        .await
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(body).unwrap(),
+        "event: next\ndata: {\"data\":{\"values\":0}}\n\n\
+         event: next\ndata: {\"data\":{\"values\":10}}\n\n\
+         event: complete\ndata:\n\n"
+    );
+
+    let req = ::http::Request::get("/?query=subscription%20%7B%20values(count%3A%202)%20%7D")
+        .body(Body::default())
+        .unwrap();
+    let resp = handle_sse(&schema, req, |builder| builder.data(10)).await;
+    assert_eq!(resp.status(), ::http::StatusCode::BAD_REQUEST);
+}
@@ -1,5 +1,5 @@
 use async_graphql::*;
-use futures::{SinkExt, Stream, StreamExt};
+use futures::{FutureExt, SinkExt, Stream, StreamExt};
 
 #[async_std::test]
 pub async fn test_subscription_ws_transport() {
@@ -62,6 +62,14 @@ pub async fn test_subscription_ws_transport() {
             serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
         );
     }
+
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "complete",
+        "id": "1",
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
 }
 
 #[async_std::test]
@@ -143,6 +151,78 @@ pub async fn test_subscription_ws_transport_with_token() {
     }
 }
 
+#[async_std::test]
+pub async fn test_subscription_ws_transport_duplicate_id() {
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {}
+
+    struct SubscriptionRoot;
+
+    #[Subscription]
+    impl SubscriptionRoot {
+        async fn values(
+            &self,
+            ctx: &Context<'_>,
+        ) -> FieldResult<impl Stream<Item = FieldResult<i32>>> {
+            ctx.data::<MemoryBroker<i32>>().subscribe("values").await
+        }
+    }
+
+    let broker = MemoryBroker::<i32>::new(16);
+    let schema = Schema::build(QueryRoot, EmptyMutation, SubscriptionRoot)
+        .data(broker.clone())
+        .finish();
+    let (mut sink, mut stream) = schema.subscription_connection(WebSocketTransport::default());
+
+    for msg in &[
+        serde_json::json!({ "type": "connection_init" }),
+        serde_json::json!({
+            "type": "start",
+            "id": "1",
+            "payload": { "query": "subscription { values }" },
+        }),
+        serde_json::json!({
+            "type": "start",
+            "id": "1",
+            "payload": { "query": "subscription { values }" },
+        }),
+    ] {
+        sink.send(serde_json::to_vec(msg).unwrap().into())
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "connection_ack",
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "error",
+        "id": "1",
+        "payload": [{ "message": "Subscriber for 1 already exists" }],
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+
+    // the first operation keeps running, and only once
+    assert!(stream.next().now_or_never().is_none());
+    broker.publish("values", 1).await.unwrap();
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "data",
+        "id": "1",
+        "payload": { "data": { "values": 1 } },
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+    assert!(stream.next().now_or_never().is_none());
+}
+
 #[async_std::test]
 pub async fn test_subscription_ws_transport_error() {
     struct QueryRoot;
@@ -234,3 +314,1099 @@ pub async fn test_subscription_ws_transport_error() {
         serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
     );
 }
+
+#[async_std::test]
+pub async fn test_subscription_graphql_transport_ws() {
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {}
+
+    struct SubscriptionRoot;
+
+    #[Subscription]
+    impl SubscriptionRoot {
+        async fn values(&self) -> impl Stream<Item = i32> {
+            futures::stream::iter(0..3)
+        }
+    }
+
+    let schema = Schema::new(QueryRoot, EmptyMutation, SubscriptionRoot);
+    let (mut sink, mut stream) = schema.subscription_connection(GraphQLTransportWS::default());
+
+    sink.send(
+        serde_json::to_vec(&serde_json::json!({
+            "type": "connection_init",
+            "payload": { "token": "123456" }
+        }))
+        .unwrap()
+        .into(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "connection_ack",
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+
+    sink.send(
+        serde_json::to_vec(&serde_json::json!({
+            "type": "ping",
+            "payload": { "a": 1 }
+        }))
+        .unwrap()
+        .into(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "pong",
+        "payload": { "a": 1 },
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+
+    sink.send(
+        serde_json::to_vec(&serde_json::json!({
+            "type": "subscribe",
+            "id": "1",
+            "payload": {
+                "query": "subscription { values }"
+            },
+        }))
+        .unwrap()
+        .into(),
+    )
+    .await
+    .unwrap();
+
+    for i in 0..3 {
+        assert_eq!(
+            Some(serde_json::json!({
+            "type": "next",
+            "id": "1",
+            "payload": { "data": { "values": i } },
+            })),
+            serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+        );
+    }
+
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "complete",
+        "id": "1",
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+}
+
+#[async_std::test]
+pub async fn test_subscription_graphql_transport_ws_unauthorized() {
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {}
+
+    struct SubscriptionRoot;
+
+    #[Subscription]
+    impl SubscriptionRoot {
+        async fn values(&self) -> impl Stream<Item = i32> {
+            futures::stream::iter(0..3)
+        }
+    }
+
+    let schema = Schema::new(QueryRoot, EmptyMutation, SubscriptionRoot);
+    let (mut sink, mut stream) = schema.subscription_connection(GraphQLTransportWS::default());
+
+    sink.send(
+        serde_json::to_vec(&serde_json::json!({
+            "type": "subscribe",
+            "id": "1",
+            "payload": {
+                "query": "subscription { values }"
+            },
+        }))
+        .unwrap()
+        .into(),
+    )
+    .await
+    .unwrap();
+
+    assert!(stream.next().await.is_none());
+    assert_eq!(
+        stream.close_frame(),
+        Some(&CloseFrame::new(4401, "Unauthorized"))
+    );
+}
+
+#[async_std::test]
+pub async fn test_subscription_graphql_transport_ws_close_codes() {
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {}
+
+    struct SubscriptionRoot;
+
+    #[Subscription]
+    impl SubscriptionRoot {
+        async fn values(&self) -> impl Stream<Item = i32> {
+            futures::stream::pending()
+        }
+    }
+
+    let schema = Schema::new(QueryRoot, EmptyMutation, SubscriptionRoot);
+    let init = serde_json::json!({ "type": "connection_init" });
+    let subscribe = serde_json::json!({
+        "type": "subscribe",
+        "id": "1",
+        "payload": { "query": "subscription { values }" },
+    });
+
+    for (messages, code) in &[
+        (vec![serde_json::json!({ "type": "unknown" })], 4400),
+        (vec![init.clone(), init.clone()], 4429),
+        (
+            vec![init.clone(), subscribe.clone(), subscribe.clone()],
+            4409,
+        ),
+    ] {
+        let (mut sink, mut stream) = schema.subscription_connection(GraphQLTransportWS::default());
+        for msg in messages {
+            sink.send(serde_json::to_vec(msg).unwrap().into())
+                .await
+                .unwrap();
+        }
+        while stream.next().await.is_some() {}
+        assert_eq!(stream.close_frame().map(|frame| frame.code), Some(*code));
+    }
+
+    let hooks =
+        WebSocketHooks::default().on_connect(|_| async move { Err("invalid token".into()) });
+    let (mut sink, mut stream) =
+        schema.subscription_connection(GraphQLTransportWS::default().hooks(hooks));
+    sink.send(serde_json::to_vec(&init).unwrap().into())
+        .await
+        .unwrap();
+    assert!(stream.next().await.is_none());
+    assert_eq!(
+        stream.close_frame(),
+        Some(&CloseFrame::new(4403, "Forbidden"))
+    );
+
+    let (_sink, stream) = schema.subscription_connection(
+        GraphQLTransportWS::default()
+            .connection_init_timeout(Some(std::time::Duration::from_secs(0))),
+    );
+    let mut stream = stream.timer(futures::stream::iter(vec![()]));
+    assert!(stream.next().await.is_none());
+    assert_eq!(
+        stream.close_frame(),
+        Some(&CloseFrame::new(4408, "Connection initialisation timeout"))
+    );
+}
+
+#[test]
+pub fn test_websocket_protocols() {
+    assert_eq!(
+        WebSocketProtocols::negotiate(None),
+        WebSocketProtocols::SubscriptionsTransportWS
+    );
+    assert_eq!(
+        WebSocketProtocols::negotiate(Some("graphql-transport-ws")),
+        WebSocketProtocols::GraphQLTransportWS
+    );
+    assert_eq!(
+        WebSocketProtocols::negotiate(Some("foo, graphql-ws, graphql-transport-ws")),
+        WebSocketProtocols::SubscriptionsTransportWS
+    );
+    assert_eq!(
+        WebSocketProtocols::GraphQLTransportWS.sec_websocket_protocol(),
+        "graphql-transport-ws"
+    );
+}
+
+#[async_std::test]
+pub async fn test_subscription_ws_transport_connection_error() {
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {}
+
+    let schema = Schema::new(QueryRoot, EmptyMutation, EmptySubscription);
+    let (mut sink, mut stream) = schema.subscription_connection(WebSocketTransport::new(|_| {
+        Err(FieldError("invalid token".to_string(), None))
+    }));
+
+    sink.send(
+        serde_json::to_vec(&serde_json::json!({
+            "type": "connection_init",
+            "payload": { "token": "abc" }
+        }))
+        .unwrap()
+        .into(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "connection_error",
+        "payload": { "message": "invalid token" },
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+
+    // the connection is closed after the rejection, without waiting for the client or a tick
+    assert!(stream.next().await.is_none());
+}
+
+#[async_std::test]
+pub async fn test_subscription_ws_transport_keep_alive() {
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {}
+
+    let schema = Schema::new(QueryRoot, EmptyMutation, EmptySubscription);
+    let (mut tick_tx, tick_rx) = futures::channel::mpsc::channel(1);
+    let (mut sink, stream) = schema.subscription_connection(
+        WebSocketTransport::default().keep_alive_interval(Some(std::time::Duration::from_secs(0))),
+    );
+    let mut stream = stream.timer(tick_rx);
+
+    sink.send(
+        serde_json::to_vec(&serde_json::json!({
+            "type": "connection_init",
+        }))
+        .unwrap()
+        .into(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "connection_ack",
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+
+    for _ in 0..2 {
+        tick_tx.send(()).await.unwrap();
+        assert_eq!(
+            Some(serde_json::json!({
+            "type": "ka",
+            })),
+            serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+        );
+    }
+
+    // the first `ka` is sent at the first tick after `connection_ack`, then once per interval
+    let (mut tick_tx, tick_rx) = futures::channel::mpsc::channel(1);
+    let (mut sink, stream) = schema.subscription_connection(WebSocketTransport::default());
+    let mut stream = stream.timer(tick_rx);
+
+    sink.send(
+        serde_json::to_vec(&serde_json::json!({
+            "type": "connection_init",
+        }))
+        .unwrap()
+        .into(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "connection_ack",
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+
+    tick_tx.send(()).await.unwrap();
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "ka",
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+    tick_tx.send(()).await.unwrap();
+    assert!(stream.next().now_or_never().is_none());
+}
+
+#[async_std::test]
+pub async fn test_subscription_ws_transport_init_timeout() {
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {}
+
+    let schema = Schema::new(QueryRoot, EmptyMutation, EmptySubscription);
+    let (_sink, stream) = schema.subscription_connection(
+        WebSocketTransport::default()
+            .connection_init_timeout(Some(std::time::Duration::from_secs(0))),
+    );
+    let mut stream = stream.timer(futures::stream::iter(vec![()]));
+    assert!(stream.next().await.is_none());
+}
+
+#[async_std::test]
+pub async fn test_subscription_ws_transport_hooks() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    struct Token(String);
+
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {}
+
+    struct SubscriptionRoot;
+
+    #[Subscription]
+    impl SubscriptionRoot {
+        async fn values(&self, ctx: &Context<'_>, n: i32) -> impl Stream<Item = String> {
+            let token = ctx.data::<Token>().0.clone();
+            futures::stream::iter(0..n).map(move |i| format!("{}:{}", token, i))
+        }
+    }
+
+    let disconnected = Arc::new(AtomicUsize::new(0));
+    let hooks = WebSocketHooks::default()
+        .on_connect(|payload| async move {
+            let token = payload["token"].as_str().unwrap_or_default().to_string();
+            if token.is_empty() {
+                return Err(FieldError(
+                    "missing token".to_string(),
+                    Some(serde_json::json!({ "code": 401 })),
+                ));
+            }
+            let mut data = Data::default();
+            data.insert(Token(token));
+            Ok(data)
+        })
+        .on_operation(|data, mut request| async move {
+            if request.operation_name.as_deref() == Some("Forbidden") {
+                return Err(format!("{} can't do that", data.get::<Token>().unwrap().0).into());
+            }
+            request.variables = Some(serde_json::json!({ "n": 2 }));
+            Ok(request)
+        })
+        .on_disconnect({
+            let disconnected = disconnected.clone();
+            move |data| {
+                assert_eq!(data.get::<Token>().unwrap().0, "123456");
+                disconnected.fetch_add(1, Ordering::SeqCst);
+                async {}
+            }
+        });
+
+    let schema = Schema::new(QueryRoot, EmptyMutation, SubscriptionRoot);
+
+    // Rejected by on_connect
+    let (mut sink, mut stream) =
+        schema.subscription_connection(WebSocketTransport::default().hooks(hooks.clone()));
+    sink.send(
+        serde_json::to_vec(&serde_json::json!({ "type": "connection_init" }))
+            .unwrap()
+            .into(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "connection_error",
+        "payload": { "message": "missing token", "extensions": { "code": 401 } },
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+    drop(sink);
+    assert!(stream.next().await.is_none());
+    assert_eq!(disconnected.load(Ordering::SeqCst), 0);
+
+    // Accepted by on_connect
+    let (mut sink, mut stream) =
+        schema.subscription_connection(WebSocketTransport::default().hooks(hooks));
+    sink.send(
+        serde_json::to_vec(&serde_json::json!({
+            "type": "connection_init",
+            "payload": { "token": "123456" }
+        }))
+        .unwrap()
+        .into(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "connection_ack",
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+
+    sink.send(
+        serde_json::to_vec(&serde_json::json!({
+            "type": "start",
+            "id": "1",
+            "payload": {
+                "query": "subscription Forbidden { values(n: 1) }",
+                "operationName": "Forbidden"
+            },
+        }))
+        .unwrap()
+        .into(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "error",
+        "id": "1",
+        "payload": [{ "message": "123456 can't do that" }],
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+
+    sink.send(
+        serde_json::to_vec(&serde_json::json!({
+            "type": "start",
+            "id": "2",
+            "payload": {
+                "query": "subscription($n: Int!) { values(n: $n) }"
+            },
+        }))
+        .unwrap()
+        .into(),
+    )
+    .await
+    .unwrap();
+    for i in 0..2 {
+        assert_eq!(
+            Some(serde_json::json!({
+            "type": "data",
+            "id": "2",
+            "payload": { "data": { "values": format!("123456:{}", i) } },
+            })),
+            serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+        );
+    }
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "complete",
+        "id": "2",
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+
+    drop(sink);
+    assert!(stream.next().await.is_none());
+    assert_eq!(disconnected.load(Ordering::SeqCst), 1);
+}
+
+#[async_std::test]
+pub async fn test_subscription_ws_transport_query_and_mutation() {
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    struct Token(String);
+
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {
+        async fn token(&self, ctx: &Context<'_>) -> String {
+            ctx.data::<Token>().0.clone()
+        }
+    }
+
+    struct MutationRoot;
+
+    #[Object]
+    impl MutationRoot {
+        async fn add(&self, ctx: &Context<'_>, n: i32) -> i32 {
+            ctx.data::<AtomicI32>().fetch_add(n, Ordering::SeqCst) + n
+        }
+    }
+
+    let schema = Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+        .data(AtomicI32::new(0))
+        .finish();
+    let (mut sink, mut stream) = schema.subscription_connection(WebSocketTransport::new(|value| {
+        #[derive(serde_derive::Deserialize)]
+        struct Payload {
+            token: String,
+        }
+
+        let payload: Payload = serde_json::from_value(value).unwrap();
+        let mut data = Data::default();
+        data.insert(Token(payload.token));
+        Ok(data)
+    }));
+
+    sink.send(
+        serde_json::to_vec(&serde_json::json!({
+            "type": "connection_init",
+            "payload": { "token": "123456" }
+        }))
+        .unwrap()
+        .into(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "connection_ack",
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+
+    sink.send(
+        serde_json::to_vec(&serde_json::json!({
+            "type": "start",
+            "id": "1",
+            "payload": {
+                "query": "{ token }"
+            },
+        }))
+        .unwrap()
+        .into(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "data",
+        "id": "1",
+        "payload": { "data": { "token": "123456" } },
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "complete",
+        "id": "1",
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+
+    sink.send(
+        serde_json::to_vec(&serde_json::json!({
+            "type": "start",
+            "id": "2",
+            "payload": {
+                "query": "query A { token } mutation B($n: Int!) { add(n: $n) }",
+                "operationName": "B",
+                "variables": { "n": 5 }
+            },
+        }))
+        .unwrap()
+        .into(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "data",
+        "id": "2",
+        "payload": { "data": { "add": 5 } },
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "complete",
+        "id": "2",
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+}
+
+#[async_std::test]
+pub async fn test_subscription_graphql_transport_ws_query() {
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {
+        async fn value(&self) -> i32 {
+            10
+        }
+    }
+
+    let schema = Schema::new(QueryRoot, EmptyMutation, EmptySubscription);
+    let (mut sink, mut stream) = schema.subscription_connection(GraphQLTransportWS::default());
+
+    sink.send(
+        serde_json::to_vec(&serde_json::json!({ "type": "connection_init" }))
+            .unwrap()
+            .into(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "connection_ack",
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+
+    sink.send(
+        serde_json::to_vec(&serde_json::json!({
+            "type": "subscribe",
+            "id": "1",
+            "payload": {
+                "query": "{ value }"
+            },
+        }))
+        .unwrap()
+        .into(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "next",
+        "id": "1",
+        "payload": { "data": { "value": 10 } },
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "complete",
+        "id": "1",
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+}
+
+#[async_std::test]
+pub async fn test_subscription_ws_transport_pending_request() {
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {}
+
+    struct SubscriptionRoot;
+
+    #[Subscription]
+    impl SubscriptionRoot {
+        async fn values(&self, n: i32) -> impl Stream<Item = i32> {
+            futures::stream::iter(0..n)
+        }
+    }
+
+    let schema = Schema::new(QueryRoot, EmptyMutation, SubscriptionRoot);
+    let hooks = WebSocketHooks::default().on_operation(|_, request| async move {
+        async_std::task::sleep(std::time::Duration::from_millis(10)).await;
+        Ok(request)
+    });
+    let (mut sink, mut stream) =
+        schema.subscription_connection(WebSocketTransport::default().hooks(hooks));
+
+    let mut messages = vec![serde_json::json!({ "type": "connection_init" })];
+    for id in 0..3 {
+        messages.push(serde_json::json!({
+            "type": "start",
+            "id": id.to_string(),
+            "payload": {
+                "query": "subscription { values(n: 3) }",
+            },
+        }));
+    }
+    for msg in messages {
+        sink.send(serde_json::to_vec(&msg).unwrap().into())
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "connection_ack",
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+
+    let mut data = std::collections::HashMap::<String, Vec<i64>>::new();
+    let mut completed = 0;
+    while completed < 3 {
+        let msg: serde_json::Value = serde_json::from_slice(&stream.next().await.unwrap()).unwrap();
+        let id = msg["id"].as_str().unwrap().to_string();
+        match msg["type"].as_str().unwrap() {
+            "data" => data
+                .entry(id)
+                .or_default()
+                .push(msg["payload"]["data"]["values"].as_i64().unwrap()),
+            "complete" => {
+                assert_eq!(data[&id], vec![0, 1, 2]);
+                completed += 1;
+            }
+            ty => panic!("unexpected message: {}", ty),
+        }
+    }
+
+    drop(sink);
+    assert!(stream.next().await.is_none());
+}
+
+#[async_std::test]
+pub async fn test_subscription_ws_transport_slow_hook() {
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {}
+
+    struct SubscriptionRoot;
+
+    #[Subscription]
+    impl SubscriptionRoot {
+        async fn values(
+            &self,
+            ctx: &Context<'_>,
+        ) -> FieldResult<impl Stream<Item = FieldResult<i32>>> {
+            ctx.data::<MemoryBroker<i32>>().subscribe("values").await
+        }
+    }
+
+    let broker = MemoryBroker::<i32>::new(16);
+    let schema = Schema::build(QueryRoot, EmptyMutation, SubscriptionRoot)
+        .data(broker.clone())
+        .finish();
+    let hooks = WebSocketHooks::default().on_operation(|_, request| async move {
+        if request.operation_name.as_deref() == Some("Slow") {
+            futures::future::pending::<()>().await;
+        }
+        Ok(request)
+    });
+    let (mut tick_tx, tick_rx) = futures::channel::mpsc::channel(1);
+    let (mut sink, stream) = schema.subscription_connection(
+        WebSocketTransport::default()
+            .hooks(hooks)
+            .keep_alive_interval(Some(std::time::Duration::from_secs(0))),
+    );
+    let mut stream = stream.timer(tick_rx);
+
+    for msg in &[
+        serde_json::json!({ "type": "connection_init" }),
+        serde_json::json!({
+            "type": "start",
+            "id": "1",
+            "payload": { "query": "subscription { values }" },
+        }),
+        serde_json::json!({
+            "type": "start",
+            "id": "2",
+            "payload": { "query": "subscription Slow { values }", "operationName": "Slow" },
+        }),
+    ] {
+        sink.send(serde_json::to_vec(msg).unwrap().into())
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "connection_ack",
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+
+    // the hook of the second operation is pending, the events and the keep-alives still flow
+    assert!(stream.next().now_or_never().is_none());
+    broker.publish("values", 1).await.unwrap();
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "data",
+        "id": "1",
+        "payload": { "data": { "values": 1 } },
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+    tick_tx.send(()).await.unwrap();
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "ka",
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+
+    // stopping the operation cancels the hook
+    sink.send(
+        serde_json::to_vec(&serde_json::json!({ "type": "stop", "id": "2" }))
+            .unwrap()
+            .into(),
+    )
+    .await
+    .unwrap();
+    broker.publish("values", 2).await.unwrap();
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "data",
+        "id": "1",
+        "payload": { "data": { "values": 2 } },
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+    assert!(stream.next().now_or_never().is_none());
+}
+
+#[async_std::test]
+pub async fn test_subscription_ws_transport_fairness() {
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {}
+
+    struct SubscriptionRoot;
+
+    #[Subscription]
+    impl SubscriptionRoot {
+        async fn values(&self, n: i32) -> impl Stream<Item = i32> {
+            futures::stream::repeat(n)
+        }
+    }
+
+    let schema = Schema::new(QueryRoot, EmptyMutation, SubscriptionRoot);
+    let (mut sink, mut stream) = schema.subscription_connection(WebSocketTransport::default());
+
+    let mut messages = vec![serde_json::json!({ "type": "connection_init" })];
+    for id in 1..=2 {
+        messages.push(serde_json::json!({
+            "type": "start",
+            "id": id.to_string(),
+            "payload": {
+                "query": format!("subscription {{ values(n: {}) }}", id),
+            },
+        }));
+    }
+    for msg in messages {
+        sink.send(serde_json::to_vec(&msg).unwrap().into())
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(
+        Some(serde_json::json!({
+        "type": "connection_ack",
+        })),
+        serde_json::from_slice(&stream.next().await.unwrap()).unwrap()
+    );
+
+    // both streams are always ready, once the second one is started they take turns
+    let mut ids = Vec::new();
+    for _ in 0..20 {
+        let msg: serde_json::Value = serde_json::from_slice(&stream.next().await.unwrap()).unwrap();
+        assert_eq!(msg["type"], "data");
+        assert_eq!(
+            msg["payload"]["data"]["values"]
+                .as_i64()
+                .unwrap()
+                .to_string(),
+            msg["id"].as_str().unwrap()
+        );
+        ids.push(msg["id"].as_str().unwrap().to_string());
+    }
+    let start = ids
+        .iter()
+        .position(|id| id == "2")
+        .expect("the second subscription is starved");
+    assert!(start < 10);
+    for pair in ids[start..].windows(2) {
+        assert_ne!(pair[0], pair[1]);
+    }
+}
+
+#[async_std::test]
+pub async fn test_serve_websocket() {
+    use async_graphql::http::{serve_websocket, WebSocketMessage};
+    use futures::channel::mpsc;
+
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {}
+
+    struct SubscriptionRoot;
+
+    #[Subscription]
+    impl SubscriptionRoot {
+        async fn values(&self) -> impl Stream<Item = i32> {
+            futures::stream::iter(0..10)
+        }
+    }
+
+    let schema = Schema::new(QueryRoot, EmptyMutation, SubscriptionRoot);
+    let (tx, stream) = mpsc::unbounded();
+    let mut stream = stream.map(|msg| match msg {
+        WebSocketMessage::Text(text) => text,
+        msg => panic!("unexpected message: {:?}", msg),
+    });
+    let (sink, rx) = mpsc::unbounded();
+    let server = async_std::task::spawn(serve_websocket(
+        schema,
+        GraphQLTransportWS::default(),
+        tx,
+        rx,
+        futures::stream::pending(),
+    ));
+
+    sink.unbounded_send(WebSocketMessage::Text(
+        serde_json::json!({ "type": "connection_init" }).to_string(),
+    ))
+    .unwrap();
+    assert_eq!(
+        serde_json::json!({ "type": "connection_ack" }),
+        serde_json::from_str::<serde_json::Value>(&stream.next().await.unwrap()).unwrap()
+    );
+
+    sink.unbounded_send(WebSocketMessage::Text(
+        serde_json::json!({
+            "type": "subscribe",
+            "id": "1",
+            "payload": { "query": "subscription { values }" },
+        })
+        .to_string(),
+    ))
+    .unwrap();
+    for i in 0..10 {
+        assert_eq!(
+            serde_json::json!({
+                "type": "next",
+                "id": "1",
+                "payload": { "data": { "values": i } },
+            }),
+            serde_json::from_str::<serde_json::Value>(&stream.next().await.unwrap()).unwrap()
+        );
+    }
+    assert_eq!(
+        serde_json::json!({ "type": "complete", "id": "1" }),
+        serde_json::from_str::<serde_json::Value>(&stream.next().await.unwrap()).unwrap()
+    );
+
+    // the server closes the sink when the client closes the connection
+    sink.unbounded_send(WebSocketMessage::Close(None)).unwrap();
+    server.await;
+    assert!(stream.next().await.is_none());
+}
+
+#[async_std::test]
+pub async fn test_serve_websocket_backpressure() {
+    use async_graphql::http::{serve_websocket, WebSocketMessage};
+    use futures::channel::mpsc;
+
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {}
+
+    struct SubscriptionRoot;
+
+    #[Subscription]
+    impl SubscriptionRoot {
+        async fn values(&self) -> impl Stream<Item = i32> {
+            futures::stream::iter(0..1)
+        }
+    }
+
+    let schema = Schema::new(QueryRoot, EmptyMutation, SubscriptionRoot);
+    let hooks = WebSocketHooks::default().on_connect(|_| async move {
+        async_std::task::sleep(std::time::Duration::from_millis(50)).await;
+        Ok(Data::default())
+    });
+    let (tx, stream) = mpsc::unbounded();
+    let mut stream = stream.map(|msg| match msg {
+        WebSocketMessage::Text(text) => text,
+        msg => panic!("unexpected message: {:?}", msg),
+    });
+    let (sink, rx) = mpsc::unbounded();
+    let server = async_std::task::spawn(serve_websocket(
+        schema,
+        GraphQLTransportWS::default().hooks(hooks),
+        tx,
+        rx,
+        futures::stream::pending(),
+    ));
+
+    // more messages than the capacity of the connection channel, sent while `on_connect` is running
+    sink.unbounded_send(WebSocketMessage::Text(
+        serde_json::json!({ "type": "connection_init" }).to_string(),
+    ))
+    .unwrap();
+    for id in 0..32 {
+        sink.unbounded_send(WebSocketMessage::Text(
+            serde_json::json!({
+                "type": "subscribe",
+                "id": id.to_string(),
+                "payload": { "query": "subscription { values }" },
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    }
+
+    let responses = async {
+        assert_eq!(
+            serde_json::json!({ "type": "connection_ack" }),
+            serde_json::from_str::<serde_json::Value>(&stream.next().await.unwrap()).unwrap()
+        );
+        let mut completed = 0;
+        while completed < 32 {
+            let msg: serde_json::Value =
+                serde_json::from_str(&stream.next().await.unwrap()).unwrap();
+            if msg["type"] == "complete" {
+                completed += 1;
+            }
+        }
+    };
+    async_std::future::timeout(std::time::Duration::from_secs(5), responses)
+        .await
+        .expect("the connection is deadlocked");
+
+    sink.unbounded_send(WebSocketMessage::Close(None)).unwrap();
+    server.await;
+}
+
+#[async_std::test]
+pub async fn test_serve_websocket_close_frame() {
+    use async_graphql::http::{serve_websocket, WebSocketMessage};
+    use futures::channel::mpsc;
+
+    struct QueryRoot;
+
+    #[Object]
+    impl QueryRoot {}
+
+    let schema = Schema::new(QueryRoot, EmptyMutation, EmptySubscription);
+    let (tx, stream) = mpsc::unbounded();
+    let (sink, rx) = mpsc::unbounded();
+    let server = async_std::task::spawn(serve_websocket(
+        schema,
+        GraphQLTransportWS::default(),
+        tx,
+        rx,
+        futures::stream::pending(),
+    ));
+
+    sink.unbounded_send(WebSocketMessage::Text(
+        serde_json::json!({
+            "type": "subscribe",
+            "id": "1",
+            "payload": { "query": "{ __typename }" },
+        })
+        .to_string(),
+    ))
+    .unwrap();
+    server.await;
+    assert_eq!(
+        stream.collect::<Vec<_>>().await,
+        vec![WebSocketMessage::Close(Some(CloseFrame::new(
+            4401,
+            "Unauthorized"
+        )))]
+    );
+}
@@ -0,0 +1,108 @@
+use async_graphql::http::{MemoryUploadStorage, UploadStorage};
+use async_graphql::*;
+use futures::AsyncReadExt;
+use std::sync::Arc;
+
+struct QueryRoot;
+
+#[Object]
+impl QueryRoot {}
+
+struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    async fn upload(&self, ctx: &Context<'_>, files: Vec<Upload>) -> FieldResult<Vec<String>> {
+        let mut contents = Vec::new();
+        for file in files {
+            let file = file.value(ctx)?;
+            let mut content = String::new();
+            file.reader()?.read_to_string(&mut content).await?;
+            contents.push(format!(
+                "{}:{}:{}",
+                file.filename,
+                file.content_type.as_deref().unwrap_or_default(),
+                content
+            ));
+        }
+        Ok(contents)
+    }
+}
+
+const BODY: &str = "--xyz\r\n\
+     Content-Disposition: form-data; name=\"operations\"\r\n\r\n\
+     { \"query\": \"mutation($files: [Upload!]!) { upload(files: $files) }\", \"variables\": { \"files\": [null, null] } }\
+     --xyz\r\n\
+     Content-Disposition: form-data; name=\"map\"\r\n\r\n\
+     { \"0\": [\"variables.files.0\", \"variables.files.1\"] }\
+     --xyz\r\n\
+     Content-Disposition: form-data; name=\"0\"; filename=\"a.txt\"\r\n\
+     Content-Type: text/plain\r\n\r\n\
+     hello\
+     --xyz--\r\n";
+
+#[async_std::test]
+pub async fn test_upload() {
+    let schema = Schema::new(QueryRoot, MutationRoot, EmptySubscription);
+    let expected = serde_json::json!({
+        "upload": ["a.txt:text/plain:hello", "a.txt:text/plain:hello"]
+    });
+
+    let builder = (Some("multipart/form-data; boundary=xyz"), BODY.as_bytes())
+        .into_query_builder()
+        .await
+        .unwrap();
+    assert_eq!(builder.execute(&schema).await.unwrap().data, expected);
+
+    let opts = IntoQueryBuilderOpts {
+        upload_storage: Some(Arc::new(MemoryUploadStorage)),
+        ..IntoQueryBuilderOpts::default()
+    };
+    let builder = (Some("multipart/form-data; boundary=xyz"), BODY.as_bytes())
+        .into_query_builder_opts(&opts)
+        .await
+        .unwrap();
+    assert_eq!(builder.execute(&schema).await.unwrap().data, expected);
+
+    let opts = IntoQueryBuilderOpts {
+        allowed_content_types: Some(vec!["image/*".to_string()]),
+        ..IntoQueryBuilderOpts::default()
+    };
+    let res = (Some("multipart/form-data; boundary=xyz"), BODY.as_bytes())
+        .into_query_builder_opts(&opts)
+        .await;
+    assert!(matches!(
+        res,
+        Err(ParseRequestError::ContentTypeNotAllowed(_))
+    ));
+}
+
+#[async_std::test]
+pub async fn test_upload_forged() {
+    let schema = Schema::new(QueryRoot, MutationRoot, EmptySubscription);
+    // only the first file is mapped, the second one is sent as a JSON string
+    let body = BODY
+        .replace("[null, null]", "[null, \"file:a.txt|0\"]")
+        .replace(", \"variables.files.1\"", "");
+    let builder = (Some("multipart/form-data; boundary=xyz"), body.as_bytes())
+        .into_query_builder()
+        .await
+        .unwrap();
+    assert!(builder.execute(&schema).await.is_err());
+
+    let builder = QueryBuilder::new(r#"mutation { upload(files: ["file:a.txt|0"]) }"#);
+    assert!(builder.execute(&schema).await.is_err());
+
+    // a file can only be read from the variable that it's set to
+    let mut builder = QueryBuilder::new("mutation($files: [Upload!]!) { upload(files: $files) }")
+        .variables(
+            Variables::parse_from_json(serde_json::json!({ "files": [null], "other": null }))
+                .unwrap(),
+        );
+    let content = MemoryUploadStorage
+        .store("a.txt", None, &mut "hello".as_bytes())
+        .await
+        .unwrap();
+    builder.set_upload("variables.other", "a.txt", None, content.into());
+    assert!(builder.execute(&schema).await.is_err());
+}